@@ -1,7 +1,17 @@
 //! Crypto domain types shared between the REST market data API and the
 //! streaming crypto feed.
 
-use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::error::{Error, Result};
+
+/// Quote currencies Alpaca lists crypto pairs against, longest first so
+/// `USDT` and `USDC` win over `USD` when splitting a compact symbol.
+const QUOTE_CURRENCIES: [&str; 5] = ["USDT", "USDC", "USD", "EUR", "BTC"];
 
 /// Side that initiated a crypto trade.
 ///
@@ -18,9 +28,185 @@ pub enum CryptoTakerSide {
     Seller,
 }
 
+/// A crypto trading pair such as `BTC/USD`: base and quote currency.
+///
+/// Market data and the crypto stream name pairs with a slash (`BTC/USD`),
+/// which is what [`Display`](fmt::Display), [`as_str`](Self::as_str) and
+/// serialization produce. Positions and asset lookups use the compact form
+/// (`BTCUSD`) from [`compact`](Self::compact); orders accept either. Both
+/// forms parse, and lowercase input is normalized to uppercase. Accepted
+/// anywhere the crate takes a crypto symbol.
+///
+/// ```
+/// # use oxidized_alpaca::CryptoPair;
+/// let pair: CryptoPair = "ETHUSDT".parse().unwrap();
+/// assert_eq!(pair.base(), "ETH");
+/// assert_eq!(pair.quote(), "USDT");
+/// assert_eq!(pair.to_string(), "ETH/USDT");
+/// assert_eq!(pair.compact(), "ETHUSDT");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CryptoPair {
+    base: String,
+    quote: String,
+    symbol: String,
+}
+
+impl CryptoPair {
+    /// Build a pair from its base and quote currencies.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidCryptoPair`] if either currency is empty or
+    /// contains anything other than ASCII letters and digits.
+    pub fn new(base: &str, quote: &str) -> Result<Self> {
+        let symbol = format!("{base}/{quote}").to_ascii_uppercase();
+        let invalid = |reason| Error::InvalidCryptoPair {
+            symbol: symbol.clone(),
+            reason,
+        };
+        if base.is_empty() || quote.is_empty() {
+            return Err(invalid("base and quote currencies must not be empty"));
+        }
+        if !base
+            .bytes()
+            .chain(quote.bytes())
+            .all(|b| b.is_ascii_alphanumeric())
+        {
+            return Err(invalid("currencies must be letters or digits"));
+        }
+        Ok(Self {
+            base: base.to_ascii_uppercase(),
+            quote: quote.to_ascii_uppercase(),
+            symbol,
+        })
+    }
+
+    /// Parse a slashed (`BTC/USD`) or compact (`BTCUSD`) pair symbol.
+    ///
+    /// The compact form is split on a known quote currency (`USD`, `USDT`,
+    /// `USDC`, `EUR` or `BTC`).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidCryptoPair`] if `symbol` is not a valid pair.
+    pub fn parse(symbol: &str) -> Result<Self> {
+        let invalid = |reason| Error::InvalidCryptoPair {
+            symbol: symbol.to_string(),
+            reason,
+        };
+        let (base, quote) = match symbol.split_once('/') {
+            Some(parts) => parts,
+            None => {
+                let upper = symbol.to_ascii_uppercase();
+                let quote = QUOTE_CURRENCIES
+                    .into_iter()
+                    .find(|quote| upper.len() > quote.len() && upper.ends_with(quote))
+                    .ok_or_else(|| {
+                        invalid("compact symbol does not end in a known quote currency")
+                    })?;
+                symbol.split_at(symbol.len() - quote.len())
+            }
+        };
+        Self::new(base, quote).map_err(|e| match e {
+            Error::InvalidCryptoPair { reason, .. } => invalid(reason),
+            other => other,
+        })
+    }
+
+    /// The slashed symbol (`BTC/USD`) used by market data and streaming.
+    pub fn as_str(&self) -> &str {
+        &self.symbol
+    }
+
+    /// The compact symbol (`BTCUSD`) used by positions and asset lookups.
+    pub fn compact(&self) -> String {
+        format!("{}{}", self.base, self.quote)
+    }
+
+    /// The currency being bought or sold, e.g. `BTC`.
+    pub fn base(&self) -> &str {
+        &self.base
+    }
+
+    /// The currency the price is quoted in, e.g. `USD`.
+    pub fn quote(&self) -> &str {
+        &self.quote
+    }
+}
+
+/// `symbol` in the form positions report it: compact for a slashed crypto
+/// pair (`BTC/USD` becomes `BTCUSD`), unchanged otherwise. Lets symbols
+/// from orders, assets and positions be compared whichever form each uses.
+/// Compact input is left alone, since `BTCUSD` can't be told apart from an
+/// equity ticker by its shape.
+pub(crate) fn position_symbol(symbol: &str) -> Cow<'_, str> {
+    if symbol.contains('/')
+        && let Ok(pair) = CryptoPair::parse(symbol)
+    {
+        Cow::Owned(pair.compact())
+    } else {
+        Cow::Borrowed(symbol)
+    }
+}
+
+impl fmt::Display for CryptoPair {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.symbol)
+    }
+}
+
+impl FromStr for CryptoPair {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::parse(s)
+    }
+}
+
+impl TryFrom<&str> for CryptoPair {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Self> {
+        Self::parse(value)
+    }
+}
+
+impl AsRef<str> for CryptoPair {
+    fn as_ref(&self) -> &str {
+        &self.symbol
+    }
+}
+
+impl From<CryptoPair> for String {
+    fn from(value: CryptoPair) -> Self {
+        value.symbol
+    }
+}
+
+impl From<&CryptoPair> for String {
+    fn from(value: &CryptoPair) -> Self {
+        value.symbol.clone()
+    }
+}
+
+impl Serialize for CryptoPair {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.symbol)
+    }
+}
+
+impl<'de> Deserialize<'de> for CryptoPair {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let symbol = String::deserialize(deserializer)?;
+        Self::parse(&symbol).map_err(serde::de::Error::custom)
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::CryptoTakerSide;
+    use super::{CryptoPair, CryptoTakerSide};
+    use crate::Error;
 
     #[test]
     fn taker_side_round_trips_single_character_codes() {
@@ -35,4 +221,57 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn pair_parses_slashed_and_compact_forms() {
+        for (input, base, quote) in [
+            ("BTC/USD", "BTC", "USD"),
+            ("BTCUSD", "BTC", "USD"),
+            ("btc/usd", "BTC", "USD"),
+            ("ETHUSDT", "ETH", "USDT"),
+            ("SOLUSDC", "SOL", "USDC"),
+            ("ETHBTC", "ETH", "BTC"),
+            ("BTCEUR", "BTC", "EUR"),
+            ("1INCH/USD", "1INCH", "USD"),
+        ] {
+            let pair = CryptoPair::parse(input).unwrap();
+            assert_eq!((pair.base(), pair.quote()), (base, quote), "{input}");
+            assert_eq!(pair.as_str(), format!("{base}/{quote}"));
+            assert_eq!(pair.compact(), format!("{base}{quote}"));
+        }
+        assert_eq!(
+            CryptoPair::parse("BTCUSD").unwrap(),
+            CryptoPair::new("BTC", "USD").unwrap()
+        );
+    }
+
+    #[test]
+    fn pair_rejects_malformed_symbols() {
+        for input in [
+            "",
+            "USD",
+            "BTCJPY",
+            "/USD",
+            "BTC/",
+            "BTC-USD/USD",
+            "BTC/US D",
+        ] {
+            assert!(
+                matches!(
+                    CryptoPair::parse(input),
+                    Err(Error::InvalidCryptoPair { ref symbol, .. }) if symbol == input
+                ),
+                "{input} should be rejected",
+            );
+        }
+    }
+
+    #[test]
+    fn pair_serializes_slashed_and_deserializes_either_form() {
+        let pair: CryptoPair = serde_json::from_str("\"BTCUSD\"").unwrap();
+        assert_eq!(serde_json::to_string(&pair).unwrap(), "\"BTC/USD\"");
+        let round_tripped: CryptoPair = serde_json::from_str("\"BTC/USD\"").unwrap();
+        assert_eq!(round_tripped, pair);
+        assert!(serde_json::from_str::<CryptoPair>("\"BTC\"").is_err());
+    }
 }
@@ -205,6 +205,104 @@ pub enum Error {
     #[cfg(feature = "streaming")]
     #[error("streaming error: {0:?}")]
     StreamingError(crate::streaming::StreamError),
+    /// The streaming connection went quiet and did not answer a liveness
+    /// probe within the configured
+    /// [`HeartbeatConfig`](crate::streaming::HeartbeatConfig) deadlines.
+    ///
+    /// Carries how long the connection had been silent. The socket is most
+    /// likely half-open; the caller should shut the client down and
+    /// reconnect.
+    #[cfg(feature = "streaming")]
+    #[error("streaming connection stale: no frames received for {0:?}")]
+    StreamingStale(std::time::Duration),
+    /// An order update would move the order through a status transition
+    /// that [`OrderStatus::can_transition_to`](crate::orders::OrderStatus::can_transition_to)
+    /// rejects. The update was not applied.
+    #[cfg(feature = "streaming")]
+    #[error("illegal order transition for {order_id}: {from:?} -> {to:?}")]
+    IllegalOrderTransition {
+        /// The order the update applies to.
+        order_id: crate::OrderId,
+        /// Status currently on record.
+        from: crate::orders::OrderStatus,
+        /// Status the update tried to move to.
+        to: crate::orders::OrderStatus,
+    },
+    /// Reading or writing a stream recording failed.
+    #[cfg(feature = "streaming")]
+    #[error("recording I/O error: {0}")]
+    RecordingIo(#[source] std::io::Error),
+    /// A line of a stream recording could not be encoded or decoded.
+    #[cfg(feature = "streaming")]
+    #[error("recording format error at line {line}: {source}")]
+    RecordingFormat {
+        /// One-based line number in the recording (0 when writing).
+        line: u64,
+        /// The underlying JSON error.
+        #[source]
+        source: serde_json::Error,
+    },
+    /// A string or set of parts is not a valid OCC option symbol.
+    #[error("invalid option symbol {symbol:?}: {reason}")]
+    InvalidOptionSymbol {
+        /// The rejected symbol.
+        symbol: String,
+        /// What is wrong with it.
+        reason: &'static str,
+    },
+    /// A string or pair of currencies is not a valid crypto pair symbol.
+    #[error("invalid crypto pair {symbol:?}: {reason}")]
+    InvalidCryptoPair {
+        /// The rejected symbol.
+        symbol: String,
+        /// What is wrong with it.
+        reason: &'static str,
+    },
+    /// An exercise or do-not-exercise instruction was due after the
+    /// [`ExercisePolicy`](crate::restful::trading::option_lifecycle::ExercisePolicy)
+    /// cutoff on expiration day. Nothing further was sent.
+    #[cfg(feature = "restful")]
+    #[error("exercise instruction for {symbol} is past the {deadline} cutoff")]
+    ExerciseCutoffPassed {
+        /// The option contract symbol.
+        symbol: String,
+        /// When instructions stopped being accepted.
+        deadline: chrono::DateTime<chrono::Utc>,
+    },
+    /// [`TradingClient::apply_exercise_policy`](crate::TradingClient::apply_exercise_policy)
+    /// failed after some instructions had already gone through.
+    #[cfg(feature = "restful")]
+    #[error("exercise policy stopped after {} instructions: {source}", applied.len())]
+    ExercisePolicyIncomplete {
+        /// The instructions sent before the failure.
+        applied: Vec<crate::restful::trading::option_lifecycle::AppliedInstruction>,
+        /// Why the next instruction was not sent.
+        source: Box<Error>,
+    },
+    /// An option settlement cannot be booked as one stock delivery: the
+    /// contract is adjusted, settles in cash, or delivers several
+    /// securities.
+    #[cfg(feature = "restful")]
+    #[error("option {symbol} does not have a standard stock deliverable")]
+    NonStandardDeliverable {
+        /// The option contract symbol.
+        symbol: String,
+    },
+    /// The contracts passed to an
+    /// [`OptionStrategy`](crate::restful::trading::option_strategies::OptionStrategy)
+    /// constructor do not form that strategy, or a leg could not be priced.
+    #[cfg(feature = "restful")]
+    #[error("invalid option strategy: {0}")]
+    InvalidStrategy(String),
+    /// No volatility reproduces the given option price, or the price is
+    /// unusable as a solver input.
+    #[error("cannot solve implied volatility for price {price}: {reason}")]
+    ImpliedVolatility {
+        /// The option price the solver was given.
+        price: f64,
+        /// Why no volatility was found.
+        reason: &'static str,
+    },
     /// A time-frame multiplier was outside the documented valid range.
     #[cfg(feature = "restful")]
     #[error("invalid timeframe: {amount}{unit} is outside the valid range {valid_range}")]
@@ -216,6 +314,42 @@ pub enum Error {
         /// Human-readable description of the valid range.
         valid_range: &'static str,
     },
+    /// An order failed pre-trade validation and was not sent. Carries every
+    /// [`OrderViolation`](crate::restful::trading::order_validation::OrderViolation)
+    /// found.
+    #[cfg(feature = "restful")]
+    #[error("invalid order: {0:?}")]
+    InvalidOrder(Vec<crate::restful::trading::order_validation::OrderViolation>),
+    /// A [`RiskGuard`](crate::restful::trading::risk_guard::RiskGuard)
+    /// limit stopped an order before it was sent.
+    #[cfg(feature = "restful")]
+    #[error("risk limit breached: {0}")]
+    RiskLimit(crate::restful::trading::risk_guard::RiskViolation),
+    /// A [dry-run](crate::TradingClient::with_dry_run) client intercepted
+    /// a mutating request it cannot synthesize a response for. Nothing was
+    /// sent to Alpaca.
+    #[cfg(feature = "restful")]
+    #[error("dry run cannot answer {method} {path}")]
+    DryRunUnsupported {
+        /// HTTP method of the intercepted request.
+        method: String,
+        /// Request path, e.g. `"v2/orders"`.
+        path: String,
+        /// Why the response could not be built, when it failed to encode
+        /// or decode.
+        #[source]
+        source: Option<JsonError>,
+    },
+    /// A [`LiveSafety`](crate::restful::LiveSafety) interlock refused a
+    /// mutating call on a live account. Nothing was sent.
+    #[cfg(feature = "restful")]
+    #[error("live safety interlock: {0}")]
+    LiveSafety(crate::restful::LiveSafetyViolation),
+    /// A REST request did not complete within the caller's deadline. The
+    /// request may or may not have reached Alpaca.
+    #[cfg(feature = "restful")]
+    #[error("request timed out after {0:?}")]
+    RequestTimeout(std::time::Duration),
 }
 
 /// A `Result` type alias using [`enum@Error`] as the default error type.
@@ -22,6 +22,9 @@ const CRYPTO_EU_KRAKEN_URL: &str = "wss://stream.data.alpaca.markets/v1beta3/cry
 const CRYPTO_US2_URL: &str = "wss://stream.data.alpaca.markets/v1beta3/crypto/us-2";
 const CRYPTO_BS1_URL: &str = "wss://stream.data.alpaca.markets/v1beta3/crypto/bs-1";
 
+#[cfg(feature = "crypto-perps")]
+const CRYPTO_PERP_GLOBAL_URL: &str = "wss://stream.data.alpaca.markets/v1beta1/crypto-perps/global";
+
 const OPTION_INDICATIVE_LIVE_URL: &str = "wss://stream.data.alpaca.markets/v1beta1/indicative";
 const OPTION_INDICATIVE_SANDBOX_URL: &str =
     "wss://stream.data.sandbox.alpaca.markets/v1beta1/indicative";
@@ -150,6 +153,34 @@ impl CryptoFeed {
     }
 }
 
+/// Crypto perpetual futures streaming feed selector.
+///
+/// Like [`CryptoFeed`], perpetual futures have no sandbox: every variant
+/// routes to the production wss host and the [`AccountType`] only selects
+/// the credential pair.
+#[cfg(feature = "crypto-perps")]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+#[non_exhaustive]
+pub enum CryptoPerpFeed {
+    /// Alpaca's global perpetual futures venue.
+    Global,
+}
+
+#[cfg(feature = "crypto-perps")]
+impl CryptoPerpFeed {
+    /// Return the WebSocket URL for this feed.
+    ///
+    /// `account_type` is accepted for interface symmetry with the other
+    /// feeds but does not change the URL.
+    #[must_use]
+    pub fn url(self, _account_type: AccountType) -> &'static str {
+        match self {
+            Self::Global => CRYPTO_PERP_GLOBAL_URL,
+        }
+    }
+}
+
 /// Options streaming feed selector. Each variant maps to a distinct
 /// WebSocket endpoint Alpaca exposes.
 #[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
@@ -87,3 +87,12 @@ id_newtype!(
     ExecutionId,
     "Identifier for a single order execution (fill / partial fill)."
 );
+id_newtype!(CryptoWalletId, "Identifier for a crypto funding wallet.");
+id_newtype!(
+    CryptoTransferId,
+    "Identifier for a crypto deposit or withdrawal transfer."
+);
+id_newtype!(
+    WhitelistedAddressId,
+    "Identifier for a whitelisted crypto withdrawal address."
+);
@@ -24,7 +24,7 @@ pub mod asset;
 pub use asset::AssetClass;
 /// Crypto domain types shared between the REST and streaming APIs.
 pub mod crypto;
-pub use crypto::CryptoTakerSide;
+pub use crypto::{CryptoPair, CryptoTakerSide};
 mod env;
 pub use env::ApiKey;
 /// Error types for the crate.
@@ -37,16 +37,29 @@ pub use error::WebsocketError;
 pub use error::{Error, Result};
 /// Data feed types for streaming and market data sources.
 mod feed;
+#[cfg(feature = "crypto-perps")]
+pub use feed::CryptoPerpFeed;
 pub use feed::{CryptoFeed, OptionFeed, RestFeed, StreamingFeed};
 /// Strongly-typed identifier newtypes for Alpaca-issued IDs.
 pub mod ids;
 pub use ids::{
-    AccountId, ActivityId, AssetId, ClientOrderId, ExecutionId, OptionContractId, OrderId,
-    WatchlistId,
+    AccountId, ActivityId, AssetId, ClientOrderId, CryptoTransferId, CryptoWalletId, ExecutionId,
+    OptionContractId, OrderId, WatchlistId, WhitelistedAddressId,
 };
+/// Option domain types shared between the REST and streaming APIs.
+pub mod options;
+pub use options::{OptionStyle, OptionSymbol, OptionType};
+/// Local order state merged from REST snapshots and trade-update events.
+#[cfg(feature = "streaming")]
+pub mod order_tracker;
+#[cfg(feature = "streaming")]
+pub use order_tracker::{OrderTracker, OrderTransition};
 /// Order domain types shared between the REST trading API and the streaming
 /// trade-updates feed.
 pub mod orders;
+/// Position, cost basis and P/L tracking built from fills.
+pub mod position_ledger;
+pub use position_ledger::{CostBasisMethod, LedgerPosition, PositionDiscrepancy, PositionLedger};
 mod serde_helpers;
 
 /// RESTful API client and endpoint types.
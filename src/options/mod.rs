@@ -0,0 +1,30 @@
+//! Option domain types shared between the REST trading / market-data APIs
+//! and the streaming option feed.
+
+use serde::{Deserialize, Serialize};
+
+pub mod pricing;
+mod symbol;
+pub use symbol::OptionSymbol;
+
+/// Option contract type.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "lowercase")]
+#[non_exhaustive]
+pub enum OptionType {
+    /// Call option.
+    Call,
+    /// Put option.
+    Put,
+}
+
+/// Option exercise style.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+#[non_exhaustive]
+pub enum OptionStyle {
+    /// American-style (exercisable any time before expiry).
+    American,
+    /// European-style (exercisable only at expiry).
+    European,
+}
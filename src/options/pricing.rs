@@ -0,0 +1,503 @@
+//! Theoretical option prices, Greeks and implied volatility.
+//!
+//! European contracts are priced with Black-Scholes-Merton under a
+//! continuous dividend yield. American contracts use a Cox-Ross-Rubinstein
+//! binomial tree, so the early-exercise premium of puts and of calls on
+//! dividend payers is captured.
+//!
+//! Rates, yields and volatilities are annualised decimals (`0.05` for 5%),
+//! time is in years and prices are per share, before the contract
+//! multiplier.
+//!
+//! ```
+//! # use oxidized_alpaca::{OptionStyle, OptionType};
+//! # use oxidized_alpaca::options::pricing::OptionPricer;
+//! let pricer = OptionPricer::new(OptionType::Call, OptionStyle::European, 100.0, 1.0, 100.0)
+//!     .rate(0.05);
+//! let price = pricer.price(0.2);
+//! assert!((price - 10.4506).abs() < 1e-4);
+//!
+//! let iv = pricer.implied_volatility(price).unwrap();
+//! assert!((iv - 0.2).abs() < 1e-6);
+//! ```
+
+#[cfg(feature = "restful")]
+use chrono::NaiveDate;
+#[cfg(feature = "restful")]
+use rust_decimal::prelude::ToPrimitive;
+
+use super::{OptionStyle, OptionType};
+use crate::error::{Error, Result};
+#[cfg(feature = "restful")]
+use crate::restful::{
+    market_data::options::OptionQuote, trading::options_contracts::OptionContract,
+};
+
+/// Tree depth used for American contracts unless overridden.
+const DEFAULT_BINOMIAL_STEPS: usize = 200;
+/// Shallowest tree that still yields delta, gamma and theta from its nodes.
+const MIN_BINOMIAL_STEPS: usize = 3;
+/// Volatilities are floored here so a zero input still prices.
+const MIN_VOLATILITY: f64 = 1e-6;
+/// Upper end of the implied-volatility search (500%).
+const MAX_VOLATILITY: f64 = 5.0;
+/// Price tolerance at which the implied-volatility solver stops.
+const PRICE_TOLERANCE: f64 = 1e-9;
+const MAX_SOLVER_ITERATIONS: usize = 200;
+const DAYS_PER_YEAR: f64 = 365.0;
+
+/// Option sensitivities, in trader units.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct Greeks {
+    /// Price change per $1 move in the underlying.
+    pub delta: f64,
+    /// Delta change per $1 move in the underlying.
+    pub gamma: f64,
+    /// Price change per calendar day that passes.
+    pub theta: f64,
+    /// Price change per one-point (0.01) rise in volatility.
+    pub vega: f64,
+    /// Price change per one-point (0.01) rise in the interest rate.
+    pub rho: f64,
+}
+
+/// Prices a single option contract under a chosen volatility.
+///
+/// Build one from raw inputs with [`new`](Self::new) or from a contract
+/// fetched via the trading API with [`from_contract`](Self::from_contract),
+/// then add the rate and dividend yield.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[must_use]
+pub struct OptionPricer {
+    option_type: OptionType,
+    style: OptionStyle,
+    strike: f64,
+    time_to_expiry: f64,
+    spot: f64,
+    rate: f64,
+    dividend_yield: f64,
+    steps: usize,
+}
+
+impl OptionPricer {
+    /// Price a contract with the given strike and years to expiry against
+    /// an underlying trading at `spot`. The rate and dividend yield start
+    /// at zero.
+    pub fn new(
+        option_type: OptionType,
+        style: OptionStyle,
+        strike: f64,
+        time_to_expiry: f64,
+        spot: f64,
+    ) -> Self {
+        Self {
+            option_type,
+            style,
+            strike,
+            time_to_expiry,
+            spot,
+            rate: 0.0,
+            dividend_yield: 0.0,
+            steps: DEFAULT_BINOMIAL_STEPS,
+        }
+    }
+
+    /// Take the type, style, strike and expiration from `contract`.
+    ///
+    /// Time to expiry counts calendar days from `as_of` to the expiration
+    /// date over a 365-day year, so a contract priced on its expiration
+    /// date is worth its intrinsic value.
+    #[cfg(feature = "restful")]
+    pub fn from_contract(contract: &OptionContract, spot: f64, as_of: NaiveDate) -> Self {
+        let days = (contract.expiration_date - as_of).num_days();
+        Self::new(
+            contract.option_type,
+            contract.style,
+            contract.strike_price.to_f64().unwrap_or_default(),
+            days as f64 / DAYS_PER_YEAR,
+            spot,
+        )
+    }
+
+    /// Set the continuously compounded risk-free rate.
+    pub fn rate(mut self, rate: f64) -> Self {
+        self.rate = rate;
+        self
+    }
+
+    /// Set the continuous dividend yield of the underlying.
+    pub fn dividend_yield(mut self, dividend_yield: f64) -> Self {
+        self.dividend_yield = dividend_yield;
+        self
+    }
+
+    /// Set the binomial tree depth used for American contracts. Defaults
+    /// to 200; values below 3 are raised to 3.
+    pub fn binomial_steps(mut self, steps: usize) -> Self {
+        self.steps = steps.max(MIN_BINOMIAL_STEPS);
+        self
+    }
+
+    /// Theoretical price at the given volatility.
+    pub fn price(&self, volatility: f64) -> f64 {
+        if self.time_to_expiry <= 0.0 {
+            return self.payoff(self.spot);
+        }
+        match self.style {
+            OptionStyle::European => self.black_scholes(volatility).price,
+            OptionStyle::American => self.binomial(volatility).price,
+        }
+    }
+
+    /// Greeks at the given volatility.
+    ///
+    /// Analytic for European contracts. For American contracts delta,
+    /// gamma and theta are read off the tree, and vega and rho come from
+    /// repricing with bumped inputs.
+    pub fn greeks(&self, volatility: f64) -> Greeks {
+        if self.time_to_expiry <= 0.0 {
+            let itm = self.payoff(self.spot) > 0.0;
+            let delta = match (self.option_type, itm) {
+                (_, false) => 0.0,
+                (OptionType::Call, true) => 1.0,
+                (OptionType::Put, true) => -1.0,
+            };
+            return Greeks {
+                delta,
+                gamma: 0.0,
+                theta: 0.0,
+                vega: 0.0,
+                rho: 0.0,
+            };
+        }
+        match self.style {
+            OptionStyle::European => self.black_scholes(volatility).greeks,
+            OptionStyle::American => {
+                let tree = self.binomial(volatility);
+                let (vol_up, vol_down) =
+                    (volatility + 0.01, (volatility - 0.01).max(MIN_VOLATILITY));
+                let vega = (self.binomial(vol_up).price - self.binomial(vol_down).price)
+                    / (vol_up - vol_down);
+                let rate_bump = 1e-4;
+                let up = Self {
+                    rate: self.rate + rate_bump,
+                    ..*self
+                };
+                let down = Self {
+                    rate: self.rate - rate_bump,
+                    ..*self
+                };
+                let rho = (up.binomial(volatility).price - down.binomial(volatility).price)
+                    / (2.0 * rate_bump);
+                Greeks {
+                    delta: tree.delta,
+                    gamma: tree.gamma,
+                    theta: tree.theta / DAYS_PER_YEAR,
+                    vega: vega / 100.0,
+                    rho: rho / 100.0,
+                }
+            }
+        }
+    }
+
+    /// Solve for the volatility at which the model reproduces `price`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::ImpliedVolatility`] if the contract has expired,
+    /// `price` is not a positive finite number, or it lies outside the
+    /// prices reachable between zero and 500% volatility.
+    pub fn implied_volatility(&self, price: f64) -> Result<f64> {
+        let fail = |reason| Error::ImpliedVolatility { price, reason };
+        if !price.is_finite() || price <= 0.0 {
+            return Err(fail("price must be positive and finite"));
+        }
+        if self.time_to_expiry <= 0.0 {
+            return Err(fail("contract has expired"));
+        }
+        let (mut low, mut high) = (MIN_VOLATILITY, MAX_VOLATILITY);
+        if price < self.price(low) - PRICE_TOLERANCE {
+            return Err(fail("price is below the option's intrinsic value"));
+        }
+        if price > self.price(high) + PRICE_TOLERANCE {
+            return Err(fail("price implies a volatility above 500%"));
+        }
+        // Price rises monotonically with volatility, so bisection always
+        // converges; it is also indifferent to the binomial model's kinks.
+        let mut mid = (low + high) / 2.0;
+        for _ in 0..MAX_SOLVER_ITERATIONS {
+            mid = (low + high) / 2.0;
+            let diff = self.price(mid) - price;
+            if diff.abs() < PRICE_TOLERANCE || high - low < MIN_VOLATILITY * 1e-6 {
+                break;
+            }
+            if diff > 0.0 {
+                high = mid;
+            } else {
+                low = mid;
+            }
+        }
+        Ok(mid)
+    }
+
+    /// Solve for the volatility implied by the midpoint of `quote`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::ImpliedVolatility`] if the quote is crossed or has
+    /// no ask, or for any reason [`implied_volatility`](Self::implied_volatility)
+    /// fails.
+    #[cfg(feature = "restful")]
+    pub fn implied_volatility_from_quote(&self, quote: &OptionQuote) -> Result<f64> {
+        let mid = (quote.bid_price + quote.ask_price) / 2.0;
+        if quote.ask_price <= 0.0 || quote.bid_price < 0.0 || quote.bid_price > quote.ask_price {
+            return Err(Error::ImpliedVolatility {
+                price: mid,
+                reason: "quote has no usable bid/ask",
+            });
+        }
+        self.implied_volatility(mid)
+    }
+
+    fn payoff(&self, spot: f64) -> f64 {
+        match self.option_type {
+            OptionType::Call => (spot - self.strike).max(0.0),
+            OptionType::Put => (self.strike - spot).max(0.0),
+        }
+    }
+
+    fn black_scholes(&self, volatility: f64) -> Valuation {
+        let (s, k, t) = (self.spot, self.strike, self.time_to_expiry);
+        let (r, q) = (self.rate, self.dividend_yield);
+        let vol = volatility.max(MIN_VOLATILITY);
+        let sqrt_t = t.sqrt();
+        let d1 = ((s / k).ln() + (r - q + vol * vol / 2.0) * t) / (vol * sqrt_t);
+        let d2 = d1 - vol * sqrt_t;
+        let carry = (-q * t).exp();
+        let discount = (-r * t).exp();
+        let density = normal_pdf(d1);
+        let gamma = carry * density / (s * vol * sqrt_t);
+        let vega = s * carry * density * sqrt_t;
+        let decay = -s * carry * density * vol / (2.0 * sqrt_t);
+        let (price, delta, theta, rho) = match self.option_type {
+            OptionType::Call => (
+                s * carry * normal_cdf(d1) - k * discount * normal_cdf(d2),
+                carry * normal_cdf(d1),
+                decay - r * k * discount * normal_cdf(d2) + q * s * carry * normal_cdf(d1),
+                k * t * discount * normal_cdf(d2),
+            ),
+            OptionType::Put => (
+                k * discount * normal_cdf(-d2) - s * carry * normal_cdf(-d1),
+                -carry * normal_cdf(-d1),
+                decay + r * k * discount * normal_cdf(-d2) - q * s * carry * normal_cdf(-d1),
+                -k * t * discount * normal_cdf(-d2),
+            ),
+        };
+        Valuation {
+            price,
+            greeks: Greeks {
+                delta,
+                gamma,
+                theta: theta / DAYS_PER_YEAR,
+                vega: vega / 100.0,
+                rho: rho / 100.0,
+            },
+        }
+    }
+
+    fn binomial(&self, volatility: f64) -> Tree {
+        let n = self.steps;
+        let s = self.spot;
+        let dt = self.time_to_expiry / n as f64;
+        let up = (volatility.max(MIN_VOLATILITY) * dt.sqrt()).exp();
+        let down = 1.0 / up;
+        let growth = ((self.rate - self.dividend_yield) * dt).exp();
+        let p = ((growth - down) / (up - down)).clamp(0.0, 1.0);
+        let discount = (-self.rate * dt).exp();
+        let node =
+            |step: usize, ups: usize| s * up.powi(ups as i32) * down.powi((step - ups) as i32);
+
+        let mut values: Vec<f64> = (0..=n).map(|i| self.payoff(node(n, i))).collect();
+        let mut level_two = [0.0; 3];
+        let mut level_one = [0.0; 2];
+        for step in (0..n).rev() {
+            for i in 0..=step {
+                let hold = discount * (p * values[i + 1] + (1.0 - p) * values[i]);
+                values[i] = hold.max(self.payoff(node(step, i)));
+            }
+            match step {
+                2 => level_two.copy_from_slice(&values[..3]),
+                1 => level_one.copy_from_slice(&values[..2]),
+                _ => {}
+            }
+        }
+        let price = values[0];
+        let (s_d, s_u) = (s * down, s * up);
+        let (s_dd, s_uu) = (s * down * down, s * up * up);
+        let delta = (level_one[1] - level_one[0]) / (s_u - s_d);
+        let gamma = ((level_two[2] - level_two[1]) / (s_uu - s)
+            - (level_two[1] - level_two[0]) / (s - s_dd))
+            / ((s_uu - s_dd) / 2.0);
+        let theta = (level_two[1] - price) / (2.0 * dt);
+        Tree {
+            price,
+            delta,
+            gamma,
+            theta,
+        }
+    }
+}
+
+struct Valuation {
+    price: f64,
+    greeks: Greeks,
+}
+
+/// Price plus the Greeks readable off the first two levels of the tree;
+/// theta is per year.
+struct Tree {
+    price: f64,
+    delta: f64,
+    gamma: f64,
+    theta: f64,
+}
+
+fn normal_pdf(x: f64) -> f64 {
+    (-x * x / 2.0).exp() / (2.0 * std::f64::consts::PI).sqrt()
+}
+
+/// Standard normal CDF to double precision (Hart 1968, as given by West,
+/// "Better approximations to cumulative normal functions", 2005).
+fn normal_cdf(x: f64) -> f64 {
+    let z = x.abs();
+    let tail = if z > 37.0 {
+        0.0
+    } else {
+        let e = (-z * z / 2.0).exp();
+        if z < 7.071_067_811_865_47 {
+            let num = [
+                0.035_262_496_599_891_1,
+                0.700_383_064_443_688,
+                6.373_962_203_531_65,
+                33.912_866_078_383,
+                112.079_291_497_871,
+                221.213_596_169_931,
+                220.206_867_912_376,
+            ]
+            .iter()
+            .fold(0.0, |acc, c| acc * z + c);
+            let den = [
+                0.088_388_347_648_318_4,
+                1.755_667_163_182_64,
+                16.064_177_579_207,
+                86.780_732_202_946_1,
+                296.564_248_779_674,
+                637.333_633_378_831,
+                793.826_512_519_948,
+                440.413_735_824_752,
+            ]
+            .iter()
+            .fold(0.0, |acc, c| acc * z + c);
+            e * num / den
+        } else {
+            let b = [4.0, 3.0, 2.0, 1.0]
+                .iter()
+                .fold(z + 0.65, |acc, c| z + c / acc);
+            e / b / 2.506_628_274_631
+        }
+    };
+    if x > 0.0 { 1.0 - tail } else { tail }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(actual: f64, expected: f64, tolerance: f64) {
+        assert!(
+            (actual - expected).abs() < tolerance,
+            "{actual} is not within {tolerance} of {expected}"
+        );
+    }
+
+    #[test]
+    fn european_prices_and_greeks_match_black_scholes() {
+        let call = OptionPricer::new(OptionType::Call, OptionStyle::European, 100.0, 1.0, 100.0)
+            .rate(0.05);
+        let put = OptionPricer {
+            option_type: OptionType::Put,
+            ..call
+        };
+        close(call.price(0.2), 10.450_584, 1e-5);
+        close(put.price(0.2), 5.573_526, 1e-5);
+
+        let greeks = call.greeks(0.2);
+        close(greeks.delta, 0.636_831, 1e-5);
+        close(greeks.gamma, 0.018_762, 1e-5);
+        close(greeks.vega, 0.375_240, 1e-5);
+        close(greeks.theta, -6.414_028 / 365.0, 1e-6);
+        close(greeks.rho, 0.532_325, 1e-5);
+        close(put.greeks(0.2).delta, greeks.delta - 1.0, 1e-9);
+
+        let expired = OptionPricer {
+            time_to_expiry: 0.0,
+            spot: 90.0,
+            ..put
+        };
+        assert_eq!(expired.price(0.2), 10.0);
+        assert_eq!(expired.greeks(0.2).delta, -1.0);
+    }
+
+    #[test]
+    fn american_tree_captures_early_exercise() {
+        let european =
+            OptionPricer::new(OptionType::Put, OptionStyle::European, 100.0, 1.0, 100.0).rate(0.05);
+        let american = OptionPricer {
+            style: OptionStyle::American,
+            ..european
+        };
+        // Reference value for this put from a 10,000-step tree.
+        close(american.price(0.2), 6.0896, 0.01);
+        assert!(american.price(0.2) > european.price(0.2));
+
+        // Without dividends an American call is never exercised early.
+        let call = OptionPricer {
+            option_type: OptionType::Call,
+            ..american
+        };
+        let european_call = OptionPricer {
+            style: OptionStyle::European,
+            ..call
+        };
+        close(call.price(0.2), european_call.price(0.2), 0.01);
+        let (tree, analytic) = (call.greeks(0.2), european_call.greeks(0.2));
+        close(tree.delta, analytic.delta, 0.005);
+        close(tree.gamma, analytic.gamma, 0.001);
+        close(tree.theta, analytic.theta, 0.001);
+        close(tree.vega, analytic.vega, 0.005);
+        close(tree.rho, analytic.rho, 0.005);
+    }
+
+    #[test]
+    fn implied_volatility_round_trips_and_rejects_bad_prices() {
+        for style in [OptionStyle::European, OptionStyle::American] {
+            let pricer = OptionPricer::new(OptionType::Put, style, 105.0, 0.25, 100.0)
+                .rate(0.04)
+                .dividend_yield(0.01);
+            let price = pricer.price(0.35);
+            close(pricer.implied_volatility(price).unwrap(), 0.35, 1e-6);
+        }
+
+        let pricer = OptionPricer::new(OptionType::Call, OptionStyle::European, 100.0, 0.5, 120.0);
+        for bad in [0.0, -1.0, f64::NAN, 5.0, 500.0] {
+            assert!(
+                matches!(
+                    pricer.implied_volatility(bad),
+                    Err(Error::ImpliedVolatility { .. })
+                ),
+                "{bad} should be rejected"
+            );
+        }
+    }
+}
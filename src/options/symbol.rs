@@ -0,0 +1,324 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{Datelike, NaiveDate};
+use rust_decimal::Decimal;
+use rust_decimal::prelude::ToPrimitive;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::OptionType;
+use crate::error::{Error, Result};
+
+/// Longest root the OCC symbology allows.
+const MAX_ROOT_LEN: usize = 6;
+/// `YYMMDD` + `C`/`P` + eight strike digits.
+const SUFFIX_LEN: usize = 15;
+/// Strikes are encoded in thousandths of a dollar.
+const STRIKE_SCALE: Decimal = Decimal::from_parts(1000, 0, 0, false, 0);
+/// Eight strike digits: at most $99,999.999.
+const MAX_STRIKE_UNITS: Decimal = Decimal::from_parts(99_999_999, 0, 0, false, 0);
+
+/// An OCC option contract symbol such as `AAPL250117C00150000`: root,
+/// expiration date, call/put flag and strike.
+///
+/// Parses the compact form Alpaca uses as well as the space-padded
+/// 21-character OSI form, and always formats compactly. Accepted anywhere
+/// the crate takes an option symbol, and converts into a `String` for the
+/// fields that hold one, like [`OrderLeg::symbol`].
+///
+/// ```
+/// # use oxidized_alpaca::{OptionSymbol, OptionType};
+/// let symbol: OptionSymbol = "AAPL250117C00150000".parse().unwrap();
+/// assert_eq!(symbol.root(), "AAPL");
+/// assert_eq!(symbol.option_type(), OptionType::Call);
+/// assert_eq!(symbol.strike().to_string(), "150");
+/// ```
+///
+/// [`OrderLeg::symbol`]: crate::restful::trading::orders::OrderLeg::symbol
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct OptionSymbol {
+    occ: String,
+    root: String,
+    expiration: NaiveDate,
+    option_type: OptionType,
+    strike: Decimal,
+}
+
+impl OptionSymbol {
+    /// Build a symbol from its parts.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidOptionSymbol`] if the root is not one to six
+    /// uppercase letters or digits, the expiration falls outside 2000–2099,
+    /// or the strike is not positive, finer than $0.001 or above $99,999.999.
+    pub fn new(
+        root: &str,
+        expiration: NaiveDate,
+        option_type: OptionType,
+        strike: Decimal,
+    ) -> Result<Self> {
+        let flag = match option_type {
+            OptionType::Call => 'C',
+            OptionType::Put => 'P',
+        };
+        let units = strike * STRIKE_SCALE;
+        let occ = format!(
+            "{root}{}{flag}{:08}",
+            expiration.format("%y%m%d"),
+            units.trunc().to_u64().unwrap_or_default()
+        );
+        let invalid = |reason| Error::InvalidOptionSymbol {
+            symbol: occ.clone(),
+            reason,
+        };
+        if root.is_empty() || root.len() > MAX_ROOT_LEN {
+            return Err(invalid("root must be one to six characters"));
+        }
+        if !root
+            .bytes()
+            .all(|b| b.is_ascii_uppercase() || b.is_ascii_digit())
+        {
+            return Err(invalid("root must be uppercase letters or digits"));
+        }
+        if !(2000..=2099).contains(&expiration.year()) {
+            return Err(invalid("expiration must fall in 2000-2099"));
+        }
+        if strike <= Decimal::ZERO || units > MAX_STRIKE_UNITS {
+            return Err(invalid("strike must be above 0 and at most 99999.999"));
+        }
+        if units.fract() != Decimal::ZERO {
+            return Err(invalid("strike must be a multiple of 0.001"));
+        }
+        Ok(Self {
+            root: root.to_string(),
+            expiration,
+            option_type,
+            strike: strike.normalize(),
+            occ,
+        })
+    }
+
+    /// Parse a compact (`AAPL250117C00150000`) or space-padded OSI
+    /// (`AAPL  250117C00150000`) symbol.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidOptionSymbol`] if `symbol` is not a valid
+    /// OCC symbol.
+    pub fn parse(symbol: &str) -> Result<Self> {
+        let invalid = |reason| Error::InvalidOptionSymbol {
+            symbol: symbol.to_string(),
+            reason,
+        };
+        if !symbol.is_ascii() || symbol.len() <= SUFFIX_LEN {
+            return Err(invalid(
+                "expected a root followed by YYMMDD, C or P and eight strike digits",
+            ));
+        }
+        let (root, suffix) = symbol.split_at(symbol.len() - SUFFIX_LEN);
+        let (date, rest) = suffix.split_at(6);
+        let (flag, strike) = rest.split_at(1);
+        let expiration = NaiveDate::parse_from_str(date, "%y%m%d")
+            .map_err(|_| invalid("expiration is not a valid YYMMDD date"))?;
+        let option_type = match flag {
+            "C" => OptionType::Call,
+            "P" => OptionType::Put,
+            _ => return Err(invalid("type flag must be C or P")),
+        };
+        if !strike.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(invalid("strike must be eight digits"));
+        }
+        let units: u64 = strike
+            .parse()
+            .map_err(|_| invalid("strike must be eight digits"))?;
+        let strike = Decimal::from(units) / STRIKE_SCALE;
+        Self::new(root.trim_end(), expiration, option_type, strike).map_err(|e| match e {
+            Error::InvalidOptionSymbol { reason, .. } => invalid(reason),
+            other => other,
+        })
+    }
+
+    /// The compact OCC symbol.
+    pub fn as_str(&self) -> &str {
+        &self.occ
+    }
+
+    /// The option root, usually the underlying's ticker.
+    pub fn root(&self) -> &str {
+        &self.root
+    }
+
+    /// The expiration date.
+    pub fn expiration(&self) -> NaiveDate {
+        self.expiration
+    }
+
+    /// Call or put.
+    pub fn option_type(&self) -> OptionType {
+        self.option_type
+    }
+
+    /// The strike price.
+    pub fn strike(&self) -> Decimal {
+        self.strike
+    }
+}
+
+/// Orders by root, then expiration, type (calls first) and strike, so a
+/// sorted chain reads expiry by expiry.
+impl Ord for OptionSymbol {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (&self.root, self.expiration, self.option_type, self.strike).cmp(&(
+            &other.root,
+            other.expiration,
+            other.option_type,
+            other.strike,
+        ))
+    }
+}
+
+impl PartialOrd for OptionSymbol {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for OptionSymbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.occ)
+    }
+}
+
+impl FromStr for OptionSymbol {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::parse(s)
+    }
+}
+
+impl TryFrom<&str> for OptionSymbol {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Self> {
+        Self::parse(value)
+    }
+}
+
+impl AsRef<str> for OptionSymbol {
+    fn as_ref(&self) -> &str {
+        &self.occ
+    }
+}
+
+impl From<OptionSymbol> for String {
+    fn from(value: OptionSymbol) -> Self {
+        value.occ
+    }
+}
+
+impl From<&OptionSymbol> for String {
+    fn from(value: &OptionSymbol) -> Self {
+        value.occ.clone()
+    }
+}
+
+impl Serialize for OptionSymbol {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.occ)
+    }
+}
+
+impl<'de> Deserialize<'de> for OptionSymbol {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let symbol = String::deserialize(deserializer)?;
+        Self::parse(&symbol).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dec(s: &str) -> Decimal {
+        Decimal::from_str_exact(s).unwrap()
+    }
+
+    #[test]
+    fn parses_and_formats_compact_and_padded_symbols() {
+        let symbol = OptionSymbol::parse("SPY240621P00512500").unwrap();
+        assert_eq!(symbol.root(), "SPY");
+        assert_eq!(
+            symbol.expiration(),
+            NaiveDate::from_ymd_opt(2024, 6, 21).unwrap()
+        );
+        assert_eq!(symbol.option_type(), OptionType::Put);
+        assert_eq!(symbol.strike(), dec("512.5"));
+        assert_eq!(symbol.to_string(), "SPY240621P00512500");
+
+        let padded: OptionSymbol = "SPY   240621P00512500".parse().unwrap();
+        assert_eq!(padded, symbol);
+
+        let built = OptionSymbol::new(
+            "SPY",
+            NaiveDate::from_ymd_opt(2024, 6, 21).unwrap(),
+            OptionType::Put,
+            dec("512.500"),
+        )
+        .unwrap();
+        assert_eq!(built, symbol);
+        assert_eq!(
+            serde_json::to_string(&built).unwrap(),
+            "\"SPY240621P00512500\""
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_symbols() {
+        for bad in [
+            "AAPL",
+            "AAPL251317C00150000",
+            "AAPL250117X00150000",
+            "AAPL250117C0015000A",
+            "aapl250117C00150000",
+            "TOOLONGROOT250117C00150000",
+            "AAPL250117C00000000",
+        ] {
+            assert!(
+                matches!(
+                    OptionSymbol::parse(bad),
+                    Err(Error::InvalidOptionSymbol { .. })
+                ),
+                "{bad} should be rejected"
+            );
+        }
+        let expiration = NaiveDate::from_ymd_opt(2025, 1, 17).unwrap();
+        assert!(OptionSymbol::new("AAPL", expiration, OptionType::Call, dec("150.0005")).is_err());
+    }
+
+    #[test]
+    fn sorts_by_expiration_then_type_then_strike() {
+        let mut symbols: Vec<OptionSymbol> = [
+            "AAPL250221C00150000",
+            "AAPL250117P00140000",
+            "AAPL250117C00155000",
+            "AAPL250117C00150000",
+        ]
+        .iter()
+        .map(|s| s.parse().unwrap())
+        .collect();
+        symbols.sort();
+        let sorted: Vec<&str> = symbols.iter().map(OptionSymbol::as_str).collect();
+        assert_eq!(
+            sorted,
+            [
+                "AAPL250117C00150000",
+                "AAPL250117C00155000",
+                "AAPL250117P00140000",
+                "AAPL250221C00150000",
+            ]
+        );
+    }
+}
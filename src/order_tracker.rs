@@ -0,0 +1,441 @@
+//! Local order state merged from REST snapshots and trade-update events.
+
+use chrono::{DateTime, Utc};
+use std::collections::{HashMap, HashSet};
+use tokio::sync::mpsc;
+
+use crate::{
+    ClientOrderId, Error, OrderId,
+    orders::{Order, OrderStatus},
+    streaming::{TradeUpdate, messages::trade_update::TradeUpdateEvent},
+};
+
+/// A status change observed by an [`OrderTracker`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct OrderTransition {
+    /// The order that changed.
+    pub order_id: OrderId,
+    /// Its client order ID.
+    pub client_order_id: ClientOrderId,
+    /// Status before the change; `None` the first time the order is seen.
+    pub from: Option<OrderStatus>,
+    /// Status after the change.
+    pub to: OrderStatus,
+    /// The trade-update event that caused the change, if it came from the
+    /// stream rather than a REST snapshot.
+    pub event: Option<TradeUpdateEvent>,
+    /// When the change happened, as reported by Alpaca.
+    pub at: DateTime<Utc>,
+}
+
+#[derive(Debug)]
+struct Tracked {
+    order: Order,
+    /// Timestamp of the snapshot currently held, used to order updates.
+    version: DateTime<Utc>,
+}
+
+/// One consistent, queryable view of orders assembled from
+/// [`TradingClient::list_orders`](crate::TradingClient) results and
+/// [`TradeUpdate`] events.
+///
+/// Each ingested snapshot carries a timestamp (`updated_at`, falling back
+/// to the event timestamp or `created_at`); snapshots older than the one on
+/// record are ignored, so events can arrive out of order or be replayed
+/// alongside REST results without rewinding state. Status changes are
+/// checked against [`OrderStatus::can_transition_to`]: a newer snapshot that
+/// would make an illegal move is rejected with
+/// [`Error::IllegalOrderTransition`] and leaves the tracked order unchanged.
+///
+/// Bracket and multi-leg `legs` are tracked as orders in their own right and
+/// linked to their parent; `replaces`/`replaced_by` links are followed to
+/// give the full replacement chain of an order.
+#[derive(Debug, Default)]
+pub struct OrderTracker {
+    orders: HashMap<OrderId, Tracked>,
+    by_client_id: HashMap<ClientOrderId, OrderId>,
+    parents: HashMap<OrderId, OrderId>,
+    legs: HashMap<OrderId, Vec<OrderId>>,
+    replaced_by: HashMap<OrderId, OrderId>,
+    replaces: HashMap<OrderId, OrderId>,
+    listeners: Vec<mpsc::UnboundedSender<OrderTransition>>,
+}
+
+impl OrderTracker {
+    /// Create an empty tracker.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Receive every [`OrderTransition`] the tracker records from now on.
+    ///
+    /// Dropping the receiver unsubscribes it.
+    pub fn subscribe(&mut self) -> mpsc::UnboundedReceiver<OrderTransition> {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        self.listeners.push(sender);
+        receiver
+    }
+
+    /// Merge a REST order snapshot, including its legs.
+    ///
+    /// Returns the status transitions it caused. On error, changes already
+    /// applied to other orders in the same snapshot (the parent or earlier
+    /// legs) are kept.
+    pub fn ingest_order(&mut self, order: &Order) -> Result<Vec<OrderTransition>, Error> {
+        let mut transitions = Vec::new();
+        self.merge(order, None, None, &mut transitions)?;
+        Ok(transitions)
+    }
+
+    /// Merge a batch of REST order snapshots, e.g. a `list_orders` page.
+    pub fn ingest_orders<'a>(
+        &mut self,
+        orders: impl IntoIterator<Item = &'a Order>,
+    ) -> Result<Vec<OrderTransition>, Error> {
+        let mut transitions = Vec::new();
+        for order in orders {
+            self.merge(order, None, None, &mut transitions)?;
+        }
+        Ok(transitions)
+    }
+
+    /// Merge a trade-update event.
+    ///
+    /// The order snapshot carried by the event is applied as of its
+    /// `updated_at`, or the event timestamp when that is missing.
+    pub fn ingest_update(&mut self, update: &TradeUpdate) -> Result<Vec<OrderTransition>, Error> {
+        let mut transitions = Vec::new();
+        self.merge(
+            &update.order,
+            Some(update.order.updated_at.unwrap_or(update.timestamp)),
+            Some(update.event),
+            &mut transitions,
+        )?;
+        Ok(transitions)
+    }
+
+    fn merge(
+        &mut self,
+        order: &Order,
+        version: Option<DateTime<Utc>>,
+        event: Option<TradeUpdateEvent>,
+        transitions: &mut Vec<OrderTransition>,
+    ) -> Result<(), Error> {
+        let version = version.unwrap_or_else(|| snapshot_version(order));
+        let from = match self.orders.get(&order.id) {
+            Some(tracked) if version < tracked.version => None,
+            Some(tracked) if !tracked.order.status.can_transition_to(order.status) => {
+                // A snapshot from the same instant that disagrees is a
+                // reordering artefact, not a real move.
+                if version == tracked.version {
+                    None
+                } else {
+                    return Err(Error::IllegalOrderTransition {
+                        order_id: order.id.clone(),
+                        from: tracked.order.status,
+                        to: order.status,
+                    });
+                }
+            }
+            Some(tracked) => Some(Some(tracked.order.status)),
+            None => Some(None),
+        };
+
+        if let Some(from) = from {
+            self.orders.insert(
+                order.id.clone(),
+                Tracked {
+                    order: order.clone(),
+                    version,
+                },
+            );
+            if from != Some(order.status) {
+                let transition = OrderTransition {
+                    order_id: order.id.clone(),
+                    client_order_id: order.client_order_id.clone(),
+                    from,
+                    to: order.status,
+                    event,
+                    at: version,
+                };
+                self.listeners
+                    .retain(|listener| listener.send(transition.clone()).is_ok());
+                transitions.push(transition);
+            }
+        }
+
+        self.by_client_id
+            .insert(order.client_order_id.clone(), order.id.clone());
+        if let Some(next) = &order.replaced_by {
+            self.link_replacement(order.id.clone(), next.clone());
+        }
+        if let Some(previous) = &order.replaces {
+            self.link_replacement(previous.clone(), order.id.clone());
+        }
+        for leg in &order.legs {
+            self.parents.insert(leg.id.clone(), order.id.clone());
+            let siblings = self.legs.entry(order.id.clone()).or_default();
+            if !siblings.contains(&leg.id) {
+                siblings.push(leg.id.clone());
+            }
+            self.merge(leg, None, None, transitions)?;
+        }
+        Ok(())
+    }
+
+    fn link_replacement(&mut self, previous: OrderId, next: OrderId) {
+        self.replaces.insert(next.clone(), previous.clone());
+        self.replaced_by.insert(previous, next);
+    }
+
+    /// The latest snapshot of an order.
+    pub fn get(&self, id: &OrderId) -> Option<&Order> {
+        self.orders.get(id).map(|tracked| &tracked.order)
+    }
+
+    /// The latest snapshot of the order with this client order ID.
+    pub fn get_by_client_id(&self, client_order_id: &ClientOrderId) -> Option<&Order> {
+        self.by_client_id
+            .get(client_order_id)
+            .and_then(|id| self.get(id))
+    }
+
+    /// Current status of an order.
+    pub fn status(&self, id: &OrderId) -> Option<OrderStatus> {
+        self.get(id).map(|order| order.status)
+    }
+
+    /// Orders whose status is not terminal.
+    pub fn open_orders(&self) -> impl Iterator<Item = &Order> {
+        self.orders
+            .values()
+            .map(|tracked| &tracked.order)
+            .filter(|order| !order.status.is_terminal())
+    }
+
+    /// Latest snapshots of an order's legs, in the order they were first
+    /// seen. Empty for single-leg orders.
+    pub fn legs(&self, id: &OrderId) -> Vec<&Order> {
+        self.legs
+            .get(id)
+            .into_iter()
+            .flatten()
+            .filter_map(|leg| self.get(leg))
+            .collect()
+    }
+
+    /// The parent order of a leg.
+    pub fn parent(&self, id: &OrderId) -> Option<&Order> {
+        self.parents.get(id).and_then(|parent| self.get(parent))
+    }
+
+    /// Every order ID in `id`'s replacement chain, oldest first, including
+    /// IDs that are known only through `replaces`/`replaced_by` links.
+    ///
+    /// Both walks stop at the first ID they have already visited, so
+    /// cyclic links from inconsistent or replayed data cannot loop forever.
+    pub fn replacement_chain(&self, id: &OrderId) -> Vec<OrderId> {
+        let mut visited = HashSet::from([id]);
+        let mut first = id;
+        while let Some(previous) = self.replaces.get(first) {
+            if !visited.insert(previous) {
+                break;
+            }
+            first = previous;
+        }
+        let mut visited = HashSet::from([first]);
+        let mut chain = vec![first.clone()];
+        let mut current = first;
+        while let Some(next) = self.replaced_by.get(current) {
+            if !visited.insert(next) {
+                break;
+            }
+            chain.push(next.clone());
+            current = next;
+        }
+        chain
+    }
+
+    /// The most recent tracked order in `id`'s replacement chain.
+    pub fn latest_in_chain(&self, id: &OrderId) -> Option<&Order> {
+        self.replacement_chain(id)
+            .iter()
+            .rev()
+            .find_map(|id| self.get(id))
+    }
+
+    /// Number of tracked orders, legs included.
+    pub fn len(&self) -> usize {
+        self.orders.len()
+    }
+
+    /// Whether no orders have been ingested.
+    pub fn is_empty(&self) -> bool {
+        self.orders.is_empty()
+    }
+}
+
+fn snapshot_version(order: &Order) -> DateTime<Utc> {
+    order
+        .updated_at
+        .or(order.submitted_at)
+        .unwrap_or(order.created_at)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn order(id: &str, status: &str, updated_at: &str) -> Order {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "client_order_id": format!("client-{id}"),
+            "created_at": "2024-01-02T15:30:00Z",
+            "updated_at": updated_at,
+            "submitted_at": "2024-01-02T15:30:00Z",
+            "filled_at": null,
+            "expired_at": null,
+            "canceled_at": null,
+            "asset_id": "asset-1",
+            "symbol": "AAPL",
+            "qty": "10",
+            "filled_qty": "0",
+            "type": "limit",
+            "side": "buy",
+            "time_in_force": "day",
+            "status": status,
+            "extended_hours": false,
+            "order_class": "",
+            "legs": null
+        }))
+        .unwrap()
+    }
+
+    fn update(event: TradeUpdateEvent, order: Order) -> TradeUpdate {
+        let mut update: TradeUpdate = serde_json::from_value(serde_json::json!({
+            "event": "new",
+            "timestamp": "2024-01-02T15:30:00Z",
+            "order": order,
+        }))
+        .unwrap();
+        update.event = event;
+        update
+    }
+
+    #[test]
+    fn merges_rest_and_stream_and_ignores_stale_snapshots() {
+        let mut tracker = OrderTracker::new();
+        let mut transitions = tracker.subscribe();
+        let id = OrderId::new("a");
+
+        tracker
+            .ingest_order(&order("a", "new", "2024-01-02T15:30:00Z"))
+            .unwrap();
+        let filled = tracker
+            .ingest_update(&update(
+                TradeUpdateEvent::Fill,
+                order("a", "filled", "2024-01-02T15:30:05Z"),
+            ))
+            .unwrap();
+        assert_eq!(filled[0].from, Some(OrderStatus::New));
+        assert_eq!(filled[0].event, Some(TradeUpdateEvent::Fill));
+
+        // A REST page fetched before the fill arrives late.
+        let stale = tracker
+            .ingest_orders([&order("a", "accepted", "2024-01-02T15:30:01Z")])
+            .unwrap();
+        assert!(stale.is_empty());
+        assert_eq!(tracker.status(&id), Some(OrderStatus::Filled));
+        assert_eq!(
+            tracker
+                .get_by_client_id(&ClientOrderId::new("client-a"))
+                .map(|order| order.status),
+            Some(OrderStatus::Filled)
+        );
+        assert_eq!(tracker.open_orders().count(), 0);
+
+        assert_eq!(transitions.try_recv().unwrap().to, OrderStatus::New);
+        assert_eq!(transitions.try_recv().unwrap().to, OrderStatus::Filled);
+        assert!(transitions.try_recv().is_err());
+    }
+
+    #[test]
+    fn rejects_newer_illegal_transitions() {
+        let mut tracker = OrderTracker::new();
+        tracker
+            .ingest_order(&order("a", "canceled", "2024-01-02T15:30:05Z"))
+            .unwrap();
+        match tracker.ingest_order(&order("a", "new", "2024-01-02T15:30:06Z")) {
+            Err(Error::IllegalOrderTransition { from, to, .. }) => {
+                assert_eq!((from, to), (OrderStatus::Canceled, OrderStatus::New));
+            }
+            other => panic!("expected Err(IllegalOrderTransition), got {other:?}"),
+        }
+        assert_eq!(
+            tracker.status(&OrderId::new("a")),
+            Some(OrderStatus::Canceled)
+        );
+    }
+
+    #[test]
+    fn tracks_legs_and_replacement_chains() {
+        let mut tracker = OrderTracker::new();
+        let mut parent = order("parent", "new", "2024-01-02T15:30:00Z");
+        parent.replaced_by = Some(OrderId::new("replacement"));
+        parent.legs = vec![
+            order("take-profit", "held", "2024-01-02T15:30:00Z"),
+            order("stop-loss", "held", "2024-01-02T15:30:00Z"),
+        ];
+        tracker.ingest_order(&parent).unwrap();
+        let mut replacement = order("replacement", "new", "2024-01-02T15:31:00Z");
+        replacement.replaces = Some(OrderId::new("parent"));
+        tracker.ingest_order(&replacement).unwrap();
+
+        assert_eq!(tracker.len(), 4);
+        let legs = tracker.legs(&OrderId::new("parent"));
+        assert_eq!(legs.len(), 2);
+        assert_eq!(legs[0].id.as_str(), "take-profit");
+        assert_eq!(
+            tracker
+                .parent(&OrderId::new("stop-loss"))
+                .map(|order| order.id.as_str()),
+            Some("parent")
+        );
+        assert_eq!(
+            tracker.replacement_chain(&OrderId::new("replacement")),
+            vec![OrderId::new("parent"), OrderId::new("replacement")]
+        );
+        assert_eq!(
+            tracker
+                .latest_in_chain(&OrderId::new("parent"))
+                .map(|order| order.id.as_str()),
+            Some("replacement")
+        );
+    }
+
+    /// Cyclic replacement links that don't pass through the starting order
+    /// still end the walk, and no ID is listed twice.
+    #[test]
+    fn replacement_chain_survives_cycles() {
+        let mut tracker = OrderTracker::new();
+        let [a, b, c] = ["a", "b", "c"].map(OrderId::new);
+        tracker.link_replacement(a.clone(), c.clone());
+        tracker.link_replacement(b.clone(), a.clone());
+        tracker.link_replacement(a.clone(), b.clone());
+
+        let chain = tracker.replacement_chain(&c);
+        assert_eq!(chain, vec![b, a]);
+        assert!(tracker.latest_in_chain(&c).is_none());
+    }
+
+    #[test]
+    fn status_state_machine() {
+        assert!(OrderStatus::New.can_transition_to(OrderStatus::PartiallyFilled));
+        assert!(OrderStatus::PendingCancel.can_transition_to(OrderStatus::New));
+        assert!(!OrderStatus::Filled.can_transition_to(OrderStatus::Canceled));
+        assert!(!OrderStatus::PartiallyFilled.can_transition_to(OrderStatus::New));
+        assert!(!OrderStatus::New.can_transition_to(OrderStatus::PendingNew));
+        assert!(OrderStatus::Replaced.is_terminal());
+    }
+}
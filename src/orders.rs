@@ -6,7 +6,7 @@ use rust_decimal::Decimal;
 use serde::{Deserialize, Deserializer, Serialize};
 
 use crate::serde_helpers::{null_def_vec, string_as_decimal, string_as_optional_decimal};
-use crate::{AssetClass, AssetId, ClientOrderId, OrderId};
+use crate::{AssetClass, AssetId, ClientOrderId, CryptoPair, OrderId};
 
 pub(crate) fn empty_string_as_none_order_class<'de, D>(
     deserializer: D,
@@ -105,7 +105,7 @@ pub enum PositionIntent {
 }
 
 /// Status of an order.
-#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 #[non_exhaustive]
 pub enum OrderStatus {
@@ -145,8 +145,42 @@ pub enum OrderStatus {
     Held,
 }
 
+impl OrderStatus {
+    /// Whether the order can no longer change: filled, canceled, expired,
+    /// replaced or rejected.
+    #[must_use]
+    pub const fn is_terminal(self) -> bool {
+        matches!(
+            self,
+            Self::Filled | Self::Canceled | Self::Expired | Self::Replaced | Self::Rejected
+        )
+    }
+
+    /// Whether an order may move from `self` to `next`.
+    ///
+    /// Terminal orders never change, nothing returns to `pending_new`, and
+    /// a partially filled order cannot become unfilled again. Everything
+    /// else is allowed: pending cancels and replaces can be rejected, which
+    /// sends the order back to its previous state. Staying in the same
+    /// status is always allowed.
+    #[must_use]
+    pub fn can_transition_to(self, next: Self) -> bool {
+        if self == next {
+            return true;
+        }
+        if self.is_terminal() || next == Self::PendingNew {
+            return false;
+        }
+        !(self == Self::PartiallyFilled
+            && matches!(
+                next,
+                Self::New | Self::Accepted | Self::AcceptedForBidding | Self::Held
+            ))
+    }
+}
+
 /// An order as returned by the Alpaca API.
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 #[non_exhaustive]
 pub struct Order {
     /// Order ID.
@@ -272,3 +306,20 @@ pub struct Order {
     #[serde(default, deserialize_with = "null_def_vec")]
     pub legs: Vec<Order>,
 }
+
+impl Order {
+    /// The crypto pair this order trades, or `None` for equities and
+    /// options.
+    ///
+    /// REST responses may omit `asset_class`; a slashed symbol is then
+    /// taken as crypto.
+    #[must_use]
+    pub fn crypto_pair(&self) -> Option<CryptoPair> {
+        match self.asset_class {
+            Some(AssetClass::Crypto) => CryptoPair::parse(&self.symbol).ok(),
+            Some(_) => None,
+            None if self.symbol.contains('/') => CryptoPair::parse(&self.symbol).ok(),
+            None => None,
+        }
+    }
+}
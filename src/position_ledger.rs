@@ -0,0 +1,685 @@
+//! Per-symbol position, cost basis and P/L maintained from fills.
+
+use rust_decimal::Decimal;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+#[cfg(feature = "restful")]
+use crate::ActivityId;
+use crate::{ExecutionId, crypto::position_symbol, orders::Side};
+
+/// How closing fills are matched against open lots when computing
+/// realized P/L.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CostBasisMethod {
+    /// Close the oldest open lot first.
+    #[default]
+    Fifo,
+    /// Close the newest open lot first.
+    Lifo,
+    /// Keep a single lot at the running average price.
+    AverageCost,
+}
+
+/// An open lot: signed quantity (negative for shorts) and its entry price.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Lot {
+    qty: Decimal,
+    price: Decimal,
+}
+
+/// The ledger's view of one symbol.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct LedgerPosition {
+    /// Ticker symbol, with crypto pairs in the compact form positions use
+    /// (`BTCUSD`).
+    pub symbol: String,
+    /// Price multiplier applied to P/L and market value (100 for option
+    /// contracts, 1 otherwise).
+    pub multiplier: Decimal,
+    /// P/L locked in by closing fills. Fees are not reported on fills and
+    /// are not included.
+    pub realized_pl: Decimal,
+    /// Latest mark price, if one has been supplied.
+    pub mark_price: Option<Decimal>,
+    lots: VecDeque<Lot>,
+}
+
+impl LedgerPosition {
+    fn new(symbol: String, multiplier: Decimal) -> Self {
+        Self {
+            symbol,
+            multiplier,
+            realized_pl: Decimal::ZERO,
+            mark_price: None,
+            lots: VecDeque::new(),
+        }
+    }
+
+    /// Signed quantity held: positive when long, negative when short.
+    pub fn qty(&self) -> Decimal {
+        self.lots.iter().map(|lot| lot.qty).sum()
+    }
+
+    /// Whether nothing is held.
+    pub fn is_flat(&self) -> bool {
+        self.lots.is_empty()
+    }
+
+    /// Average entry price of the open lots, or `None` when flat.
+    pub fn avg_cost(&self) -> Option<Decimal> {
+        let qty = self.qty();
+        (!qty.is_zero()).then(|| self.entry_value() / qty)
+    }
+
+    /// Total cost of the open lots (negative for shorts).
+    pub fn cost_basis(&self) -> Decimal {
+        self.entry_value() * self.multiplier
+    }
+
+    /// Value of the position at the mark price.
+    pub fn market_value(&self) -> Option<Decimal> {
+        self.mark_price
+            .map(|mark| mark * self.qty() * self.multiplier)
+    }
+
+    /// P/L of the open lots at the mark price.
+    pub fn unrealized_pl(&self) -> Option<Decimal> {
+        self.market_value().map(|value| value - self.cost_basis())
+    }
+
+    fn entry_value(&self) -> Decimal {
+        self.lots.iter().map(|lot| lot.qty * lot.price).sum()
+    }
+
+    fn apply(&mut self, method: CostBasisMethod, mut delta: Decimal, price: Decimal) {
+        // Close lots on the opposite side first; whatever is left opens a
+        // new lot, which is how a fill larger than the position flips it.
+        while !delta.is_zero() {
+            let lot = match method {
+                CostBasisMethod::Fifo | CostBasisMethod::AverageCost => self.lots.front_mut(),
+                CostBasisMethod::Lifo => self.lots.back_mut(),
+            };
+            let Some(lot) =
+                lot.filter(|lot| lot.qty.is_sign_negative() != delta.is_sign_negative())
+            else {
+                break;
+            };
+            let closed = if lot.qty.abs() <= delta.abs() {
+                lot.qty
+            } else {
+                -delta
+            };
+            self.realized_pl += (price - lot.price) * closed * self.multiplier;
+            lot.qty -= closed;
+            delta += closed;
+            if lot.qty.is_zero() {
+                match method {
+                    CostBasisMethod::Fifo | CostBasisMethod::AverageCost => self.lots.pop_front(),
+                    CostBasisMethod::Lifo => self.lots.pop_back(),
+                };
+            }
+        }
+        if delta.is_zero() {
+            return;
+        }
+        match (method, self.lots.front_mut()) {
+            (CostBasisMethod::AverageCost, Some(lot)) => {
+                let qty = lot.qty + delta;
+                lot.price = (lot.qty * lot.price + delta * price) / qty;
+                lot.qty = qty;
+            }
+            _ => self.lots.push_back(Lot { qty: delta, price }),
+        }
+    }
+}
+
+/// A difference between the ledger and the broker's positions found by
+/// [`PositionLedger::reconcile`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct PositionDiscrepancy {
+    /// Ticker symbol.
+    pub symbol: String,
+    /// Signed quantity according to the ledger.
+    pub ledger_qty: Decimal,
+    /// Signed quantity according to the broker.
+    pub broker_qty: Decimal,
+    /// Average entry price according to the ledger.
+    pub ledger_avg_cost: Option<Decimal>,
+    /// Average entry price according to the broker.
+    pub broker_avg_cost: Option<Decimal>,
+}
+
+/// Positions and P/L rebuilt from fills.
+///
+/// Feed it every fill — [`apply_trade_update`](Self::apply_trade_update)
+/// for the trade-updates stream, [`apply_fill`](Self::apply_fill) for
+/// anything else — and it keeps signed quantity, average cost and realized
+/// P/L per symbol under the chosen [`CostBasisMethod`]. Fills that carry an
+/// execution ID are applied once, so a replayed stream can be fed in
+/// without double counting. [`reconciled`](crate::streaming::TradeUpdate::reconciled)
+/// updates carry no execution ID and are applied every time they are
+/// given; the resilient trade-updates client already delivers each of
+/// them once. [`mark`](Self::mark) supplies live prices for unrealized P/L.
+///
+/// Crypto pairs are keyed by their compact symbol (`BTCUSD`), as positions
+/// report them, so fills on `BTC/USD` orders and lookups in either form
+/// land on the same position.
+///
+/// Call [`reconcile`](Self::reconcile) with `list_positions` results from
+/// time to time to catch drift (fills missed while disconnected, manual
+/// trades, corporate actions).
+#[derive(Debug, Default)]
+pub struct PositionLedger {
+    method: CostBasisMethod,
+    price_tolerance: Decimal,
+    positions: HashMap<String, LedgerPosition>,
+    executions: HashSet<ExecutionId>,
+    #[cfg(feature = "restful")]
+    settlements: HashSet<ActivityId>,
+}
+
+impl PositionLedger {
+    /// Create an empty ledger using `method` to match closing fills.
+    pub fn new(method: CostBasisMethod) -> Self {
+        Self {
+            method,
+            ..Self::default()
+        }
+    }
+
+    /// How far the ledger's average cost may drift from the broker's before
+    /// [`reconcile`](Self::reconcile) reports it. Defaults to zero.
+    #[must_use]
+    pub fn price_tolerance(mut self, tolerance: Decimal) -> Self {
+        self.price_tolerance = tolerance.abs();
+        self
+    }
+
+    /// The cost-basis method in use.
+    pub fn method(&self) -> CostBasisMethod {
+        self.method
+    }
+
+    /// Apply a fill of `qty` units at `price`. `qty` is always positive;
+    /// `side` gives the direction. Option contracts, recognized by their
+    /// OCC symbol, are valued with a multiplier of 100.
+    pub fn apply_fill(
+        &mut self,
+        symbol: &str,
+        side: Side,
+        qty: Decimal,
+        price: Decimal,
+    ) -> &LedgerPosition {
+        self.apply(symbol, symbol_multiplier(symbol), side, qty, price)
+    }
+
+    /// Apply a fill identified by `execution_id`, ignoring it if that
+    /// execution was applied before. Returns `None` for duplicates.
+    pub fn apply_execution(
+        &mut self,
+        execution_id: &ExecutionId,
+        symbol: &str,
+        side: Side,
+        qty: Decimal,
+        price: Decimal,
+    ) -> Option<&LedgerPosition> {
+        if !self.executions.insert(execution_id.clone()) {
+            return None;
+        }
+        Some(self.apply_fill(symbol, side, qty, price))
+    }
+
+    fn apply(
+        &mut self,
+        symbol: &str,
+        multiplier: Decimal,
+        side: Side,
+        qty: Decimal,
+        price: Decimal,
+    ) -> &LedgerPosition {
+        let delta = match side {
+            Side::Buy => qty.abs(),
+            Side::Sell => -qty.abs(),
+        };
+        let symbol = position_symbol(symbol);
+        let position = self
+            .positions
+            .entry(symbol.to_string())
+            .or_insert_with(|| LedgerPosition::new(symbol.into_owned(), multiplier));
+        position.apply(self.method, delta, price);
+        position
+    }
+
+    /// Apply a `fill` or `partial_fill` trade update, returning the
+    /// positions it changed. Other events, and fills already applied under
+    /// the same execution ID, change nothing.
+    ///
+    /// A multi-leg (`mleg`) fill is booked per leg from
+    /// [`legs`](crate::streaming::TradeUpdate::legs), each with its own
+    /// side, quantity and price; without leg detail it is skipped rather
+    /// than booked against the strategy order's symbol. Option contracts
+    /// are valued with a multiplier of 100.
+    #[cfg(feature = "streaming")]
+    pub fn apply_trade_update(
+        &mut self,
+        update: &crate::streaming::TradeUpdate,
+    ) -> Vec<&LedgerPosition> {
+        use crate::orders::OrderClass;
+        use crate::streaming::messages::trade_update::TradeUpdateEvent;
+
+        if !matches!(
+            update.event,
+            TradeUpdateEvent::Fill | TradeUpdateEvent::PartialFill
+        ) {
+            return Vec::new();
+        }
+        let order = &update.order;
+        let mut changed = Vec::new();
+        if order.order_class == Some(OrderClass::Mleg) {
+            for leg in update.legs.iter().flatten() {
+                let (Some(symbol), Some(qty), Some(price)) = (&leg.symbol, leg.qty, leg.price)
+                else {
+                    continue;
+                };
+                let leg_order = order.legs.iter().find(|leg_order| {
+                    leg.order_id.as_ref() == Some(&leg_order.id) || leg_order.symbol == *symbol
+                });
+                let Some(leg_order) = leg_order else {
+                    continue;
+                };
+                if let Some(execution_id) = &leg.execution_id
+                    && !self.executions.insert(execution_id.clone())
+                {
+                    continue;
+                }
+                let multiplier = contract_multiplier(leg_order);
+                self.apply(symbol, multiplier, leg_order.side, qty, price);
+                changed.push(position_symbol(symbol).into_owned());
+            }
+        } else {
+            let Some((qty, price)) = update.qty.zip(update.price) else {
+                return Vec::new();
+            };
+            if let Some(execution_id) = &update.execution_id
+                && !self.executions.insert(execution_id.clone())
+            {
+                return Vec::new();
+            }
+            self.apply(
+                &order.symbol,
+                contract_multiplier(order),
+                order.side,
+                qty,
+                price,
+            );
+            changed.push(position_symbol(&order.symbol).into_owned());
+        }
+        changed.dedup();
+        changed
+            .iter()
+            .filter_map(|symbol| self.positions.get(symbol))
+            .collect()
+    }
+
+    /// Apply an option assignment, exercise or expiration, ignoring it if
+    /// that activity was applied before. Returns `false` for duplicates.
+    ///
+    /// Up to `settlement.contracts` of the ledger's option position are
+    /// closed at zero, so the premium paid or received is realized, and any
+    /// delivered shares are booked at the strike.
+    #[cfg(feature = "restful")]
+    pub fn apply_option_settlement(
+        &mut self,
+        settlement: &crate::restful::trading::option_lifecycle::OptionSettlement,
+    ) -> bool {
+        if !self.settlements.insert(settlement.activity_id.clone()) {
+            return false;
+        }
+        let symbol = settlement.contract.as_str();
+        if let Some(held) = self.position(symbol).map(LedgerPosition::qty)
+            && !held.is_zero()
+        {
+            let side = if held.is_sign_positive() {
+                Side::Sell
+            } else {
+                Side::Buy
+            };
+            let qty = held.abs().min(settlement.contracts);
+            self.apply(symbol, Decimal::ONE_HUNDRED, side, qty, Decimal::ZERO);
+        }
+        if let Some(stock) = &settlement.stock {
+            self.apply_fill(&stock.symbol, stock.side, stock.qty, stock.price);
+        }
+        true
+    }
+
+    /// Set the mark price used for `symbol`'s unrealized P/L.
+    pub fn mark(&mut self, symbol: &str, price: Decimal) {
+        if let Some(position) = self.positions.get_mut(position_symbol(symbol).as_ref()) {
+            position.mark_price = Some(price);
+        }
+    }
+
+    /// The ledger's position in `symbol`, including flat positions that
+    /// still carry realized P/L. Crypto pairs may be given in either form.
+    pub fn position(&self, symbol: &str) -> Option<&LedgerPosition> {
+        self.positions.get(position_symbol(symbol).as_ref())
+    }
+
+    /// All positions the ledger has seen, flat ones included.
+    pub fn positions(&self) -> impl Iterator<Item = &LedgerPosition> {
+        self.positions.values()
+    }
+
+    /// Realized P/L summed over all symbols.
+    pub fn realized_pl(&self) -> Decimal {
+        self.positions.values().map(|p| p.realized_pl).sum()
+    }
+
+    /// Unrealized P/L summed over open positions that have a mark price.
+    pub fn unrealized_pl(&self) -> Decimal {
+        self.positions
+            .values()
+            .filter_map(LedgerPosition::unrealized_pl)
+            .sum()
+    }
+
+    /// Compare the ledger with the broker's open positions.
+    ///
+    /// Reports every symbol whose quantity differs, or whose average cost
+    /// differs by more than the [price tolerance](Self::price_tolerance),
+    /// including symbols only one side holds. The broker's current prices
+    /// become the ledger's marks; the ledger itself is otherwise left
+    /// unchanged so the caller decides how to resolve each discrepancy.
+    #[cfg(feature = "restful")]
+    pub fn reconcile(
+        &mut self,
+        positions: &[crate::restful::trading::positions::Position],
+    ) -> Vec<PositionDiscrepancy> {
+        use crate::restful::trading::positions::PositionSide;
+
+        let mut discrepancies = Vec::new();
+        let mut seen = HashSet::new();
+        for broker in positions {
+            let symbol = position_symbol(&broker.symbol);
+            let broker_qty = match broker.side {
+                PositionSide::Short => -broker.qty.abs(),
+                _ => broker.qty.abs(),
+            };
+            let broker_avg_cost = Some(broker.avg_entry_price);
+            let ledger = self.positions.get_mut(symbol.as_ref());
+            let (ledger_qty, ledger_avg_cost) = match ledger {
+                Some(ledger) => {
+                    ledger.mark_price = Some(broker.current_price);
+                    (ledger.qty(), ledger.avg_cost())
+                }
+                None => (Decimal::ZERO, None),
+            };
+            let cost_drift = ledger_avg_cost
+                .is_none_or(|cost| (cost - broker.avg_entry_price).abs() > self.price_tolerance);
+            if ledger_qty != broker_qty || cost_drift {
+                discrepancies.push(PositionDiscrepancy {
+                    symbol: symbol.to_string(),
+                    ledger_qty,
+                    broker_qty,
+                    ledger_avg_cost,
+                    broker_avg_cost,
+                });
+            }
+            seen.insert(symbol);
+        }
+        for ledger in self.positions.values() {
+            if !ledger.is_flat() && !seen.contains(ledger.symbol.as_str()) {
+                discrepancies.push(PositionDiscrepancy {
+                    symbol: ledger.symbol.clone(),
+                    ledger_qty: ledger.qty(),
+                    broker_qty: Decimal::ZERO,
+                    ledger_avg_cost: ledger.avg_cost(),
+                    broker_avg_cost: None,
+                });
+            }
+        }
+        discrepancies.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+        discrepancies
+    }
+
+    /// Fetch positions with `client` and [`reconcile`](Self::reconcile)
+    /// against them.
+    #[cfg(feature = "restful")]
+    pub async fn reconcile_with(
+        &mut self,
+        client: &crate::TradingClient,
+    ) -> crate::Result<Vec<PositionDiscrepancy>> {
+        let positions = client.list_positions().await?;
+        Ok(self.reconcile(&positions))
+    }
+}
+
+/// Price multiplier for `order`'s instrument: 100 for option contracts,
+/// 1 otherwise.
+#[cfg(feature = "streaming")]
+fn contract_multiplier(order: &crate::orders::Order) -> Decimal {
+    match order.asset_class {
+        Some(crate::AssetClass::UsOption) => Decimal::ONE_HUNDRED,
+        Some(_) => Decimal::ONE,
+        None => symbol_multiplier(&order.symbol),
+    }
+}
+
+/// Price multiplier for `symbol`: 100 for OCC option symbols, 1 otherwise.
+fn symbol_multiplier(symbol: &str) -> Decimal {
+    if crate::OptionSymbol::parse(symbol).is_ok() {
+        Decimal::ONE_HUNDRED
+    } else {
+        Decimal::ONE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dec(value: &str) -> Decimal {
+        value.parse().unwrap()
+    }
+
+    fn ledger_after(method: CostBasisMethod) -> PositionLedger {
+        let mut ledger = PositionLedger::new(method);
+        ledger.apply_fill("AAPL", Side::Buy, dec("10"), dec("100"));
+        ledger.apply_fill("AAPL", Side::Buy, dec("10"), dec("110"));
+        ledger.apply_fill("AAPL", Side::Sell, dec("15"), dec("120"));
+        ledger
+    }
+
+    #[test]
+    fn cost_basis_methods_match_closing_fills_differently() {
+        let fifo = ledger_after(CostBasisMethod::Fifo);
+        let position = fifo.position("AAPL").unwrap();
+        assert_eq!(position.realized_pl, dec("250"));
+        assert_eq!(position.avg_cost(), Some(dec("110")));
+
+        let lifo = ledger_after(CostBasisMethod::Lifo);
+        let position = lifo.position("AAPL").unwrap();
+        assert_eq!(position.realized_pl, dec("200"));
+        assert_eq!(position.avg_cost(), Some(dec("100")));
+
+        let average = ledger_after(CostBasisMethod::AverageCost);
+        let position = average.position("AAPL").unwrap();
+        assert_eq!(position.realized_pl, dec("225"));
+        assert_eq!(position.qty(), dec("5"));
+        assert_eq!(position.avg_cost(), Some(dec("105")));
+    }
+
+    #[test]
+    fn fills_through_zero_flip_to_a_short() {
+        let mut ledger = PositionLedger::default();
+        ledger.apply_fill("TSLA", Side::Buy, dec("5"), dec("200"));
+        ledger.apply_fill("TSLA", Side::Sell, dec("8"), dec("210"));
+        ledger.mark("TSLA", dec("190"));
+        let position = ledger.position("TSLA").unwrap();
+        assert_eq!(position.qty(), dec("-3"));
+        assert_eq!(position.realized_pl, dec("50"));
+        assert_eq!(position.avg_cost(), Some(dec("210")));
+        assert_eq!(position.unrealized_pl(), Some(dec("60")));
+
+        ledger.apply_fill("TSLA", Side::Buy, dec("3"), dec("190"));
+        assert!(ledger.position("TSLA").unwrap().is_flat());
+        assert_eq!(ledger.realized_pl(), dec("110"));
+    }
+
+    #[test]
+    fn executions_apply_once() {
+        let mut ledger = PositionLedger::default();
+        let execution = ExecutionId::new("exec-1");
+        assert!(
+            ledger
+                .apply_execution(&execution, "AAPL", Side::Buy, dec("1"), dec("100"))
+                .is_some()
+        );
+        assert!(
+            ledger
+                .apply_execution(&execution, "AAPL", Side::Buy, dec("1"), dec("100"))
+                .is_none()
+        );
+        assert_eq!(ledger.position("AAPL").unwrap().qty(), dec("1"));
+    }
+
+    #[test]
+    fn option_fills_are_valued_per_contract() {
+        let mut ledger = PositionLedger::default();
+        ledger.apply_fill("AAPL240119C00190000", Side::Sell, dec("1"), dec("2.50"));
+        ledger.apply_fill("AAPL240119C00190000", Side::Buy, dec("1"), dec("0.50"));
+        let position = ledger.position("AAPL240119C00190000").unwrap();
+        assert_eq!(position.multiplier, Decimal::ONE_HUNDRED);
+        assert_eq!(position.realized_pl, dec("200"));
+        ledger.apply_fill("AAPL", Side::Buy, dec("1"), dec("190"));
+        assert_eq!(ledger.position("AAPL").unwrap().multiplier, Decimal::ONE);
+    }
+
+    #[cfg(feature = "restful")]
+    fn broker_position(
+        symbol: &str,
+        qty: &str,
+        side: &str,
+        avg: &str,
+    ) -> crate::restful::trading::positions::Position {
+        serde_json::from_value(serde_json::json!({
+                "asset_id": "asset-1",
+                "symbol": symbol,
+                "exchange": "NASDAQ",
+                "asset_class": "us_equity",
+                "asset_marginable": true,
+                "avg_entry_price": avg,
+                "qty": qty,
+                "qty_available": qty,
+                "side": side,
+                "market_value": "0",
+                "cost_basis": "0",
+                "unrealized_pl": "0",
+                "unrealized_plpc": "0",
+                "unrealized_intraday_pl": "0",
+                "unrealized_intraday_plpc": "0",
+                "current_price": "105",
+                "lastday_price": "100",
+                "change_today": "0"
+        }))
+        .unwrap()
+    }
+
+    #[cfg(feature = "restful")]
+    #[test]
+    fn reconcile_reports_drift_both_ways() {
+        let position = broker_position;
+
+        let mut ledger = PositionLedger::default().price_tolerance(dec("0.01"));
+        ledger.apply_fill("AAPL", Side::Buy, dec("10"), dec("100"));
+        ledger.apply_fill("MSFT", Side::Sell, dec("2"), dec("300"));
+        ledger.apply_fill("TSLA", Side::Buy, dec("1"), dec("200"));
+
+        let discrepancies = ledger.reconcile(&[
+            position("AAPL", "10", "long", "100.001"),
+            position("MSFT", "-3", "short", "300"),
+            position("NVDA", "4", "long", "50"),
+        ]);
+        let symbols: Vec<_> = discrepancies.iter().map(|d| d.symbol.as_str()).collect();
+        assert_eq!(symbols, ["MSFT", "NVDA", "TSLA"]);
+        assert_eq!(discrepancies[0].broker_qty, dec("-3"));
+        assert_eq!(discrepancies[2].broker_qty, Decimal::ZERO);
+        assert_eq!(
+            ledger.position("AAPL").unwrap().unrealized_pl(),
+            Some(dec("50"))
+        );
+    }
+
+    /// Crypto fills booked under the slashed order symbol match the compact
+    /// symbol the broker reports positions under.
+    #[cfg(feature = "restful")]
+    #[test]
+    fn reconcile_matches_crypto_pairs_in_either_form() {
+        let mut ledger = PositionLedger::default();
+        ledger.apply_fill("BTC/USD", Side::Buy, dec("0.5"), dec("60000"));
+        let discrepancies = ledger.reconcile(&[broker_position("BTCUSD", "0.5", "long", "60000")]);
+        assert!(discrepancies.is_empty(), "{discrepancies:?}");
+        assert_eq!(ledger.position("BTC/USD").unwrap().symbol, "BTCUSD");
+        assert_eq!(ledger.position("BTCUSD").unwrap().qty(), dec("0.5"));
+    }
+
+    /// A multi-leg fill is booked per leg with each leg's own side and
+    /// price, never against the strategy order's symbol.
+    #[cfg(feature = "streaming")]
+    #[test]
+    fn multi_leg_fills_book_each_leg() {
+        let leg = |id: &str, symbol: &str, side: &str| {
+            serde_json::json!({
+                "id": id, "client_order_id": format!("client-{id}"),
+                "created_at": "2024-01-02T15:30:00Z", "updated_at": "2024-01-02T15:30:01Z",
+                "submitted_at": "2024-01-02T15:30:00Z", "asset_id": "asset-1",
+                "symbol": symbol, "asset_class": "us_option", "qty": "1", "filled_qty": "1",
+                "type": "limit", "side": side, "time_in_force": "day", "status": "filled",
+                "extended_hours": false, "ratio_qty": "1"
+            })
+        };
+        let json = serde_json::json!({
+            "event": "fill",
+            "execution_id": "exec-parent",
+            "timestamp": "2024-01-02T15:30:01Z",
+            "price": "1.10",
+            "qty": "1",
+            "legs": [
+                {"execution_id": "exec-1", "order_id": "leg-1", "symbol": "AAPL240119C00190000", "qty": "1", "price": "3.00"},
+                {"execution_id": "exec-2", "order_id": "leg-2", "symbol": "AAPL240119C00195000", "qty": "1", "price": "1.90"}
+            ],
+            "order": {
+                "id": "parent", "client_order_id": "client-parent",
+                "created_at": "2024-01-02T15:30:00Z", "updated_at": "2024-01-02T15:30:01Z",
+                "submitted_at": "2024-01-02T15:30:00Z", "asset_id": "",
+                "symbol": "", "qty": "1", "filled_qty": "1", "type": "limit", "side": "buy",
+                "time_in_force": "day", "status": "filled", "extended_hours": false,
+                "order_class": "mleg",
+                "legs": [
+                    leg("leg-1", "AAPL240119C00190000", "buy"),
+                    leg("leg-2", "AAPL240119C00195000", "sell")
+                ]
+            }
+        });
+        let mut without_leg_orders = json.clone();
+        without_leg_orders["order"]["legs"] = serde_json::json!([]);
+        let without_leg_orders: crate::streaming::TradeUpdate =
+            serde_json::from_value(without_leg_orders).unwrap();
+        let update: crate::streaming::TradeUpdate = serde_json::from_value(json).unwrap();
+
+        let mut ledger = PositionLedger::default();
+        assert!(ledger.apply_trade_update(&without_leg_orders).is_empty());
+        assert_eq!(ledger.apply_trade_update(&update).len(), 2);
+        assert!(ledger.apply_trade_update(&update).is_empty());
+        assert!(ledger.position("").is_none());
+        let long = ledger.position("AAPL240119C00190000").unwrap();
+        assert_eq!((long.qty(), long.avg_cost()), (dec("1"), Some(dec("3.00"))));
+        assert_eq!(long.multiplier, Decimal::ONE_HUNDRED);
+        let short = ledger.position("AAPL240119C00195000").unwrap();
+        assert_eq!(short.qty(), dec("-1"));
+    }
+}
@@ -0,0 +1,639 @@
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use chrono::{DateTime, Utc};
+use reqwest::{Method, Request};
+use rust_decimal::Decimal;
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value, json};
+
+use crate::error::{Error, JsonError, Result};
+use crate::restful::TradingClient;
+use crate::restful::trading::orders::generate_client_order_id;
+
+macro_rules! info {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        tracing::info!($($arg)*);
+    };
+}
+
+/// A mutating request that a dry-run [`TradingClient`] intercepted instead
+/// of sending. See [`TradingClient::with_dry_run`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct DryRunCall {
+    /// HTTP method, e.g. `"POST"`.
+    pub method: String,
+    /// Request path including the version prefix, e.g. `"v2/orders"`.
+    pub path: String,
+    /// Encoded query string, if the request had one.
+    pub query: Option<String>,
+    /// The exact JSON body that would have been sent.
+    pub body: Option<String>,
+    /// When the request was intercepted.
+    pub at: DateTime<Utc>,
+}
+
+/// The log and id counter shared by every clone of a dry-run client.
+#[derive(Debug, Default)]
+pub(crate) struct DryRun {
+    calls: Mutex<Vec<DryRunCall>>,
+    next_id: AtomicU64,
+}
+
+/// A response synthesized for an intercepted request.
+pub(crate) struct Synthesized {
+    method: Method,
+    path: String,
+    value: Value,
+}
+
+impl Synthesized {
+    pub(crate) fn decode<T: DeserializeOwned>(self) -> Result<T> {
+        serde_json::from_value(self.value).map_err(|e| Error::DryRunUnsupported {
+            method: self.method.to_string(),
+            path: self.path,
+            source: Some(JsonError::new(e)),
+        })
+    }
+}
+
+impl DryRun {
+    pub(crate) fn calls(&self) -> Vec<DryRunCall> {
+        self.calls.lock().expect("dry-run log poisoned").clone()
+    }
+
+    /// Record `request` and answer it the way Alpaca would have, reading
+    /// whatever current state the answer depends on through `client`.
+    pub(crate) async fn respond(
+        &self,
+        client: &TradingClient,
+        request: &Request,
+    ) -> Result<Synthesized> {
+        let method = request.method().clone();
+        let path = request.url().path().trim_start_matches('/').to_string();
+        let query = request.url().query().map(str::to_string);
+        let body = request
+            .body()
+            .and_then(|body| body.as_bytes())
+            .map(|bytes| String::from_utf8_lossy(bytes).into_owned());
+        info!(%method, path, ?query, ?body, "dry run: intercepted request");
+        self.calls
+            .lock()
+            .expect("dry-run log poisoned")
+            .push(DryRunCall {
+                method: method.to_string(),
+                path: path.clone(),
+                query: query.clone(),
+                body: body.clone(),
+                at: Utc::now(),
+            });
+
+        let unsupported = |source| Error::DryRunUnsupported {
+            method: method.to_string(),
+            path: path.clone(),
+            source,
+        };
+        let payload = match &body {
+            Some(body) => {
+                serde_json::from_str(body).map_err(|e| unsupported(Some(JsonError::new(e))))?
+            }
+            None => Value::Null,
+        };
+        let params: Map<String, Value> = request
+            .url()
+            .query_pairs()
+            .map(|(key, value)| (key.into_owned(), Value::String(value.into_owned())))
+            .collect();
+
+        let segments: Vec<&str> = path.split('/').collect();
+        let value = match (&method, segments.as_slice()) {
+            (&Method::POST, ["v2", "orders"]) => self.order(&payload, Value::Null),
+            (&Method::PATCH, ["v2", "orders", id]) => {
+                let original = fetch(client, &format!("v2/orders/{id}")).await?;
+                self.replacement(original, &payload)
+            }
+            (&Method::DELETE, ["v2", "orders", _]) => Value::Null,
+            (&Method::DELETE, ["v2", "orders"]) => {
+                let request = client
+                    .request(Method::GET, "v2/orders")?
+                    .query(&[("status", "open"), ("limit", "500")]);
+                let open = client.fetch_json(request).await?;
+                let statuses = as_array(open)
+                    .into_iter()
+                    .map(|order| json!({ "id": order["id"], "status": 200, "body": null }))
+                    .collect();
+                Value::Array(statuses)
+            }
+            (&Method::POST, ["v2", "positions", .., "exercise" | "do-not-exercise"]) => Value::Null,
+            (&Method::DELETE, ["v2", "positions"]) => {
+                let positions = fetch(client, "v2/positions").await?;
+                let statuses = as_array(positions)
+                    .into_iter()
+                    .map(|position| {
+                        let order = self.closing_order(&position, &Map::new());
+                        json!({ "symbol": position["symbol"], "status": 200, "body": order })
+                    })
+                    .collect();
+                Value::Array(statuses)
+            }
+            (&Method::DELETE, ["v2", "positions", symbol @ ..]) => {
+                let position = fetch(client, &format!("v2/positions/{}", symbol.join("/"))).await?;
+                self.closing_order(&position, &params)
+            }
+            (&Method::POST, ["v2", "watchlists"]) => {
+                let account = fetch(client, "v2/account").await?;
+                let now = Utc::now();
+                json!({
+                    "id": self.next_id(),
+                    "account_id": account["id"],
+                    "created_at": now,
+                    "updated_at": now,
+                    "name": payload["name"],
+                    "assets": fetch_assets(client, &payload["symbols"]).await?,
+                })
+            }
+            (&Method::PUT, ["v2", "watchlists", id]) => {
+                let mut watchlist = fetch(client, &format!("v2/watchlists/{id}")).await?;
+                if !payload["name"].is_null() {
+                    watchlist["name"] = payload["name"].clone();
+                }
+                if !payload["symbols"].is_null() {
+                    watchlist["assets"] = fetch_assets(client, &payload["symbols"]).await?;
+                }
+                watchlist["updated_at"] = json!(Utc::now());
+                watchlist
+            }
+            (&Method::POST, ["v2", "watchlists", id]) => {
+                let mut watchlist = fetch(client, &format!("v2/watchlists/{id}")).await?;
+                let symbol = payload["symbol"].as_str().unwrap_or_default();
+                let asset = fetch(client, &format!("v2/assets/{symbol}")).await?;
+                let mut assets = as_array(watchlist["assets"].take());
+                assets.push(asset);
+                watchlist["assets"] = Value::Array(assets);
+                watchlist["updated_at"] = json!(Utc::now());
+                watchlist
+            }
+            (&Method::DELETE, ["v2", "watchlists", id, symbol]) => {
+                let mut watchlist = fetch(client, &format!("v2/watchlists/{id}")).await?;
+                let assets = as_array(watchlist["assets"].take())
+                    .into_iter()
+                    .filter(|asset| asset["symbol"] != *symbol)
+                    .collect();
+                watchlist["assets"] = Value::Array(assets);
+                watchlist["updated_at"] = json!(Utc::now());
+                watchlist
+            }
+            (&Method::DELETE, ["v2", "watchlists", _]) => Value::Null,
+            (&Method::PATCH, ["v2", "account", "configurations"]) => {
+                let mut config = fetch(client, "v2/account/configurations").await?;
+                merge(&mut config, &payload);
+                config
+            }
+            (&Method::POST, ["v2", "wallets", "transfers"]) => json!({
+                "id": self.next_id(),
+                "tx_hash": null,
+                "direction": "OUTGOING",
+                "status": "PROCESSING",
+                "amount": payload["amount"],
+                "asset": payload["asset"],
+                "to_address": payload["address"],
+                "created_at": Utc::now(),
+            }),
+            (&Method::POST, ["v2", "wallets", "whitelists"]) => json!({
+                "id": self.next_id(),
+                "asset": payload["asset"],
+                "address": payload["address"],
+                "status": "PENDING",
+                "created_at": Utc::now(),
+            }),
+            (&Method::DELETE, ["v2", "wallets", "whitelists", _]) => Value::Null,
+            _ => return Err(unsupported(None)),
+        };
+        Ok(Synthesized {
+            method,
+            path,
+            value,
+        })
+    }
+
+    fn next_id(&self) -> String {
+        let n = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        format!("dry-run-{n}")
+    }
+
+    /// An `accepted` order echoing the submitted `payload`, with bracket,
+    /// OCO/OTO and multi-leg children expanded into `held` legs.
+    fn order(&self, payload: &Value, asset_id: Value) -> Value {
+        let now = Utc::now();
+        let client_order_id = match &payload["client_order_id"] {
+            Value::Null => json!(generate_client_order_id()),
+            id => id.clone(),
+        };
+        let mut order = json!({
+            "id": self.next_id(),
+            "client_order_id": client_order_id,
+            "created_at": now,
+            "updated_at": now,
+            "submitted_at": now,
+            "filled_at": null,
+            "expired_at": null,
+            "canceled_at": null,
+            "asset_id": if asset_id.is_null() { json!("") } else { asset_id },
+            "symbol": payload["symbol"].as_str().unwrap_or_default(),
+            "filled_qty": "0",
+            "type": payload["type"],
+            "side": payload["side"].as_str().unwrap_or("buy"),
+            "time_in_force": payload["time_in_force"],
+            "status": "accepted",
+            "extended_hours": payload["extended_hours"].as_bool().unwrap_or(false),
+            "order_class": payload["order_class"].as_str().unwrap_or("simple"),
+        });
+        for field in [
+            "qty",
+            "notional",
+            "limit_price",
+            "stop_price",
+            "trail_price",
+            "trail_percent",
+        ] {
+            if !payload[field].is_null() {
+                order[field] = payload[field].clone();
+            }
+        }
+
+        let mut legs = Vec::new();
+        let exit_side = if payload["side"] == "sell" {
+            "buy"
+        } else {
+            "sell"
+        };
+        let take_profit = &payload["take_profit"];
+        if !take_profit.is_null() {
+            legs.push(json!({
+                "type": "limit",
+                "side": exit_side,
+                "limit_price": take_profit["limit_price"],
+            }));
+        }
+        let stop_loss = &payload["stop_loss"];
+        if !stop_loss.is_null() {
+            let order_type = if stop_loss["limit_price"].is_null() {
+                "stop"
+            } else {
+                "stop_limit"
+            };
+            legs.push(json!({
+                "type": order_type,
+                "side": exit_side,
+                "stop_price": stop_loss["stop_price"],
+                "limit_price": stop_loss["limit_price"],
+            }));
+        }
+        for leg in payload["legs"].as_array().into_iter().flatten() {
+            let ratio = decimal(&leg["ratio_qty"]).unwrap_or(Decimal::ONE);
+            let qty = decimal(&payload["qty"]).map(|qty| (qty * ratio).to_string());
+            legs.push(json!({
+                "symbol": leg["symbol"],
+                "type": "limit",
+                "side": leg["side"],
+                "qty": qty,
+            }));
+        }
+        order["legs"] = legs
+            .into_iter()
+            .map(|mut leg| {
+                for field in ["symbol", "qty", "time_in_force"] {
+                    if leg[field].is_null() {
+                        leg[field] = order[field].clone();
+                    }
+                }
+                leg["order_class"] = order["order_class"].clone();
+                let mut child = self.order(&leg, order["asset_id"].clone());
+                child["status"] = json!("held");
+                child
+            })
+            .collect();
+        order
+    }
+
+    /// `original` replaced by a new `accepted` order carrying the changes
+    /// in `payload`.
+    fn replacement(&self, mut original: Value, payload: &Value) -> Value {
+        let now = Utc::now();
+        let replaces = original["id"].take();
+        for field in ["qty", "limit_price", "stop_price", "time_in_force"] {
+            if !payload[field].is_null() {
+                original[field] = payload[field].clone();
+            }
+        }
+        if !payload["trail"].is_null() {
+            let field = if original["trail_percent"].is_null() {
+                "trail_price"
+            } else {
+                "trail_percent"
+            };
+            original[field] = payload["trail"].clone();
+        }
+        original["client_order_id"] = match &payload["client_order_id"] {
+            Value::Null => json!(generate_client_order_id()),
+            id => id.clone(),
+        };
+        original["id"] = json!(self.next_id());
+        original["replaces"] = replaces;
+        original["replaced_by"] = Value::Null;
+        original["status"] = json!("accepted");
+        original["filled_qty"] = json!("0");
+        original["filled_avg_price"] = Value::Null;
+        original["created_at"] = json!(now);
+        original["updated_at"] = json!(now);
+        original["submitted_at"] = json!(now);
+        original
+    }
+
+    /// The market order that would close `position`, sized by the
+    /// `qty` or `percentage` query parameter when one was given.
+    fn closing_order(&self, position: &Value, params: &Map<String, Value>) -> Value {
+        let held = decimal(&position["qty"]).unwrap_or_default().abs();
+        let qty = match (
+            params.get("qty").and_then(decimal),
+            params.get("percentage").and_then(decimal),
+        ) {
+            (Some(qty), _) => qty,
+            (None, Some(percentage)) => held * percentage / Decimal::ONE_HUNDRED,
+            (None, None) => held,
+        };
+        let side = if position["side"] == "short" {
+            "buy"
+        } else {
+            "sell"
+        };
+        let payload = json!({
+            "symbol": position["symbol"],
+            "side": side,
+            "type": "market",
+            "time_in_force": "day",
+            "qty": qty.normalize().to_string(),
+        });
+        self.order(&payload, position["asset_id"].clone())
+    }
+}
+
+impl TradingClient {
+    /// Send a read-only request straight to Alpaca, bypassing dry-run
+    /// interception, and return the raw JSON body.
+    pub(crate) async fn fetch_json(&self, request: reqwest::RequestBuilder) -> Result<Value> {
+        let response = request
+            .send()
+            .await
+            .map_err(|e| Error::ReqwestSend(e.into()))?;
+        Self::deserialize(response).await
+    }
+}
+
+async fn fetch(client: &TradingClient, path: &str) -> Result<Value> {
+    client.fetch_json(client.request(Method::GET, path)?).await
+}
+
+async fn fetch_assets(client: &TradingClient, symbols: &Value) -> Result<Value> {
+    let mut assets = Vec::new();
+    for symbol in symbols.as_array().into_iter().flatten() {
+        let symbol = symbol.as_str().unwrap_or_default();
+        assets.push(fetch(client, &format!("v2/assets/{symbol}")).await?);
+    }
+    Ok(Value::Array(assets))
+}
+
+fn as_array(value: Value) -> Vec<Value> {
+    match value {
+        Value::Array(values) => values,
+        _ => Vec::new(),
+    }
+}
+
+fn decimal(value: &Value) -> Option<Decimal> {
+    match value {
+        Value::String(s) => s.parse().ok(),
+        Value::Number(n) => n.to_string().parse().ok(),
+        _ => None,
+    }
+}
+
+fn merge(target: &mut Value, patch: &Value) {
+    if let (Some(target), Some(patch)) = (target.as_object_mut(), patch.as_object()) {
+        for (key, value) in patch {
+            target.insert(key.clone(), value.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::restful::trading::crypto_funding::{
+        CryptoTransferDirection, CryptoTransferStatus, WhitelistedAddressStatus,
+    };
+    use crate::restful::trading::orders::{OrderStatus, Side, StopLoss, TakeProfit};
+    use crate::{AccountType, ApiKey, OrderId};
+    use serial_test::serial;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn dry_run_client(server: &MockServer) -> TradingClient {
+        let api_key = ApiKey::new("test_key_id", "test_secret_key");
+        TradingClient::new_with_credentials(AccountType::Live, api_key)
+            .unwrap()
+            .with_base_url(&server.uri())
+            .with_dry_run()
+    }
+
+    fn dec(s: &str) -> Decimal {
+        Decimal::from_str_exact(s).unwrap()
+    }
+
+    async fn forbid(server: &MockServer, verb: &str) {
+        Mock::given(method(verb))
+            .respond_with(ResponseTemplate::new(500))
+            .expect(0)
+            .mount(server)
+            .await;
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn order_submission_is_logged_and_synthesized() {
+        let server = MockServer::start().await;
+        forbid(&server, "POST").await;
+
+        let client = dry_run_client(&server);
+        let order = client
+            .limit_order("AAPL", Side::Buy, dec("150"))
+            .qty(dec("10"))
+            .take_profit(TakeProfit::new(dec("160")))
+            .stop_loss(StopLoss::new(dec("140")))
+            .client_order_id("strategy-1")
+            .execute()
+            .await
+            .unwrap();
+
+        assert_eq!(order.id.as_str(), "dry-run-1");
+        assert_eq!(order.client_order_id.as_str(), "strategy-1");
+        assert_eq!(order.status, OrderStatus::Accepted);
+        assert_eq!(order.limit_price, Some(dec("150")));
+        assert_eq!(order.legs.len(), 2);
+        assert!(order.legs.iter().all(|leg| leg.side == Side::Sell));
+        assert_eq!(order.legs[1].stop_price, Some(dec("140")));
+
+        let log = client.dry_run_log();
+        assert_eq!(log.len(), 1);
+        assert_eq!(
+            (log[0].method.as_str(), log[0].path.as_str()),
+            ("POST", "v2/orders")
+        );
+        let body: Value = serde_json::from_str(log[0].body.as_deref().unwrap()).unwrap();
+        assert_eq!(body["take_profit"]["limit_price"], "160");
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn replacement_and_close_read_current_state() {
+        let server = MockServer::start().await;
+        forbid(&server, "PATCH").await;
+        forbid(&server, "DELETE").await;
+        Mock::given(method("GET"))
+            .and(path("/v2/orders/order-1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": "order-1",
+                "client_order_id": "original",
+                "created_at": "2024-01-02T15:30:00Z",
+                "updated_at": "2024-01-02T15:30:00Z",
+                "submitted_at": "2024-01-02T15:30:00Z",
+                "filled_at": null,
+                "expired_at": null,
+                "canceled_at": null,
+                "asset_id": "asset-1",
+                "symbol": "AAPL",
+                "qty": "10",
+                "filled_qty": "0",
+                "type": "limit",
+                "limit_price": "150",
+                "side": "buy",
+                "time_in_force": "day",
+                "status": "new",
+                "extended_hours": false,
+                "order_class": "",
+                "legs": null
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v2/positions/AAPL"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "asset_id": "asset-1",
+                "symbol": "AAPL",
+                "qty": "-8",
+                "side": "short"
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = dry_run_client(&server);
+        let replaced = client
+            .replace_order(&OrderId::new("order-1"))
+            .limit_price(dec("151"))
+            .execute()
+            .await
+            .unwrap();
+        assert_eq!(replaced.replaces, Some(OrderId::new("order-1")));
+        assert_eq!(replaced.limit_price, Some(dec("151")));
+        assert_eq!(replaced.qty, Some(dec("10")));
+
+        let close = client
+            .close_position("AAPL")
+            .percentage(dec("25"))
+            .execute()
+            .await
+            .unwrap();
+        assert_eq!(close.side, Side::Buy);
+        assert_eq!(close.qty, Some(dec("2")));
+        assert_eq!(close.asset_id.as_str(), "asset-1");
+
+        client.cancel_order(&OrderId::new("order-1")).await.unwrap();
+        let paths: Vec<_> = client
+            .dry_run_log()
+            .into_iter()
+            .map(|call| call.path)
+            .collect();
+        assert_eq!(
+            paths,
+            [
+                "v2/orders/order-1",
+                "v2/positions/AAPL",
+                "v2/orders/order-1"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn crypto_withdrawals_and_whitelist_changes_are_synthesized() {
+        let server = MockServer::start().await;
+        forbid(&server, "POST").await;
+        forbid(&server, "DELETE").await;
+
+        let client = dry_run_client(&server);
+        let transfer = client
+            .crypto_withdrawal("BTC", dec("0.05"), "bc1qexample")
+            .execute()
+            .await
+            .unwrap();
+        assert_eq!(transfer.status, CryptoTransferStatus::Processing);
+        assert_eq!(transfer.direction, CryptoTransferDirection::Outgoing);
+        assert_eq!(transfer.amount, dec("0.05"));
+        assert_eq!(transfer.to_address.as_deref(), Some("bc1qexample"));
+
+        let address = client
+            .add_whitelisted_address("BTC", "bc1qexample")
+            .await
+            .unwrap();
+        assert_eq!(address.status, WhitelistedAddressStatus::Pending);
+        assert_eq!(address.address, "bc1qexample");
+        client
+            .remove_whitelisted_address(&address.id)
+            .await
+            .unwrap();
+
+        let paths: Vec<_> = client
+            .dry_run_log()
+            .into_iter()
+            .map(|call| format!("{} {}", call.method, call.path))
+            .collect();
+        assert_eq!(
+            paths,
+            [
+                "POST v2/wallets/transfers",
+                "POST v2/wallets/whitelists",
+                "DELETE v2/wallets/whitelists/dry-run-2"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn unsupported_endpoints_are_refused_not_sent() {
+        let server = MockServer::start().await;
+        forbid(&server, "POST").await;
+
+        let client = dry_run_client(&server);
+        let request = client.request(Method::POST, "v2/unknown").unwrap();
+        let err = client
+            .send_and_deserialize::<Value>(request)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::DryRunUnsupported { .. }));
+        assert_eq!(client.dry_run_log().len(), 1);
+    }
+}
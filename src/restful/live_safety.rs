@@ -0,0 +1,656 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use reqwest::{Method, Request, Url};
+use rust_decimal::Decimal;
+use serde_json::Value;
+
+use crate::crypto::position_symbol;
+use crate::error::{Error, Result};
+
+type Confirm = dyn Fn(&LiveMutation) -> bool + Send + Sync;
+
+/// A kind of mutating call that [`LiveSafety`] can allow or refuse.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum LiveOperation {
+    /// Submit a new order.
+    SubmitOrder,
+    /// Replace an open order.
+    ReplaceOrder,
+    /// Cancel one order.
+    CancelOrder,
+    /// Cancel every open order. Bulk.
+    CancelAllOrders,
+    /// Close (part of) one position.
+    ClosePosition,
+    /// Close every position. Bulk.
+    CloseAllPositions,
+    /// Exercise, or decline to exercise, an option position.
+    ExerciseOption,
+    /// Create, edit or delete a watchlist.
+    Watchlist,
+    /// Update the account configuration.
+    AccountConfig,
+    /// Withdraw crypto to an external address. Moves funds.
+    CryptoWithdrawal,
+    /// Add or remove a whitelisted withdrawal address. Moves funds.
+    CryptoWhitelist,
+}
+
+impl LiveOperation {
+    /// Whether this operation touches every order or position at once.
+    /// Bulk operations are refused unless explicitly allowed and armed
+    /// separately; see [`LiveSafety`].
+    pub const fn is_bulk(self) -> bool {
+        matches!(self, Self::CancelAllOrders | Self::CloseAllPositions)
+    }
+
+    /// Whether this operation moves funds out of the account, or changes
+    /// where they may be moved to. Such operations are refused unless
+    /// explicitly allowed; see [`LiveSafety`].
+    pub const fn moves_funds(self) -> bool {
+        matches!(self, Self::CryptoWithdrawal | Self::CryptoWhitelist)
+    }
+
+    fn classify(method: &Method, path: &str) -> Option<Self> {
+        let segments: Vec<&str> = path.split('/').collect();
+        let operation = match (method, segments.as_slice()) {
+            (&Method::POST, ["v2", "orders"]) => Self::SubmitOrder,
+            (&Method::PATCH, ["v2", "orders", _]) => Self::ReplaceOrder,
+            (&Method::DELETE, ["v2", "orders", _]) => Self::CancelOrder,
+            (&Method::DELETE, ["v2", "orders"]) => Self::CancelAllOrders,
+            (&Method::POST, ["v2", "positions", .., "exercise" | "do-not-exercise"]) => {
+                Self::ExerciseOption
+            }
+            (&Method::DELETE, ["v2", "positions"]) => Self::CloseAllPositions,
+            (&Method::DELETE, ["v2", "positions", _, ..]) => Self::ClosePosition,
+            (_, ["v2", "watchlists", ..]) => Self::Watchlist,
+            (&Method::PATCH, ["v2", "account", "configurations"]) => Self::AccountConfig,
+            (&Method::POST, ["v2", "wallets", "transfers"]) => Self::CryptoWithdrawal,
+            (&Method::POST, ["v2", "wallets", "whitelists"])
+            | (&Method::DELETE, ["v2", "wallets", "whitelists", _]) => Self::CryptoWhitelist,
+            _ => return None,
+        };
+        Some(operation)
+    }
+}
+
+/// A mutating call awaiting a [`LiveSafety`] confirmation callback.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct LiveMutation {
+    /// What the call does.
+    pub operation: LiveOperation,
+    /// Request path, e.g. `"v2/orders"`.
+    pub path: String,
+    /// The exact JSON body that would be sent.
+    pub body: Option<String>,
+}
+
+/// Why [`LiveSafety`] refused a mutating call on a live account.
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+#[non_exhaustive]
+pub enum LiveSafetyViolation {
+    /// The client has not been [armed](crate::TradingClient::arm_live_trading)
+    /// and no confirmation callback is set.
+    #[error("live trading is not armed for {0:?}")]
+    NotArmed(LiveOperation),
+    /// A bulk operation was attempted without a
+    /// [bulk arming](crate::TradingClient::arm_bulk_operation) or
+    /// confirmation.
+    #[error("bulk operation {0:?} is not armed")]
+    BulkNotArmed(LiveOperation),
+    /// The confirmation callback declined the call.
+    #[error("{0:?} was declined")]
+    Declined(LiveOperation),
+    /// The operation is not on the allow list.
+    #[error("operation {0:?} is not allowed")]
+    OperationNotAllowed(LiveOperation),
+    /// The request is not an operation the interlock recognizes, so it is
+    /// refused rather than let through unchecked.
+    #[error("unrecognized live mutation {method} {path}")]
+    Unrecognized {
+        /// HTTP method of the request.
+        method: String,
+        /// Request path.
+        path: String,
+    },
+    /// The symbol is not on the allow list.
+    #[error("symbol {0} is not allowed")]
+    SymbolNotAllowed(String),
+    /// The order quantity is above the per-order limit.
+    #[error("order quantity {qty} exceeds {max}")]
+    OrderQty {
+        /// Requested quantity.
+        qty: Decimal,
+        /// Configured maximum.
+        max: Decimal,
+    },
+    /// The order's value is above the per-order limit.
+    #[error("order notional {notional} exceeds {max}")]
+    OrderNotional {
+        /// Notional, or quantity times limit (or stop) price.
+        notional: Decimal,
+        /// Configured maximum.
+        max: Decimal,
+    },
+    /// A notional limit is configured but the order carries no notional or
+    /// price to value it with, as with market orders sized by quantity.
+    #[error("cannot value the order against the notional limit")]
+    Unpriced,
+}
+
+/// Opt-in interlock for mutating calls on an [`AccountType::Live`]
+/// client, installed with
+/// [`TradingClient::with_live_safety`](crate::TradingClient::with_live_safety).
+///
+/// Once installed, every mutating call on a live client must pass the
+/// interlock before it is sent:
+///
+/// - the client must be [armed](crate::TradingClient::arm_live_trading),
+///   or the [`confirm_with`](Self::confirm_with) callback must approve the
+///   call;
+/// - the operation must be allowed — by default everything except the
+///   bulk operations and the ones that [move funds](LiveOperation::moves_funds);
+/// - order symbols must be on the allow list, if one is set, and orders
+///   must fit under the quantity and notional limits; closes given by
+///   quantity must fit under the quantity limit.
+///
+/// Bulk operations ([`cancel_all_orders`](crate::TradingClient::cancel_all_orders),
+/// [`close_all_positions`](crate::TradingClient::close_all_positions)) must
+/// also be named in [`allow_operations`](Self::allow_operations), and each
+/// call needs its own [`arm_bulk_operation`](crate::TradingClient::arm_bulk_operation)
+/// or callback approval; arming the client is not enough. Crypto
+/// withdrawals and whitelist changes must likewise be named in
+/// `allow_operations`, after which they are armed like any other call.
+///
+/// Paper clients are unaffected. Refusals surface as
+/// [`Error::LiveSafety`](crate::Error::LiveSafety) and nothing is sent.
+///
+/// [`AccountType::Live`]: crate::AccountType::Live
+#[derive(Clone, Default)]
+#[must_use]
+pub struct LiveSafety {
+    operations: Option<HashSet<LiveOperation>>,
+    symbols: Option<HashSet<String>>,
+    max_order_qty: Option<Decimal>,
+    max_order_notional: Option<Decimal>,
+    confirm: Option<Arc<Confirm>>,
+}
+
+impl fmt::Debug for LiveSafety {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LiveSafety")
+            .field("operations", &self.operations)
+            .field("symbols", &self.symbols)
+            .field("max_order_qty", &self.max_order_qty)
+            .field("max_order_notional", &self.max_order_notional)
+            .field("confirm", &self.confirm.is_some())
+            .finish()
+    }
+}
+
+impl LiveSafety {
+    /// An interlock that requires arming, allows every operation that is
+    /// neither bulk nor moves funds, and sets no symbol or size limits.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only allow these operations. Bulk operations and operations that
+    /// move funds must be listed here to be possible at all.
+    pub fn allow_operations(mut self, operations: &[LiveOperation]) -> Self {
+        self.operations = Some(operations.iter().copied().collect());
+        self
+    }
+
+    /// Only allow orders and closes in these symbols. Crypto pairs match
+    /// in either the slashed or the compact form.
+    pub fn allow_symbols(mut self, symbols: &[&str]) -> Self {
+        self.symbols = Some(
+            symbols
+                .iter()
+                .map(|s| position_symbol(s).into_owned())
+                .collect(),
+        );
+        self
+    }
+
+    /// Largest quantity a new or replaced order, or a close given by
+    /// quantity, may have.
+    pub fn max_order_qty(mut self, max: Decimal) -> Self {
+        self.max_order_qty = Some(max);
+        self
+    }
+
+    /// Largest value a new or replaced order may have.
+    pub fn max_order_notional(mut self, max: Decimal) -> Self {
+        self.max_order_notional = Some(max);
+        self
+    }
+
+    /// Ask `confirm` about each call the client is not armed for. Returning
+    /// `false` refuses the call with [`LiveSafetyViolation::Declined`].
+    pub fn confirm_with(
+        mut self,
+        confirm: impl Fn(&LiveMutation) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.confirm = Some(Arc::new(confirm));
+        self
+    }
+
+    fn allows(&self, operation: LiveOperation) -> bool {
+        match &self.operations {
+            Some(operations) => operations.contains(&operation),
+            None => !operation.is_bulk() && !operation.moves_funds(),
+        }
+    }
+
+    fn check_symbol(&self, symbol: &str) -> Result<(), LiveSafetyViolation> {
+        match &self.symbols {
+            Some(symbols) if !symbols.contains(position_symbol(symbol).as_ref()) => {
+                Err(LiveSafetyViolation::SymbolNotAllowed(symbol.to_string()))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn check_size(&self, payload: &Value) -> Result<(), LiveSafetyViolation> {
+        let qty = decimal(&payload["qty"]);
+        if let (Some(qty), Some(max)) = (qty, self.max_order_qty)
+            && qty > max
+        {
+            return Err(LiveSafetyViolation::OrderQty { qty, max });
+        }
+        if let Some(max) = self.max_order_notional {
+            let price = decimal(&payload["limit_price"]).or(decimal(&payload["stop_price"]));
+            let notional = match (decimal(&payload["notional"]), qty, price) {
+                (Some(notional), _, _) => notional,
+                (None, Some(qty), Some(price)) => qty * price.abs(),
+                _ => return Err(LiveSafetyViolation::Unpriced),
+            };
+            if notional > max {
+                return Err(LiveSafetyViolation::OrderNotional { notional, max });
+            }
+        }
+        Ok(())
+    }
+
+    fn check_limits(
+        &self,
+        operation: LiveOperation,
+        url: &Url,
+        payload: &Value,
+    ) -> Result<(), LiveSafetyViolation> {
+        let path = url.path().trim_start_matches('/');
+        match operation {
+            LiveOperation::SubmitOrder => {
+                if let Some(symbol) = payload["symbol"].as_str() {
+                    self.check_symbol(symbol)?;
+                }
+                for leg in payload["legs"].as_array().into_iter().flatten() {
+                    self.check_symbol(leg["symbol"].as_str().unwrap_or_default())?;
+                }
+                self.check_size(payload)
+            }
+            LiveOperation::ReplaceOrder => self.check_size(payload),
+            LiveOperation::ClosePosition => {
+                self.check_symbol(path.trim_start_matches("v2/positions/"))?;
+                let qty = url
+                    .query_pairs()
+                    .find(|(key, _)| key == "qty")
+                    .and_then(|(_, qty)| qty.parse::<Decimal>().ok());
+                match (qty, self.max_order_qty) {
+                    (Some(qty), Some(max)) if qty > max => {
+                        Err(LiveSafetyViolation::OrderQty { qty, max })
+                    }
+                    _ => Ok(()),
+                }
+            }
+            LiveOperation::ExerciseOption => {
+                let symbol = path
+                    .trim_start_matches("v2/positions/")
+                    .trim_end_matches("/exercise")
+                    .trim_end_matches("/do-not-exercise");
+                self.check_symbol(symbol)
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// A [`LiveSafety`] together with the arming state shared by every clone
+/// of the client it is installed on.
+#[derive(Debug)]
+pub(crate) struct LiveInterlock {
+    safety: LiveSafety,
+    armed: AtomicBool,
+    bulk_armed: AtomicBool,
+}
+
+impl LiveInterlock {
+    pub(crate) fn new(safety: LiveSafety) -> Self {
+        Self {
+            safety,
+            armed: AtomicBool::new(false),
+            bulk_armed: AtomicBool::new(false),
+        }
+    }
+
+    pub(crate) fn arm(&self, armed: bool) {
+        self.armed.store(armed, Ordering::SeqCst);
+        if !armed {
+            self.bulk_armed.store(false, Ordering::SeqCst);
+        }
+    }
+
+    pub(crate) fn arm_bulk(&self) {
+        self.bulk_armed.store(true, Ordering::SeqCst);
+    }
+
+    pub(crate) fn is_armed(&self) -> bool {
+        self.armed.load(Ordering::SeqCst)
+    }
+
+    /// Refuse `request` unless the interlock lets it through. A bulk
+    /// arming is used up by the call it lets through.
+    pub(crate) fn check(&self, request: &Request) -> Result<()> {
+        self.evaluate(request).map_err(Error::LiveSafety)
+    }
+
+    fn evaluate(&self, request: &Request) -> Result<(), LiveSafetyViolation> {
+        let path = request.url().path().trim_start_matches('/');
+        let operation = LiveOperation::classify(request.method(), path).ok_or_else(|| {
+            LiveSafetyViolation::Unrecognized {
+                method: request.method().to_string(),
+                path: path.to_string(),
+            }
+        })?;
+        if !self.safety.allows(operation) {
+            return Err(LiveSafetyViolation::OperationNotAllowed(operation));
+        }
+        let body = request
+            .body()
+            .and_then(|body| body.as_bytes())
+            .map(|bytes| String::from_utf8_lossy(bytes).into_owned());
+        let payload = body
+            .as_deref()
+            .and_then(|body| serde_json::from_str(body).ok())
+            .unwrap_or(Value::Null);
+        self.safety
+            .check_limits(operation, request.url(), &payload)?;
+
+        let armed = if operation.is_bulk() {
+            self.bulk_armed.swap(false, Ordering::SeqCst)
+        } else {
+            self.is_armed()
+        };
+        if armed {
+            return Ok(());
+        }
+        let Some(confirm) = &self.safety.confirm else {
+            return Err(if operation.is_bulk() {
+                LiveSafetyViolation::BulkNotArmed(operation)
+            } else {
+                LiveSafetyViolation::NotArmed(operation)
+            });
+        };
+        let mutation = LiveMutation {
+            operation,
+            path: path.to_string(),
+            body,
+        };
+        if confirm(&mutation) {
+            Ok(())
+        } else {
+            Err(LiveSafetyViolation::Declined(operation))
+        }
+    }
+}
+
+fn decimal(value: &Value) -> Option<Decimal> {
+    value.as_str().and_then(|s| s.parse().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::restful::TradingClient;
+    use crate::restful::trading::orders::Side;
+    use crate::{AccountType, ApiKey, WhitelistedAddressId};
+    use serial_test::serial;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn client(server: &MockServer, account_type: AccountType, safety: LiveSafety) -> TradingClient {
+        let api_key = ApiKey::new("test_key_id", "test_secret_key");
+        TradingClient::new_with_credentials(account_type, api_key)
+            .unwrap()
+            .with_base_url(&server.uri())
+            .with_dry_run()
+            .with_live_safety(safety)
+    }
+
+    fn dec(s: &str) -> Decimal {
+        Decimal::from_str_exact(s).unwrap()
+    }
+
+    fn violation(err: Error) -> LiveSafetyViolation {
+        match err {
+            Error::LiveSafety(violation) => violation,
+            other => panic!("expected a live safety refusal, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn mutations_require_arming_and_respect_limits() {
+        let server = MockServer::start().await;
+        let safety = LiveSafety::new()
+            .allow_symbols(&["AAPL"])
+            .max_order_qty(dec("10"));
+        let client = client(&server, AccountType::Live, safety);
+
+        let err = client
+            .market_order("AAPL", Side::Buy)
+            .qty(dec("1"))
+            .execute()
+            .await
+            .unwrap_err();
+        assert_eq!(
+            violation(err),
+            LiveSafetyViolation::NotArmed(LiveOperation::SubmitOrder)
+        );
+
+        client.arm_live_trading();
+        let err = client
+            .market_order("TSLA", Side::Buy)
+            .qty(dec("1"))
+            .execute()
+            .await
+            .unwrap_err();
+        assert_eq!(
+            violation(err),
+            LiveSafetyViolation::SymbolNotAllowed("TSLA".into())
+        );
+        let err = client
+            .market_order("AAPL", Side::Buy)
+            .qty(dec("11"))
+            .execute()
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            violation(err),
+            LiveSafetyViolation::OrderQty { .. }
+        ));
+        client
+            .market_order("AAPL", Side::Buy)
+            .qty(dec("10"))
+            .execute()
+            .await
+            .unwrap();
+        assert_eq!(client.dry_run_log().len(), 1);
+
+        let err = client
+            .close_position("AAPL")
+            .qty(dec("11"))
+            .execute()
+            .await
+            .unwrap_err();
+        assert_eq!(
+            violation(err),
+            LiveSafetyViolation::OrderQty {
+                qty: dec("11"),
+                max: dec("10")
+            }
+        );
+        assert_eq!(client.dry_run_log().len(), 1);
+
+        client.disarm_live_trading();
+        assert!(!client.is_live_trading_armed());
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn bulk_operations_need_their_own_one_shot_arming() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v2/orders"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([])))
+            .mount(&server)
+            .await;
+
+        let default_safety = client(&server, AccountType::Live, LiveSafety::new());
+        default_safety.arm_live_trading();
+        default_safety.arm_bulk_operation();
+        let err = default_safety.cancel_all_orders().await.unwrap_err();
+        assert_eq!(
+            violation(err),
+            LiveSafetyViolation::OperationNotAllowed(LiveOperation::CancelAllOrders)
+        );
+
+        let safety = LiveSafety::new().allow_operations(&[LiveOperation::CancelAllOrders]);
+        let client = client(&server, AccountType::Live, safety);
+        client.arm_live_trading();
+        let err = client.cancel_all_orders().await.unwrap_err();
+        assert_eq!(
+            violation(err),
+            LiveSafetyViolation::BulkNotArmed(LiveOperation::CancelAllOrders)
+        );
+        client.arm_bulk_operation();
+        client.cancel_all_orders().await.unwrap();
+        assert!(client.cancel_all_orders().await.is_err());
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn funding_operations_must_be_allowed_explicitly() {
+        let server = MockServer::start().await;
+        let default_safety = client(&server, AccountType::Live, LiveSafety::new());
+        default_safety.arm_live_trading();
+        let err = default_safety
+            .crypto_withdrawal("BTC", dec("0.05"), "bc1qexample")
+            .execute()
+            .await
+            .unwrap_err();
+        assert_eq!(
+            violation(err),
+            LiveSafetyViolation::OperationNotAllowed(LiveOperation::CryptoWithdrawal)
+        );
+        let err = default_safety
+            .add_whitelisted_address("BTC", "bc1qexample")
+            .await
+            .unwrap_err();
+        assert_eq!(
+            violation(err),
+            LiveSafetyViolation::OperationNotAllowed(LiveOperation::CryptoWhitelist)
+        );
+        let err = default_safety
+            .remove_whitelisted_address(&WhitelistedAddressId::new("address-1"))
+            .await
+            .unwrap_err();
+        assert_eq!(
+            violation(err),
+            LiveSafetyViolation::OperationNotAllowed(LiveOperation::CryptoWhitelist)
+        );
+        assert!(default_safety.dry_run_log().is_empty());
+
+        let safety = LiveSafety::new().allow_operations(&[
+            LiveOperation::CryptoWithdrawal,
+            LiveOperation::CryptoWhitelist,
+        ]);
+        let client = client(&server, AccountType::Live, safety);
+        let err = client
+            .crypto_withdrawal("BTC", dec("0.05"), "bc1qexample")
+            .execute()
+            .await
+            .unwrap_err();
+        assert_eq!(
+            violation(err),
+            LiveSafetyViolation::NotArmed(LiveOperation::CryptoWithdrawal)
+        );
+        client.arm_live_trading();
+        client
+            .crypto_withdrawal("BTC", dec("0.05"), "bc1qexample")
+            .execute()
+            .await
+            .unwrap();
+        client
+            .remove_whitelisted_address(&WhitelistedAddressId::new("address-1"))
+            .await
+            .unwrap();
+        assert_eq!(client.dry_run_log().len(), 2);
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn confirmation_callback_stands_in_for_arming() {
+        let server = MockServer::start().await;
+        let safety = LiveSafety::new().confirm_with(|mutation| {
+            mutation
+                .body
+                .as_deref()
+                .is_some_and(|body| body.contains("\"AAPL\""))
+        });
+        let client = client(&server, AccountType::Live, safety.clone());
+        client
+            .market_order("AAPL", Side::Buy)
+            .qty(dec("1"))
+            .execute()
+            .await
+            .unwrap();
+        let err = client
+            .market_order("MSFT", Side::Buy)
+            .qty(dec("1"))
+            .execute()
+            .await
+            .unwrap_err();
+        assert_eq!(
+            violation(err),
+            LiveSafetyViolation::Declined(LiveOperation::SubmitOrder)
+        );
+
+        let paper = self::client(&server, AccountType::Paper, LiveSafety::new());
+        paper
+            .market_order("MSFT", Side::Buy)
+            .qty(dec("1"))
+            .execute()
+            .await
+            .unwrap();
+    }
+
+    #[test]
+    fn crypto_symbols_match_in_either_form() {
+        let safety = LiveSafety::new().allow_symbols(&["BTC/USD", "ETHUSD"]);
+        for symbol in ["BTC/USD", "BTCUSD", "ETH/USD", "ETHUSD"] {
+            assert_eq!(safety.check_symbol(symbol), Ok(()), "{symbol}");
+        }
+        assert_eq!(
+            safety.check_symbol("SOL/USD"),
+            Err(LiveSafetyViolation::SymbolNotAllowed("SOL/USD".to_string()))
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use crate::restful::{MarketDataClient, SortDirection, market_data::TimeFrame};
+use crate::restful::{MarketDataClient, SortDirection, join_symbols, market_data::TimeFrame};
 use chrono::{DateTime, Utc};
 use reqwest::Method;
 use serde::{Deserialize, Serialize};
@@ -112,16 +112,16 @@ impl CryptoBarsRequest<'_> {
 
 impl MarketDataClient {
     /// Request crypto bars.
-    pub fn crypto_bars<'a>(
+    pub fn crypto_bars<'a, S: AsRef<str>>(
         &'a self,
-        symbols: &[&str],
+        symbols: &[S],
         timeframe: TimeFrame,
         loc: CryptoLocation,
     ) -> CryptoBarsRequest<'a> {
         CryptoBarsRequest {
             client: self,
             loc,
-            symbols: symbols.join(","),
+            symbols: join_symbols(symbols),
             timeframe,
             start: None,
             end: None,
@@ -132,15 +132,15 @@ impl MarketDataClient {
     }
 
     /// Get the latest crypto bars.
-    pub async fn crypto_latest_bars(
+    pub async fn crypto_latest_bars<S: AsRef<str>>(
         &self,
-        symbols: &[&str],
+        symbols: &[S],
         loc: CryptoLocation,
     ) -> crate::Result<std::collections::HashMap<String, CryptoBar>> {
         let path = format!("v1beta3/crypto/{loc}/latest/bars");
         let request = self
             .request(Method::GET, &path)?
-            .query(&[("symbols", symbols.join(","))]);
+            .query(&[("symbols", join_symbols(symbols))]);
         let response: LatestBarsResponse = self.send_and_deserialize(request).await?;
         Ok(response.bars)
     }
@@ -1,4 +1,4 @@
-use crate::restful::MarketDataClient;
+use crate::restful::{MarketDataClient, join_symbols};
 use reqwest::Method;
 use serde::Deserialize;
 
@@ -11,15 +11,15 @@ struct OrderbooksResponse {
 
 impl MarketDataClient {
     /// Get the latest crypto orderbooks.
-    pub async fn crypto_latest_orderbooks(
+    pub async fn crypto_latest_orderbooks<S: AsRef<str>>(
         &self,
-        symbols: &[&str],
+        symbols: &[S],
         loc: CryptoLocation,
     ) -> crate::Result<std::collections::HashMap<String, CryptoOrderbook>> {
         let path = format!("v1beta3/crypto/{loc}/latest/orderbooks");
         let request = self
             .request(Method::GET, &path)?
-            .query(&[("symbols", symbols.join(","))]);
+            .query(&[("symbols", join_symbols(symbols))]);
         let response: OrderbooksResponse = self.send_and_deserialize(request).await?;
         Ok(response.orderbooks)
     }
@@ -1,4 +1,4 @@
-use crate::restful::MarketDataClient;
+use crate::restful::{MarketDataClient, join_symbols};
 use reqwest::Method;
 use serde::Deserialize;
 
@@ -11,15 +11,15 @@ struct SnapshotsResponse {
 
 impl MarketDataClient {
     /// Get crypto snapshots.
-    pub async fn crypto_snapshots(
+    pub async fn crypto_snapshots<S: AsRef<str>>(
         &self,
-        symbols: &[&str],
+        symbols: &[S],
         loc: CryptoLocation,
     ) -> crate::Result<std::collections::HashMap<String, CryptoSnapshot>> {
         let path = format!("v1beta3/crypto/{loc}/snapshots");
         let request = self
             .request(Method::GET, &path)?
-            .query(&[("symbols", symbols.join(","))]);
+            .query(&[("symbols", join_symbols(symbols))]);
         let response: SnapshotsResponse = self.send_and_deserialize(request).await?;
         Ok(response.snapshots)
     }
@@ -1,4 +1,4 @@
-use crate::restful::{MarketDataClient, SortDirection};
+use crate::restful::{MarketDataClient, SortDirection, join_symbols};
 use chrono::{DateTime, Utc};
 use reqwest::Method;
 use serde::{Deserialize, Serialize};
@@ -111,15 +111,15 @@ impl CryptoTradesRequest<'_> {
 
 impl MarketDataClient {
     /// Request historical crypto trades.
-    pub fn crypto_trades<'a>(
+    pub fn crypto_trades<'a, S: AsRef<str>>(
         &'a self,
-        symbols: &[&str],
+        symbols: &[S],
         loc: CryptoLocation,
     ) -> CryptoTradesRequest<'a> {
         CryptoTradesRequest {
             client: self,
             loc,
-            symbols: symbols.join(","),
+            symbols: join_symbols(symbols),
             start: None,
             end: None,
             limit: None,
@@ -129,15 +129,15 @@ impl MarketDataClient {
     }
 
     /// Get the latest crypto trades.
-    pub async fn crypto_latest_trades(
+    pub async fn crypto_latest_trades<S: AsRef<str>>(
         &self,
-        symbols: &[&str],
+        symbols: &[S],
         loc: CryptoLocation,
     ) -> crate::Result<std::collections::HashMap<String, CryptoTrade>> {
         let path = format!("v1beta3/crypto/{loc}/latest/trades");
         let request = self
             .request(Method::GET, &path)?
-            .query(&[("symbols", symbols.join(","))]);
+            .query(&[("symbols", join_symbols(symbols))]);
         let response: LatestTradesResponse = self.send_and_deserialize(request).await?;
         Ok(response.trades)
     }
@@ -211,7 +211,7 @@ mod tests {
     async fn empty_symbols_short_circuits_without_request() {
         let client = paper_client();
         let result = client
-            .crypto_trades(&[], CryptoLocation::Us)
+            .crypto_trades(&[] as &[&str], CryptoLocation::Us)
             .execute()
             .await
             .unwrap();
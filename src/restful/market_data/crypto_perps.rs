@@ -0,0 +1,317 @@
+//! Bars, trades, quotes and orderbooks share their wire shape with spot
+//! crypto, so the [`crate::restful::market_data::crypto`] types are reused.
+//! Perpetual-specific pricing (index and mark price, funding rate, open
+//! interest) is modelled by
+//! [`CryptoPerpPricing`](crate::restful::market_data::crypto_perps::CryptoPerpPricing).
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use reqwest::Method;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+
+use crate::restful::{MarketDataClient, join_symbols};
+
+use super::crypto::{CryptoBar, CryptoOrderbook, CryptoQuote, CryptoTrade};
+
+/// Crypto perpetual futures venue.
+#[derive(Clone, Copy, Debug, Serialize, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CryptoPerpLocation {
+    /// Alpaca's global perpetual futures venue.
+    #[serde(rename = "global")]
+    Global,
+}
+
+impl CryptoPerpLocation {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Global => "global",
+        }
+    }
+}
+
+impl std::fmt::Display for CryptoPerpLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Latest pricing data for a perpetual futures contract.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[non_exhaustive]
+pub struct CryptoPerpPricing {
+    /// The pricing timestamp.
+    #[serde(rename = "t")]
+    pub timestamp: DateTime<Utc>,
+    /// The index price of the underlying spot pair.
+    #[serde(rename = "ip")]
+    pub index_price: f64,
+    /// The mark price used for margining and liquidation.
+    #[serde(rename = "mp")]
+    pub mark_price: f64,
+    /// The current funding rate, as a fraction per funding interval.
+    #[serde(rename = "fr")]
+    pub funding_rate: f64,
+    /// Open interest in contracts.
+    #[serde(rename = "oi")]
+    pub open_interest: f64,
+    /// When the next funding payment is exchanged.
+    #[serde(rename = "ft")]
+    pub next_funding_time: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LatestBarsResponse {
+    bars: HashMap<String, CryptoBar>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LatestTradesResponse {
+    trades: HashMap<String, CryptoTrade>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LatestQuotesResponse {
+    quotes: HashMap<String, CryptoQuote>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LatestOrderbooksResponse {
+    orderbooks: HashMap<String, CryptoOrderbook>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LatestPricingResponse {
+    pricing: HashMap<String, CryptoPerpPricing>,
+}
+
+impl MarketDataClient {
+    /// Get the latest minute bars for perpetual futures contracts.
+    pub async fn crypto_perp_latest_bars<S: AsRef<str>>(
+        &self,
+        symbols: &[S],
+        loc: CryptoPerpLocation,
+    ) -> crate::Result<HashMap<String, CryptoBar>> {
+        let response: LatestBarsResponse = self.crypto_perp_latest(symbols, loc, "bars").await?;
+        Ok(response.bars)
+    }
+
+    /// Get the latest trades for perpetual futures contracts.
+    pub async fn crypto_perp_latest_trades<S: AsRef<str>>(
+        &self,
+        symbols: &[S],
+        loc: CryptoPerpLocation,
+    ) -> crate::Result<HashMap<String, CryptoTrade>> {
+        let response: LatestTradesResponse =
+            self.crypto_perp_latest(symbols, loc, "trades").await?;
+        Ok(response.trades)
+    }
+
+    /// Get the latest quotes for perpetual futures contracts.
+    pub async fn crypto_perp_latest_quotes<S: AsRef<str>>(
+        &self,
+        symbols: &[S],
+        loc: CryptoPerpLocation,
+    ) -> crate::Result<HashMap<String, CryptoQuote>> {
+        let response: LatestQuotesResponse =
+            self.crypto_perp_latest(symbols, loc, "quotes").await?;
+        Ok(response.quotes)
+    }
+
+    /// Get the latest orderbooks for perpetual futures contracts.
+    pub async fn crypto_perp_latest_orderbooks<S: AsRef<str>>(
+        &self,
+        symbols: &[S],
+        loc: CryptoPerpLocation,
+    ) -> crate::Result<HashMap<String, CryptoOrderbook>> {
+        let response: LatestOrderbooksResponse =
+            self.crypto_perp_latest(symbols, loc, "orderbooks").await?;
+        Ok(response.orderbooks)
+    }
+
+    /// Get the latest index price, mark price, funding rate and open
+    /// interest for perpetual futures contracts.
+    pub async fn crypto_perp_latest_pricing<S: AsRef<str>>(
+        &self,
+        symbols: &[S],
+        loc: CryptoPerpLocation,
+    ) -> crate::Result<HashMap<String, CryptoPerpPricing>> {
+        let response: LatestPricingResponse =
+            self.crypto_perp_latest(symbols, loc, "pricing").await?;
+        Ok(response.pricing)
+    }
+
+    async fn crypto_perp_latest<T: DeserializeOwned, S: AsRef<str>>(
+        &self,
+        symbols: &[S],
+        loc: CryptoPerpLocation,
+        kind: &str,
+    ) -> crate::Result<T> {
+        let path = format!("v1beta1/crypto-perps/{loc}/latest/{kind}");
+        let request = self
+            .request(Method::GET, &path)?
+            .query(&[("symbols", join_symbols(symbols))]);
+        self.send_and_deserialize(request).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AccountType;
+    use serial_test::serial;
+    use std::env;
+    use wiremock::matchers::{method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn paper_client() -> MarketDataClient {
+        unsafe {
+            if env::var("ALPACA_PAPER_API_KEY_ID").is_err() {
+                env::set_var("ALPACA_PAPER_API_KEY_ID", "test_key_id");
+            }
+            if env::var("ALPACA_PAPER_API_SECRET_KEY").is_err() {
+                env::set_var("ALPACA_PAPER_API_SECRET_KEY", "test_secret_key");
+            }
+        }
+        MarketDataClient::new(AccountType::Paper).unwrap()
+    }
+
+    async fn mock_latest(server: &MockServer, kind: &str, body: serde_json::Value) {
+        Mock::given(method("GET"))
+            .and(path(format!("/v1beta1/crypto-perps/global/latest/{kind}")))
+            .and(query_param("symbols", "BTC-PERP,ETH-PERP"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(body))
+            .expect(1)
+            .mount(server)
+            .await;
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn latest_pricing_decodes_funding_and_mark_price() {
+        let server = MockServer::start().await;
+        mock_latest(
+            &server,
+            "pricing",
+            serde_json::json!({
+                "pricing": {
+                    "BTC-PERP": {
+                        "t": "2026-05-07T13:30:00Z",
+                        "ip": 103250.5,
+                        "mp": 103262.25,
+                        "fr": 0.0001,
+                        "oi": 1520.75,
+                        "ft": "2026-05-07T16:00:00Z"
+                    },
+                    "ETH-PERP": {
+                        "t": "2026-05-07T13:30:00Z",
+                        "ip": 3120.0,
+                        "mp": 3119.5,
+                        "fr": -0.00005,
+                        "oi": 8800.0,
+                        "ft": "2026-05-07T16:00:00Z"
+                    }
+                }
+            }),
+        )
+        .await;
+
+        let client = paper_client().with_base_url(&server.uri());
+        let pricing = client
+            .crypto_perp_latest_pricing(&["BTC-PERP", "ETH-PERP"], CryptoPerpLocation::Global)
+            .await
+            .unwrap();
+        let btc = &pricing["BTC-PERP"];
+        assert_eq!(btc.index_price, 103_250.5);
+        assert_eq!(btc.mark_price, 103_262.25);
+        assert_eq!(btc.funding_rate, 0.0001);
+        assert_eq!(btc.open_interest, 1520.75);
+        assert_eq!(
+            btc.next_funding_time.to_rfc3339(),
+            "2026-05-07T16:00:00+00:00"
+        );
+        assert!(pricing["ETH-PERP"].funding_rate < 0.0);
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn latest_market_data_reuses_spot_crypto_types() {
+        let server = MockServer::start().await;
+        let bar = serde_json::json!({"t":"2026-05-07T13:29:00Z","o":103200.0,"h":103260.0,"l":103190.0,"c":103250.0,"v":12.5,"n":420,"vw":103225.0});
+        let trade = serde_json::json!({"t":"2026-05-07T13:30:00Z","p":103250.5,"s":0.014,"i":12345,"tks":"S"});
+        let quote = serde_json::json!({"t":"2026-05-07T13:30:00Z","bp":103250.0,"bs":0.5,"ap":103251.0,"as":0.4});
+        let book = serde_json::json!({"t":"2026-05-07T13:30:00Z","b":[{"p":103250.0,"s":0.5}],"a":[{"p":103251.0,"s":0.4}]});
+        mock_latest(
+            &server,
+            "bars",
+            serde_json::json!({"bars": {"BTC-PERP": bar, "ETH-PERP": bar}}),
+        )
+        .await;
+        mock_latest(
+            &server,
+            "trades",
+            serde_json::json!({"trades": {"BTC-PERP": trade}}),
+        )
+        .await;
+        mock_latest(
+            &server,
+            "quotes",
+            serde_json::json!({"quotes": {"BTC-PERP": quote}}),
+        )
+        .await;
+        mock_latest(
+            &server,
+            "orderbooks",
+            serde_json::json!({"orderbooks": {"BTC-PERP": book}}),
+        )
+        .await;
+
+        let client = paper_client().with_base_url(&server.uri());
+        let symbols = ["BTC-PERP", "ETH-PERP"];
+        let loc = CryptoPerpLocation::Global;
+        let bars = client.crypto_perp_latest_bars(&symbols, loc).await.unwrap();
+        assert_eq!(bars.len(), 2);
+        assert_eq!(bars["BTC-PERP"].trade_count, 420);
+        let trades = client
+            .crypto_perp_latest_trades(&symbols, loc)
+            .await
+            .unwrap();
+        assert_eq!(
+            trades["BTC-PERP"].taker_side,
+            Some(crate::CryptoTakerSide::Seller)
+        );
+        let quotes = client
+            .crypto_perp_latest_quotes(&symbols, loc)
+            .await
+            .unwrap();
+        assert_eq!(quotes["BTC-PERP"].ask_price, 103_251.0);
+        let books = client
+            .crypto_perp_latest_orderbooks(&symbols, loc)
+            .await
+            .unwrap();
+        assert_eq!(books["BTC-PERP"].bids[0].size, 0.5);
+        assert!(!books["BTC-PERP"].reset);
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn not_found_surfaces_as_api_error() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v1beta1/crypto-perps/global/latest/pricing"))
+            .respond_with(ResponseTemplate::new(404).set_body_string("Not Found"))
+            .mount(&server)
+            .await;
+
+        let client = paper_client().with_base_url(&server.uri());
+        let err = client
+            .crypto_perp_latest_pricing(&["BTC-PERP"], CryptoPerpLocation::Global)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, crate::Error::ApiError { status, .. } if status == 404),
+            "{err:?}"
+        );
+    }
+}
@@ -2,6 +2,9 @@
 pub mod corporate_actions;
 /// Crypto market data endpoint types and methods.
 pub mod crypto;
+/// Crypto perpetual futures endpoint types and methods.
+#[cfg(feature = "crypto-perps")]
+pub mod crypto_perps;
 /// Fixed income endpoint types and methods.
 pub mod fixed_income;
 /// Forex endpoint types and methods.
@@ -1,9 +1,11 @@
-use crate::restful::{MarketDataClient, market_data::TimeFrame};
+use crate::OptionFeed;
+use crate::restful::market_data::pagination;
+use crate::restful::{MarketDataClient, SortDirection, join_symbols, market_data::TimeFrame};
 use chrono::{DateTime, Utc};
 use reqwest::Method;
 use serde::{Deserialize, Serialize};
 
-use super::OptionBar;
+use super::{MAX_SYMBOLS_PER_REQUEST, OptionBar};
 
 #[derive(Debug, Deserialize)]
 struct BarsResponse {
@@ -11,6 +13,11 @@ struct BarsResponse {
     next_page_token: Option<String>,
 }
 
+#[derive(Debug, Deserialize)]
+struct LatestBarsResponse {
+    bars: std::collections::HashMap<String, OptionBar>,
+}
+
 /// Builder for requesting option bars.
 #[derive(Debug, Serialize)]
 #[must_use]
@@ -23,10 +30,13 @@ pub struct OptionBarsRequest<'a> {
     start: Option<DateTime<Utc>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     end: Option<DateTime<Utc>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    /// Per-symbol cap applied client-side during pagination.
+    #[serde(skip)]
     limit: Option<usize>,
     #[serde(skip_serializing_if = "Option::is_none")]
     page_token: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sort: Option<SortDirection>,
 }
 
 impl OptionBarsRequest<'_> {
@@ -40,39 +50,104 @@ impl OptionBarsRequest<'_> {
         self.end = Some(end);
         self
     }
-    /// Cap the number of bars returned per symbol after auto-pagination.
+    /// Cap the total number of bars returned per symbol across all
+    /// auto-paginated pages.
     pub fn limit(mut self, limit: usize) -> Self {
         self.limit = Some(limit);
         self
     }
+    /// Set the result `sort` order. Defaults to ascending when unset.
+    pub fn sort(mut self, sort: SortDirection) -> Self {
+        self.sort = Some(sort);
+        self
+    }
 
     /// Execute the request, auto-paginating until all matching bars are
     /// retrieved. When `limit` is set, each symbol's series is truncated to
-    /// at most that many bars after pagination completes.
+    /// the cap as pages arrive, and pagination stops as soon as every
+    /// requested symbol has reached the cap (or the API runs out of pages).
     pub async fn execute(
         mut self,
     ) -> crate::Result<std::collections::HashMap<String, Vec<OptionBar>>> {
         let cap = self.limit;
+        if cap == Some(0) || self.symbols.is_empty() {
+            return Ok(std::collections::HashMap::new());
+        }
+        let requested: Vec<String> = self.symbols.split(',').map(str::to_string).collect();
         let mut combined: std::collections::HashMap<String, Vec<OptionBar>> =
             std::collections::HashMap::new();
         loop {
+            if let Some(cap) = cap {
+                let pending = pagination::pending_symbols(&combined, &requested, cap);
+                if pending.is_empty() {
+                    break;
+                }
+                let next_symbols = pending.join(",");
+                if next_symbols != self.symbols {
+                    // The cursor is tied to the symbol set, so narrowing
+                    // restarts the range; drop the partial series it will
+                    // refill.
+                    pagination::drop_partials(&mut combined, &pending);
+                    self.symbols = next_symbols;
+                    self.page_token = None;
+                }
+            }
             let request = self
                 .client
                 .request(Method::GET, "v1beta1/options/bars")?
                 .query(&self);
             let response: BarsResponse = self.client.send_and_deserialize(request).await?;
-            for (symbol, bars) in response.bars {
-                combined.entry(symbol).or_default().extend(bars);
-            }
+            pagination::extend_capped(&mut combined, response.bars, cap);
             match response.next_page_token {
                 Some(token) => self.page_token = Some(token),
                 None => break,
             }
         }
-        if let Some(cap) = cap {
-            for bars in combined.values_mut() {
-                bars.truncate(cap);
-            }
+        Ok(combined)
+    }
+}
+
+/// Builder for the latest minute bar of each option contract.
+///
+/// Symbols are sent in chunks of at most
+/// [`chunk_size`](Self::chunk_size) per request.
+#[derive(Debug, Serialize)]
+#[must_use]
+pub struct OptionLatestBarsRequest<'a> {
+    #[serde(skip)]
+    client: &'a MarketDataClient,
+    #[serde(skip)]
+    all_symbols: Vec<String>,
+    #[serde(skip)]
+    chunk_size: usize,
+    symbols: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    feed: Option<OptionFeed>,
+}
+
+impl OptionLatestBarsRequest<'_> {
+    /// Set the data feed to use.
+    pub fn feed(mut self, feed: OptionFeed) -> Self {
+        self.feed = Some(feed);
+        self
+    }
+    /// Most symbols sent per request. Defaults to, and is capped at, 100.
+    pub fn chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = chunk_size.clamp(1, MAX_SYMBOLS_PER_REQUEST);
+        self
+    }
+
+    /// Send the requests and merge the bars, keyed by contract symbol.
+    pub async fn execute(mut self) -> crate::Result<std::collections::HashMap<String, OptionBar>> {
+        let mut combined = std::collections::HashMap::new();
+        for chunk in std::mem::take(&mut self.all_symbols).chunks(self.chunk_size) {
+            self.symbols = chunk.join(",");
+            let request = self
+                .client
+                .request(Method::GET, "v1beta1/options/bars/latest")?
+                .query(&self);
+            let response: LatestBarsResponse = self.client.send_and_deserialize(request).await?;
+            combined.extend(response.bars);
         }
         Ok(combined)
     }
@@ -80,19 +155,120 @@ impl OptionBarsRequest<'_> {
 
 impl MarketDataClient {
     /// Request option bars.
-    pub fn option_bars<'a>(
+    pub fn option_bars<'a, S: AsRef<str>>(
         &'a self,
-        symbols: &[&str],
+        symbols: &[S],
         timeframe: TimeFrame,
     ) -> OptionBarsRequest<'a> {
         OptionBarsRequest {
             client: self,
-            symbols: symbols.join(","),
+            symbols: join_symbols(symbols),
             timeframe,
             start: None,
             end: None,
             limit: None,
             page_token: None,
+            sort: None,
         }
     }
+
+    /// Request the latest bar for each of the given contract symbols.
+    ///
+    /// Returns a builder; call [`OptionLatestBarsRequest::execute`] to send.
+    pub fn option_latest_bars<S: AsRef<str>>(&self, symbols: &[S]) -> OptionLatestBarsRequest<'_> {
+        OptionLatestBarsRequest {
+            client: self,
+            all_symbols: symbols.iter().map(|s| s.as_ref().to_string()).collect(),
+            chunk_size: MAX_SYMBOLS_PER_REQUEST,
+            symbols: String::new(),
+            feed: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AccountType;
+    use serial_test::serial;
+    use std::env;
+
+    fn paper_client() -> MarketDataClient {
+        unsafe {
+            if env::var("ALPACA_PAPER_API_KEY_ID").is_err() {
+                env::set_var("ALPACA_PAPER_API_KEY_ID", "test_key_id");
+            }
+            if env::var("ALPACA_PAPER_API_SECRET_KEY").is_err() {
+                env::set_var("ALPACA_PAPER_API_SECRET_KEY", "test_secret_key");
+            }
+        }
+        MarketDataClient::new(AccountType::Paper).unwrap()
+    }
+
+    #[test]
+    #[serial]
+    fn limit_does_not_serialize() {
+        let client = paper_client();
+        let request = client
+            .option_bars(&["SPY250321C00500000"], TimeFrame::ONE_DAY)
+            .limit(5)
+            .sort(SortDirection::Desc);
+        let query = serde_urlencoded::to_string(&request).unwrap();
+        assert!(!query.contains("limit"), "{query}");
+        assert!(query.contains("sort=desc"), "{query}");
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn latest_bars_merge_across_chunks() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, Respond, ResponseTemplate};
+
+        struct Echo;
+        impl Respond for Echo {
+            fn respond(&self, request: &wiremock::Request) -> ResponseTemplate {
+                let (_, symbols) = request
+                    .url
+                    .query_pairs()
+                    .find(|(key, _)| key == "symbols")
+                    .unwrap();
+                let bars: serde_json::Map<_, _> = symbols
+                    .split(',')
+                    .map(|s| {
+                        (
+                            s.to_string(),
+                            serde_json::json!({
+                                "t": "2026-05-07T13:30:00Z",
+                                "o": 1.0, "h": 1.2, "l": 0.9, "c": 1.1,
+                                "v": 10, "n": 3, "vw": 1.05
+                            }),
+                        )
+                    })
+                    .collect();
+                ResponseTemplate::new(200).set_body_json(serde_json::json!({ "bars": bars }))
+            }
+        }
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v1beta1/options/bars/latest"))
+            .respond_with(Echo)
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let client = paper_client().with_base_url(&server.uri());
+        let result = client
+            .option_latest_bars(&[
+                "SPY250321C00500000",
+                "SPY250321C00505000",
+                "SPY250321P00500000",
+            ])
+            .chunk_size(2)
+            .execute()
+            .await
+            .unwrap();
+        assert_eq!(result.len(), 3);
+        assert_eq!(result["SPY250321P00500000"].close, 1.1);
+    }
 }
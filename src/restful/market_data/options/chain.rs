@@ -0,0 +1,429 @@
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+
+use super::OptionSnapshot;
+use super::snapshots::OptionChainRequest;
+use crate::restful::trading::options_contracts::{
+    ListOptionContractsRequest, OptionContract, OptionType,
+};
+
+/// One contract in an [`OptionChain`] with its market snapshot, when the
+/// snapshot source had one.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct ChainEntry {
+    /// Contract details: style, open interest, close price, deliverables.
+    pub contract: OptionContract,
+    /// Latest quote, trade, greeks and implied volatility.
+    pub snapshot: Option<OptionSnapshot>,
+}
+
+impl ChainEntry {
+    /// The contract's delta, if the snapshot carries greeks.
+    pub fn delta(&self) -> Option<f64> {
+        self.snapshot.as_ref()?.greeks.as_ref()?.delta
+    }
+
+    /// The snapshot's implied volatility, if any.
+    pub fn implied_volatility(&self) -> Option<f64> {
+        self.snapshot.as_ref()?.implied_volatility
+    }
+}
+
+/// The call and put sharing a strike and root within one expiration.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct StrikeRow {
+    /// The strike price.
+    pub strike: Decimal,
+    /// The OCC root the row's contracts trade under. Contracts adjusted
+    /// for a corporate action get a root of their own (e.g. `SPY1`) and
+    /// non-standard deliverables, so they sit in a separate row.
+    pub root: String,
+    /// The call at this strike, if listed.
+    pub call: Option<ChainEntry>,
+    /// The put at this strike, if listed.
+    pub put: Option<ChainEntry>,
+}
+
+impl StrikeRow {
+    /// The call or the put.
+    pub fn side(&self, option_type: OptionType) -> Option<&ChainEntry> {
+        match option_type {
+            OptionType::Call => self.call.as_ref(),
+            OptionType::Put => self.put.as_ref(),
+        }
+    }
+}
+
+/// Every strike listed for one expiration date, ascending.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct ChainExpiration {
+    /// The expiration date.
+    pub expiration: NaiveDate,
+    /// Strike rows, lowest strike first. Rows sharing a strike are
+    /// ordered by root, so the standard contract precedes adjusted ones.
+    pub strikes: Vec<StrikeRow>,
+}
+
+impl ChainExpiration {
+    /// Calendar days from `as_of` to expiration.
+    pub fn days_to_expiration(&self, as_of: NaiveDate) -> i64 {
+        (self.expiration - as_of).num_days()
+    }
+
+    /// The row whose strike is closest to `spot`; the lower strike wins a
+    /// tie.
+    pub fn nearest_the_money(&self, spot: Decimal) -> Option<&StrikeRow> {
+        self.strikes
+            .iter()
+            .min_by_key(|row| (row.strike - spot).abs())
+    }
+
+    /// The call or put whose delta is closest to `delta` (signed, so pass
+    /// `-0.25` for a 25-delta put). Contracts without greeks are skipped.
+    pub fn by_delta(&self, option_type: OptionType, delta: f64) -> Option<&ChainEntry> {
+        self.strikes
+            .iter()
+            .filter_map(|row| row.side(option_type))
+            .filter_map(|entry| Some((entry, (entry.delta()? - delta).abs())))
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(entry, _)| entry)
+    }
+}
+
+/// An options chain grouped by expiration and strike, with calls and puts
+/// side by side.
+///
+/// Joins the contract listing from the trading API, which carries open
+/// interest, close price, style and deliverables, with the market-data
+/// snapshots that carry quotes and greeks. Build it from results you
+/// already hold with [`new`](Self::new), or let [`fetch`](Self::fetch) run
+/// both requests.
+///
+/// ```ignore
+/// let chain = OptionChain::fetch(
+///     trading.list_option_contracts().underlying_symbols(&["SPY"]),
+///     market.option_chain("SPY"),
+/// )
+/// .await?;
+/// let monthly = chain.nearest_days_to_expiration(today, 30).unwrap();
+/// let atm = monthly.nearest_the_money(spot);
+/// let short_put = monthly.by_delta(OptionType::Put, -0.25);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct OptionChain {
+    expirations: Vec<ChainExpiration>,
+}
+
+impl OptionChain {
+    /// Group `contracts` by expiration, strike and root and attach each
+    /// one's snapshot by symbol. Snapshots with no matching contract are
+    /// dropped.
+    pub fn new(
+        contracts: Vec<OptionContract>,
+        mut snapshots: HashMap<String, OptionSnapshot>,
+    ) -> Self {
+        let mut grouped: BTreeMap<NaiveDate, BTreeMap<(Decimal, String), StrikeRow>> =
+            BTreeMap::new();
+        for contract in contracts {
+            let strike = contract.strike_price;
+            let root = contract_root(&contract);
+            let row = grouped
+                .entry(contract.expiration_date)
+                .or_default()
+                .entry((strike, root.clone()))
+                .or_insert_with(|| StrikeRow {
+                    strike,
+                    root,
+                    call: None,
+                    put: None,
+                });
+            let option_type = contract.option_type;
+            let entry = ChainEntry {
+                snapshot: snapshots.remove(&contract.symbol),
+                contract,
+            };
+            match option_type {
+                OptionType::Call => row.call = Some(entry),
+                OptionType::Put => row.put = Some(entry),
+            }
+        }
+        Self {
+            expirations: grouped
+                .into_iter()
+                .map(|(expiration, strikes)| ChainExpiration {
+                    expiration,
+                    strikes: strikes.into_values().collect(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Run a contract listing and a snapshot chain request concurrently and
+    /// join the results. Apply the same filters to both so they cover the
+    /// same contracts.
+    pub async fn fetch(
+        contracts: ListOptionContractsRequest<'_>,
+        snapshots: OptionChainRequest<'_>,
+    ) -> crate::Result<Self> {
+        let (contracts, snapshots) = futures::try_join!(contracts.execute(), snapshots.execute())?;
+        Ok(Self::new(contracts, snapshots))
+    }
+
+    /// All expirations, soonest first.
+    pub fn expirations(&self) -> &[ChainExpiration] {
+        &self.expirations
+    }
+
+    /// The strikes for one expiration date.
+    pub fn expiration(&self, date: NaiveDate) -> Option<&ChainExpiration> {
+        self.expirations
+            .binary_search_by_key(&date, |e| e.expiration)
+            .ok()
+            .map(|i| &self.expirations[i])
+    }
+
+    /// The expiration whose days to expiration from `as_of` is closest to
+    /// `days`; the sooner one wins a tie. Expired dates are skipped.
+    pub fn nearest_days_to_expiration(
+        &self,
+        as_of: NaiveDate,
+        days: i64,
+    ) -> Option<&ChainExpiration> {
+        self.expirations
+            .iter()
+            .filter(|e| e.days_to_expiration(as_of) >= 0)
+            .min_by_key(|e| (e.days_to_expiration(as_of) - days).abs())
+    }
+
+    /// Every entry in the chain.
+    pub fn entries(&self) -> impl Iterator<Item = &ChainEntry> {
+        self.expirations
+            .iter()
+            .flat_map(|e| &e.strikes)
+            .flat_map(|row| row.call.iter().chain(row.put.iter()))
+    }
+
+    /// Look up a contract by symbol.
+    pub fn get(&self, symbol: &str) -> Option<&ChainEntry> {
+        self.entries().find(|entry| entry.contract.symbol == symbol)
+    }
+}
+
+/// The contract's OCC root: the listed root symbol, else the one in its
+/// OCC symbol.
+fn contract_root(contract: &OptionContract) -> String {
+    match &contract.root_symbol {
+        Some(root) => root.clone(),
+        None => contract
+            .option_symbol()
+            .map(|symbol| symbol.root().to_string())
+            .unwrap_or_else(|_| contract.underlying_symbol.clone()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AccountType;
+    use crate::restful::{MarketDataClient, TradingClient};
+    use serial_test::serial;
+    use std::env;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    const SYMBOLS: [&str; 6] = [
+        "SPY250221P00500000",
+        "SPY250117C00510000",
+        "SPY250117P00500000",
+        "SPY250117C00500000",
+        "SPY250117P00490000",
+        "SPY250117C00490000",
+    ];
+
+    fn dec(s: &str) -> Decimal {
+        Decimal::from_str_exact(s).unwrap()
+    }
+
+    fn contract_json(symbol: &str) -> serde_json::Value {
+        let parsed = crate::OptionSymbol::parse(symbol).unwrap();
+        serde_json::json!({
+            "id": symbol,
+            "symbol": symbol,
+            "name": symbol,
+            "status": "active",
+            "tradable": true,
+            "expiration_date": parsed.expiration(),
+            "underlying_symbol": "SPY",
+            "type": parsed.option_type(),
+            "style": "american",
+            "strike_price": parsed.strike().to_string(),
+            "multiplier": "100",
+            "open_interest": "1200",
+        })
+    }
+
+    fn snapshot_json(symbol: &str) -> serde_json::Value {
+        let parsed = crate::OptionSymbol::parse(symbol).unwrap();
+        let delta = match (parsed.option_type(), parsed.strike().to_string().as_str()) {
+            (OptionType::Call, "490") => 0.7,
+            (OptionType::Call, "500") => 0.5,
+            (OptionType::Call, _) => 0.3,
+            (OptionType::Put, "490") => -0.3,
+            (OptionType::Put, _) => -0.5,
+        };
+        serde_json::json!({ "greeks": { "delta": delta }, "impliedVolatility": 0.18 })
+    }
+
+    fn chain() -> OptionChain {
+        let contracts = SYMBOLS
+            .iter()
+            .map(|s| serde_json::from_value(contract_json(s)).unwrap())
+            .collect();
+        // The 510 call has no snapshot.
+        let snapshots = SYMBOLS
+            .iter()
+            .filter(|s| **s != "SPY250117C00510000")
+            .map(|s| {
+                (
+                    s.to_string(),
+                    serde_json::from_value(snapshot_json(s)).unwrap(),
+                )
+            })
+            .collect();
+        OptionChain::new(contracts, snapshots)
+    }
+
+    #[test]
+    fn groups_by_expiration_and_strike_with_calls_and_puts_side_by_side() {
+        let chain = chain();
+        let dates: Vec<NaiveDate> = chain.expirations().iter().map(|e| e.expiration).collect();
+        assert_eq!(
+            dates,
+            [
+                NaiveDate::from_ymd_opt(2025, 1, 17).unwrap(),
+                NaiveDate::from_ymd_opt(2025, 2, 21).unwrap()
+            ]
+        );
+        let january = &chain.expirations()[0];
+        let strikes: Vec<Decimal> = january.strikes.iter().map(|r| r.strike).collect();
+        assert_eq!(strikes, [dec("490"), dec("500"), dec("510")]);
+        let row = &january.strikes[1];
+        assert_eq!(
+            row.call.as_ref().unwrap().contract.symbol,
+            "SPY250117C00500000"
+        );
+        assert_eq!(
+            row.put.as_ref().unwrap().contract.symbol,
+            "SPY250117P00500000"
+        );
+        assert!(january.strikes[2].put.is_none());
+
+        let uncovered = chain.get("SPY250117C00510000").unwrap();
+        assert!(uncovered.snapshot.is_none());
+        assert_eq!(uncovered.contract.open_interest, Some(1200));
+        assert_eq!(chain.entries().count(), 6);
+    }
+
+    #[test]
+    fn adjusted_contracts_get_their_own_row() {
+        let contracts = ["SPY1250117C00500000", "SPY250117C00500000"]
+            .iter()
+            .map(|s| serde_json::from_value(contract_json(s)).unwrap())
+            .collect();
+        let chain = OptionChain::new(contracts, HashMap::new());
+        let rows = &chain.expirations()[0].strikes;
+        let roots: Vec<&str> = rows.iter().map(|r| r.root.as_str()).collect();
+        assert_eq!(roots, ["SPY", "SPY1"]);
+        assert_eq!(
+            rows[0].call.as_ref().unwrap().contract.symbol,
+            "SPY250117C00500000"
+        );
+        assert_eq!(
+            rows[1].call.as_ref().unwrap().contract.symbol,
+            "SPY1250117C00500000"
+        );
+        assert_eq!(chain.entries().count(), 2);
+    }
+
+    #[test]
+    fn looks_up_by_moneyness_delta_and_days_to_expiration() {
+        let chain = chain();
+        let today = NaiveDate::from_ymd_opt(2025, 1, 2).unwrap();
+        let near = chain.nearest_days_to_expiration(today, 10).unwrap();
+        assert_eq!(near.days_to_expiration(today), 15);
+        let far = chain.nearest_days_to_expiration(today, 45).unwrap();
+        assert_eq!(
+            far.expiration,
+            NaiveDate::from_ymd_opt(2025, 2, 21).unwrap()
+        );
+        assert!(chain.expiration(today).is_none());
+
+        assert_eq!(
+            near.nearest_the_money(dec("503.2")).unwrap().strike,
+            dec("500")
+        );
+        assert_eq!(
+            near.nearest_the_money(dec("495")).unwrap().strike,
+            dec("490")
+        );
+        let put = near.by_delta(OptionType::Put, -0.25).unwrap();
+        assert_eq!(put.contract.symbol, "SPY250117P00490000");
+        // The 510 call has no greeks, so the 500 call is the nearest 0.3.
+        let call = near.by_delta(OptionType::Call, 0.3).unwrap();
+        assert_eq!(call.contract.symbol, "SPY250117C00500000");
+        assert_eq!(call.implied_volatility(), Some(0.18));
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn fetch_joins_contracts_and_snapshots() {
+        unsafe {
+            if env::var("ALPACA_PAPER_API_KEY_ID").is_err() {
+                env::set_var("ALPACA_PAPER_API_KEY_ID", "test_key_id");
+            }
+            if env::var("ALPACA_PAPER_API_SECRET_KEY").is_err() {
+                env::set_var("ALPACA_PAPER_API_SECRET_KEY", "test_secret_key");
+            }
+        }
+        let server = MockServer::start().await;
+        let contracts: Vec<_> = SYMBOLS.iter().map(|s| contract_json(s)).collect();
+        Mock::given(method("GET"))
+            .and(path("/v2/options/contracts"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(
+                serde_json::json!({ "option_contracts": contracts, "next_page_token": null }),
+            ))
+            .mount(&server)
+            .await;
+        let snapshots: serde_json::Map<_, _> = SYMBOLS
+            .iter()
+            .map(|s| (s.to_string(), snapshot_json(s)))
+            .collect();
+        Mock::given(method("GET"))
+            .and(path("/v1beta1/options/snapshots/SPY"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(
+                serde_json::json!({ "snapshots": snapshots, "next_page_token": null }),
+            ))
+            .mount(&server)
+            .await;
+
+        let trading = TradingClient::new(AccountType::Paper)
+            .unwrap()
+            .with_base_url(&server.uri());
+        let market = MarketDataClient::new(AccountType::Paper)
+            .unwrap()
+            .with_base_url(&server.uri());
+        let chain = OptionChain::fetch(
+            trading.list_option_contracts().underlying_symbols(&["SPY"]),
+            market.option_chain("SPY"),
+        )
+        .await
+        .unwrap();
+        assert_eq!(chain.expirations().len(), 2);
+        assert!(chain.entries().all(|entry| entry.delta().is_some()));
+    }
+}
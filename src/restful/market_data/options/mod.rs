@@ -1,5 +1,7 @@
 /// Option bars endpoint types and methods.
 pub mod bars;
+/// Option chain assembled from contract listings and snapshots.
+pub mod chain;
 /// Option quotes endpoint types and methods.
 pub mod quotes;
 /// Option snapshots endpoint types and methods.
@@ -10,6 +12,10 @@ pub mod trades;
 use chrono::{DateTime, Utc};
 use serde::Deserialize;
 
+/// Most contract symbols Alpaca accepts in one snapshots or latest-data
+/// request.
+const MAX_SYMBOLS_PER_REQUEST: usize = 100;
+
 /// An option bar (OHLCV).
 #[derive(Clone, Debug, Deserialize, PartialEq)]
 #[non_exhaustive]
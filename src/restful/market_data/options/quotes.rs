@@ -1,24 +1,78 @@
-use crate::restful::MarketDataClient;
+use std::collections::HashMap;
+
 use reqwest::Method;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+
+use crate::OptionFeed;
+use crate::restful::MarketDataClient;
 
-use super::OptionQuote;
+use super::{MAX_SYMBOLS_PER_REQUEST, OptionQuote};
 
 #[derive(Debug, Deserialize)]
 struct LatestQuotesResponse {
-    quotes: std::collections::HashMap<String, OptionQuote>,
+    quotes: HashMap<String, OptionQuote>,
+}
+
+/// Builder for the latest option quotes by contract symbol.
+///
+/// Symbols are sent in chunks of at most
+/// [`chunk_size`](Self::chunk_size) per request.
+#[derive(Debug, Serialize)]
+#[must_use]
+pub struct OptionLatestQuotesRequest<'a> {
+    #[serde(skip)]
+    client: &'a MarketDataClient,
+    #[serde(skip)]
+    all_symbols: Vec<String>,
+    #[serde(skip)]
+    chunk_size: usize,
+    symbols: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    feed: Option<OptionFeed>,
+}
+
+impl OptionLatestQuotesRequest<'_> {
+    /// Set the data feed to use.
+    pub fn feed(mut self, feed: OptionFeed) -> Self {
+        self.feed = Some(feed);
+        self
+    }
+    /// Most symbols sent per request. Defaults to, and is capped at, 100.
+    pub fn chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = chunk_size.clamp(1, MAX_SYMBOLS_PER_REQUEST);
+        self
+    }
+
+    /// Send the requests and merge the quotes, keyed by contract symbol.
+    pub async fn execute(mut self) -> crate::Result<HashMap<String, OptionQuote>> {
+        let mut combined = HashMap::new();
+        for chunk in std::mem::take(&mut self.all_symbols).chunks(self.chunk_size) {
+            self.symbols = chunk.join(",");
+            let request = self
+                .client
+                .request(Method::GET, "v1beta1/options/quotes/latest")?
+                .query(&self);
+            let response: LatestQuotesResponse = self.client.send_and_deserialize(request).await?;
+            combined.extend(response.quotes);
+        }
+        Ok(combined)
+    }
 }
 
 impl MarketDataClient {
-    /// Get the latest option quotes.
-    pub async fn option_latest_quotes(
+    /// Request the latest option quotes for the given contract symbols.
+    ///
+    /// Returns a builder; call [`OptionLatestQuotesRequest::execute`] to send.
+    pub fn option_latest_quotes<S: AsRef<str>>(
         &self,
-        symbols: &[&str],
-    ) -> crate::Result<std::collections::HashMap<String, OptionQuote>> {
-        let request = self
-            .request(Method::GET, "v1beta1/options/quotes/latest")?
-            .query(&[("symbols", symbols.join(","))]);
-        let response: LatestQuotesResponse = self.send_and_deserialize(request).await?;
-        Ok(response.quotes)
+        symbols: &[S],
+    ) -> OptionLatestQuotesRequest<'_> {
+        OptionLatestQuotesRequest {
+            client: self,
+            all_symbols: symbols.iter().map(|s| s.as_ref().to_string()).collect(),
+            chunk_size: MAX_SYMBOLS_PER_REQUEST,
+            symbols: String::new(),
+            feed: None,
+        }
     }
 }
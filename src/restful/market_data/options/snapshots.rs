@@ -1,35 +1,441 @@
-use crate::restful::MarketDataClient;
+use std::collections::HashMap;
+
+use chrono::{DateTime, NaiveDate, Utc};
 use reqwest::Method;
-use serde::Deserialize;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::restful::MarketDataClient;
+use crate::{OptionFeed, OptionType};
 
-use super::OptionSnapshot;
+use super::{MAX_SYMBOLS_PER_REQUEST, OptionSnapshot};
+
+/// Largest page size Alpaca accepts for the option chain.
+const MAX_CHAIN_PAGE_SIZE: usize = 1000;
 
 #[derive(Debug, Deserialize)]
 struct SnapshotsResponse {
-    snapshots: std::collections::HashMap<String, OptionSnapshot>,
+    snapshots: HashMap<String, OptionSnapshot>,
+    #[serde(default)]
+    next_page_token: Option<String>,
+}
+
+/// Builder for option snapshots by contract symbol.
+///
+/// Symbols are sent in chunks of at most
+/// [`chunk_size`](Self::chunk_size) per request and every page of each
+/// chunk is followed, so any number of contracts can be requested at once.
+#[derive(Debug, Serialize)]
+#[must_use]
+pub struct OptionSnapshotsRequest<'a> {
+    #[serde(skip)]
+    client: &'a MarketDataClient,
+    #[serde(skip)]
+    all_symbols: Vec<String>,
+    #[serde(skip)]
+    chunk_size: usize,
+    symbols: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    feed: Option<OptionFeed>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    updated_since: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    limit: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    page_token: Option<String>,
+}
+
+impl OptionSnapshotsRequest<'_> {
+    /// Set the data feed to use.
+    pub fn feed(mut self, feed: OptionFeed) -> Self {
+        self.feed = Some(feed);
+        self
+    }
+    /// Only return snapshots updated at or after `since`.
+    pub fn updated_since(mut self, since: DateTime<Utc>) -> Self {
+        self.updated_since = Some(since);
+        self
+    }
+    /// Page size for each request (Alpaca allows up to 1000).
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+    /// Most symbols sent per request. Defaults to, and is capped at, 100.
+    pub fn chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = chunk_size.clamp(1, MAX_SYMBOLS_PER_REQUEST);
+        self
+    }
+
+    /// Send the requests and merge the snapshots, keyed by contract symbol.
+    pub async fn execute(mut self) -> crate::Result<HashMap<String, OptionSnapshot>> {
+        let mut combined = HashMap::new();
+        for chunk in std::mem::take(&mut self.all_symbols).chunks(self.chunk_size) {
+            self.symbols = chunk.join(",");
+            self.page_token = None;
+            loop {
+                let request = self
+                    .client
+                    .request(Method::GET, "v1beta1/options/snapshots")?
+                    .query(&self);
+                let response: SnapshotsResponse = self.client.send_and_deserialize(request).await?;
+                combined.extend(response.snapshots);
+                match response.next_page_token {
+                    Some(token) => self.page_token = Some(token),
+                    None => break,
+                }
+            }
+        }
+        Ok(combined)
+    }
+}
+
+/// Builder for the option chain of an underlying: a snapshot of every
+/// contract matching the filters.
+///
+/// Follows `next_page_token` until the chain is complete, or until
+/// [`limit`](Self::limit) contracts have been collected.
+#[derive(Debug, Serialize)]
+#[must_use]
+pub struct OptionChainRequest<'a> {
+    #[serde(skip)]
+    client: &'a MarketDataClient,
+    #[serde(skip)]
+    underlying_symbol: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    feed: Option<OptionFeed>,
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    option_type: Option<OptionType>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    strike_price_gte: Option<Decimal>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    strike_price_lte: Option<Decimal>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    expiration_date: Option<NaiveDate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    expiration_date_gte: Option<NaiveDate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    expiration_date_lte: Option<NaiveDate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    root_symbol: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    updated_since: Option<DateTime<Utc>>,
+    #[serde(skip)]
+    max_contracts: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    limit: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    page_token: Option<String>,
+}
+
+impl OptionChainRequest<'_> {
+    /// Set the data feed to use.
+    pub fn feed(mut self, feed: OptionFeed) -> Self {
+        self.feed = Some(feed);
+        self
+    }
+    /// Only calls or only puts.
+    pub fn option_type(mut self, option_type: OptionType) -> Self {
+        self.option_type = Some(option_type);
+        self
+    }
+    /// Only strikes at or above `strike`.
+    pub fn strike_price_gte(mut self, strike: Decimal) -> Self {
+        self.strike_price_gte = Some(strike);
+        self
+    }
+    /// Only strikes at or below `strike`.
+    pub fn strike_price_lte(mut self, strike: Decimal) -> Self {
+        self.strike_price_lte = Some(strike);
+        self
+    }
+    /// Only contracts expiring on `date`. Clears any expiration range.
+    pub fn expiration_date(mut self, date: NaiveDate) -> Self {
+        self.expiration_date = Some(date);
+        self.expiration_date_gte = None;
+        self.expiration_date_lte = None;
+        self
+    }
+    /// Only contracts expiring on or after `date`. Clears
+    /// [`expiration_date`](Self::expiration_date).
+    pub fn expiration_date_gte(mut self, date: NaiveDate) -> Self {
+        self.expiration_date_gte = Some(date);
+        self.expiration_date = None;
+        self
+    }
+    /// Only contracts expiring on or before `date`. Clears
+    /// [`expiration_date`](Self::expiration_date).
+    pub fn expiration_date_lte(mut self, date: NaiveDate) -> Self {
+        self.expiration_date_lte = Some(date);
+        self.expiration_date = None;
+        self
+    }
+    /// Only contracts with this OCC root, e.g. `SPXW` under `SPX`.
+    pub fn root_symbol(mut self, root: &str) -> Self {
+        self.root_symbol = Some(root.to_string());
+        self
+    }
+    /// Only return snapshots updated at or after `since`.
+    pub fn updated_since(mut self, since: DateTime<Utc>) -> Self {
+        self.updated_since = Some(since);
+        self
+    }
+    /// Return at most this many contracts; if a page goes past the limit,
+    /// its lowest symbols are kept. Also used as the page size, up to
+    /// Alpaca's maximum of 1000; larger limits are reached by following
+    /// more pages.
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.max_contracts = Some(limit);
+        self.limit = Some(limit.min(MAX_CHAIN_PAGE_SIZE));
+        self
+    }
+
+    /// Send the request, following pagination, and return the snapshots
+    /// keyed by contract symbol.
+    pub async fn execute(mut self) -> crate::Result<HashMap<String, OptionSnapshot>> {
+        let path = format!("v1beta1/options/snapshots/{}", self.underlying_symbol);
+        let mut combined = HashMap::new();
+        if self.max_contracts == Some(0) {
+            return Ok(combined);
+        }
+        loop {
+            let request = self.client.request(Method::GET, &path)?.query(&self);
+            let response: SnapshotsResponse = self.client.send_and_deserialize(request).await?;
+            let remaining = self
+                .max_contracts
+                .map_or(usize::MAX, |limit| limit.saturating_sub(combined.len()));
+            let mut page: Vec<_> = response.snapshots.into_iter().collect();
+            if page.len() >= remaining {
+                page.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+                page.truncate(remaining);
+                combined.extend(page);
+                break;
+            }
+            combined.extend(page);
+            match response.next_page_token {
+                Some(token) => self.page_token = Some(token),
+                None => break,
+            }
+        }
+        Ok(combined)
+    }
 }
 
 impl MarketDataClient {
-    /// Get option snapshots for given symbols.
-    pub async fn option_snapshots(
-        &self,
-        symbols: &[&str],
-    ) -> crate::Result<std::collections::HashMap<String, OptionSnapshot>> {
-        let request = self
-            .request(Method::GET, "v1beta1/options/snapshots")?
-            .query(&[("symbols", symbols.join(","))]);
-        let response: SnapshotsResponse = self.send_and_deserialize(request).await?;
-        Ok(response.snapshots)
-    }
-
-    /// Get the option chain (all snapshots for an underlying symbol).
-    pub async fn option_chain(
-        &self,
-        underlying_symbol: &str,
-    ) -> crate::Result<std::collections::HashMap<String, OptionSnapshot>> {
-        let path = format!("v1beta1/options/snapshots/{underlying_symbol}");
-        let request = self.request(Method::GET, &path)?;
-        let response: SnapshotsResponse = self.send_and_deserialize(request).await?;
-        Ok(response.snapshots)
+    /// Request option snapshots for the given contract symbols.
+    ///
+    /// Returns a builder; call [`OptionSnapshotsRequest::execute`] to send.
+    pub fn option_snapshots<S: AsRef<str>>(&self, symbols: &[S]) -> OptionSnapshotsRequest<'_> {
+        OptionSnapshotsRequest {
+            client: self,
+            all_symbols: symbols.iter().map(|s| s.as_ref().to_string()).collect(),
+            chunk_size: MAX_SYMBOLS_PER_REQUEST,
+            symbols: String::new(),
+            feed: None,
+            updated_since: None,
+            limit: None,
+            page_token: None,
+        }
+    }
+
+    /// Request the option chain for an underlying symbol.
+    ///
+    /// Returns a builder; call [`OptionChainRequest::execute`] to send.
+    ///
+    /// ```ignore
+    /// let chain = client.option_chain("SPY")
+    ///     .option_type(OptionType::Put)
+    ///     .expiration_date_lte(NaiveDate::from_ymd_opt(2025, 3, 21).unwrap())
+    ///     .strike_price_gte(dec!(500))
+    ///     .execute().await?;
+    /// ```
+    pub fn option_chain(&self, underlying_symbol: &str) -> OptionChainRequest<'_> {
+        OptionChainRequest {
+            client: self,
+            underlying_symbol: underlying_symbol.to_string(),
+            feed: None,
+            option_type: None,
+            strike_price_gte: None,
+            strike_price_lte: None,
+            expiration_date: None,
+            expiration_date_gte: None,
+            expiration_date_lte: None,
+            root_symbol: None,
+            updated_since: None,
+            max_contracts: None,
+            limit: None,
+            page_token: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AccountType;
+    use serial_test::serial;
+    use std::env;
+    use wiremock::matchers::{method, path, query_param};
+    use wiremock::{Mock, MockServer, Respond, ResponseTemplate};
+
+    fn paper_client() -> MarketDataClient {
+        unsafe {
+            if env::var("ALPACA_PAPER_API_KEY_ID").is_err() {
+                env::set_var("ALPACA_PAPER_API_KEY_ID", "test_key_id");
+            }
+            if env::var("ALPACA_PAPER_API_SECRET_KEY").is_err() {
+                env::set_var("ALPACA_PAPER_API_SECRET_KEY", "test_secret_key");
+            }
+        }
+        MarketDataClient::new(AccountType::Paper).unwrap()
+    }
+
+    fn snapshots(symbols: &[&str], next_page_token: Option<&str>) -> serde_json::Value {
+        let snapshots: serde_json::Map<_, _> = symbols
+            .iter()
+            .map(|s| {
+                (
+                    s.to_string(),
+                    serde_json::json!({ "impliedVolatility": 0.2 }),
+                )
+            })
+            .collect();
+        serde_json::json!({ "snapshots": snapshots, "next_page_token": next_page_token })
+    }
+
+    #[test]
+    #[serial]
+    fn chain_filters_serialize_to_query() {
+        let client = paper_client();
+        let request = client
+            .option_chain("SPY")
+            .feed(OptionFeed::Indicative)
+            .option_type(OptionType::Put)
+            .strike_price_gte(Decimal::from(500))
+            .expiration_date(NaiveDate::from_ymd_opt(2025, 3, 21).unwrap())
+            .root_symbol("SPY");
+        let query = serde_urlencoded::to_string(&request).unwrap();
+        assert_eq!(
+            query,
+            "feed=indicative&type=put&strike_price_gte=500&expiration_date=2025-03-21&root_symbol=SPY"
+        );
+
+        let request = client.option_chain("SPY").limit(2500);
+        let query = serde_urlencoded::to_string(&request).unwrap();
+        assert_eq!(query, "limit=1000");
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn chain_follows_next_page_token() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v1beta1/options/snapshots/SPY"))
+            .and(query_param("page_token", "p2"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(snapshots(&["SPY250321P00500000"], None)),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v1beta1/options/snapshots/SPY"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(snapshots(
+                &["SPY250321C00500000", "SPY250321C00505000"],
+                Some("p2"),
+            )))
+            .mount(&server)
+            .await;
+
+        let client = paper_client().with_base_url(&server.uri());
+        let chain = client.option_chain("SPY").execute().await.unwrap();
+        assert_eq!(chain.len(), 3);
+        assert!(chain.contains_key("SPY250321P00500000"));
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn chain_stops_at_the_limit() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v1beta1/options/snapshots/SPY"))
+            .and(query_param("page_token", "p2"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(snapshots(
+                &["SPY250321P00505000", "SPY250321P00500000"],
+                Some("p3"),
+            )))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v1beta1/options/snapshots/SPY"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(snapshots(
+                &["SPY250321C00500000", "SPY250321C00505000"],
+                Some("p2"),
+            )))
+            .mount(&server)
+            .await;
+
+        let client = paper_client().with_base_url(&server.uri());
+        let chain = client.option_chain("SPY").limit(3).execute().await.unwrap();
+        assert_eq!(chain.len(), 3);
+        assert!(chain.contains_key("SPY250321P00500000"));
+        assert!(!chain.contains_key("SPY250321P00505000"));
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn chain_with_zero_limit_sends_nothing() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(500))
+            .expect(0)
+            .mount(&server)
+            .await;
+
+        let client = paper_client().with_base_url(&server.uri());
+        let chain = client.option_chain("SPY").limit(0).execute().await.unwrap();
+        assert!(chain.is_empty());
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn snapshots_are_requested_in_chunks() {
+        struct Echo;
+        impl Respond for Echo {
+            fn respond(&self, request: &wiremock::Request) -> ResponseTemplate {
+                let (_, symbols) = request
+                    .url
+                    .query_pairs()
+                    .find(|(key, _)| key == "symbols")
+                    .unwrap();
+                let symbols: Vec<&str> = symbols.split(',').collect();
+                assert!(symbols.len() <= 2, "chunk too large: {symbols:?}");
+                ResponseTemplate::new(200).set_body_json(snapshots(&symbols, None))
+            }
+        }
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v1beta1/options/snapshots"))
+            .respond_with(Echo)
+            .expect(3)
+            .mount(&server)
+            .await;
+
+        let client = paper_client().with_base_url(&server.uri());
+        let symbols = [
+            "A250117C00001000",
+            "B250117C00001000",
+            "C250117C00001000",
+            "D250117C00001000",
+            "E250117C00001000",
+        ];
+        let result = client
+            .option_snapshots(&symbols)
+            .chunk_size(2)
+            .execute()
+            .await
+            .unwrap();
+        assert_eq!(result.len(), 5);
     }
 }
@@ -1,24 +1,295 @@
-use crate::restful::MarketDataClient;
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
 use reqwest::Method;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+
+use crate::OptionFeed;
+use crate::restful::market_data::pagination;
+use crate::restful::{MarketDataClient, SortDirection, join_symbols};
+
+use super::{MAX_SYMBOLS_PER_REQUEST, OptionTrade};
 
-use super::OptionTrade;
+#[derive(Debug, Deserialize)]
+struct TradesResponse {
+    trades: HashMap<String, Vec<OptionTrade>>,
+    next_page_token: Option<String>,
+}
 
 #[derive(Debug, Deserialize)]
 struct LatestTradesResponse {
-    trades: std::collections::HashMap<String, OptionTrade>,
+    trades: HashMap<String, OptionTrade>,
+}
+
+/// Builder for requesting historical option trades.
+#[derive(Debug, Serialize)]
+#[must_use]
+pub struct OptionTradesRequest<'a> {
+    #[serde(skip)]
+    client: &'a MarketDataClient,
+    symbols: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    start: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    end: Option<DateTime<Utc>>,
+    /// Per-symbol cap applied client-side during pagination.
+    #[serde(skip)]
+    limit: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    page_token: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sort: Option<SortDirection>,
+}
+
+impl OptionTradesRequest<'_> {
+    /// Set the start time filter.
+    pub fn start(mut self, start: DateTime<Utc>) -> Self {
+        self.start = Some(start);
+        self
+    }
+    /// Set the end time filter.
+    pub fn end(mut self, end: DateTime<Utc>) -> Self {
+        self.end = Some(end);
+        self
+    }
+    /// Cap the total number of trades returned per symbol across all
+    /// auto-paginated pages.
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+    /// Set the result `sort` order. Defaults to ascending when unset.
+    pub fn sort(mut self, sort: SortDirection) -> Self {
+        self.sort = Some(sort);
+        self
+    }
+
+    /// Execute the request, auto-paginating until all matching trades are
+    /// retrieved. When `limit` is set, each symbol's series is truncated to
+    /// the cap as pages arrive, and pagination stops as soon as every
+    /// requested symbol has reached the cap (or the API runs out of pages).
+    pub async fn execute(mut self) -> crate::Result<HashMap<String, Vec<OptionTrade>>> {
+        let cap = self.limit;
+        if cap == Some(0) || self.symbols.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let requested: Vec<String> = self.symbols.split(',').map(str::to_string).collect();
+        let mut combined: HashMap<String, Vec<OptionTrade>> = HashMap::new();
+        loop {
+            if let Some(cap) = cap {
+                let pending = pagination::pending_symbols(&combined, &requested, cap);
+                if pending.is_empty() {
+                    break;
+                }
+                let next_symbols = pending.join(",");
+                if next_symbols != self.symbols {
+                    // The cursor is tied to the symbol set, so narrowing
+                    // restarts the range; drop the partial series it will
+                    // refill.
+                    pagination::drop_partials(&mut combined, &pending);
+                    self.symbols = next_symbols;
+                    self.page_token = None;
+                }
+            }
+            let request = self
+                .client
+                .request(Method::GET, "v1beta1/options/trades")?
+                .query(&self);
+            let response: TradesResponse = self.client.send_and_deserialize(request).await?;
+            pagination::extend_capped(&mut combined, response.trades, cap);
+            match response.next_page_token {
+                Some(token) => self.page_token = Some(token),
+                None => break,
+            }
+        }
+        Ok(combined)
+    }
+}
+
+/// Builder for the latest option trades by contract symbol.
+///
+/// Symbols are sent in chunks of at most
+/// [`chunk_size`](Self::chunk_size) per request.
+#[derive(Debug, Serialize)]
+#[must_use]
+pub struct OptionLatestTradesRequest<'a> {
+    #[serde(skip)]
+    client: &'a MarketDataClient,
+    #[serde(skip)]
+    all_symbols: Vec<String>,
+    #[serde(skip)]
+    chunk_size: usize,
+    symbols: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    feed: Option<OptionFeed>,
+}
+
+impl OptionLatestTradesRequest<'_> {
+    /// Set the data feed to use.
+    pub fn feed(mut self, feed: OptionFeed) -> Self {
+        self.feed = Some(feed);
+        self
+    }
+    /// Most symbols sent per request. Defaults to, and is capped at, 100.
+    pub fn chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = chunk_size.clamp(1, MAX_SYMBOLS_PER_REQUEST);
+        self
+    }
+
+    /// Send the requests and merge the trades, keyed by contract symbol.
+    pub async fn execute(mut self) -> crate::Result<HashMap<String, OptionTrade>> {
+        let mut combined = HashMap::new();
+        for chunk in std::mem::take(&mut self.all_symbols).chunks(self.chunk_size) {
+            self.symbols = chunk.join(",");
+            let request = self
+                .client
+                .request(Method::GET, "v1beta1/options/trades/latest")?
+                .query(&self);
+            let response: LatestTradesResponse = self.client.send_and_deserialize(request).await?;
+            combined.extend(response.trades);
+        }
+        Ok(combined)
+    }
 }
 
 impl MarketDataClient {
-    /// Get the latest option trades.
-    pub async fn option_latest_trades(
+    /// Request historical option trades.
+    ///
+    /// Returns a builder; call [`OptionTradesRequest::execute`] to send.
+    pub fn option_trades<S: AsRef<str>>(&self, symbols: &[S]) -> OptionTradesRequest<'_> {
+        OptionTradesRequest {
+            client: self,
+            symbols: join_symbols(symbols),
+            start: None,
+            end: None,
+            limit: None,
+            page_token: None,
+            sort: None,
+        }
+    }
+
+    /// Request the latest option trades for the given contract symbols.
+    ///
+    /// Returns a builder; call [`OptionLatestTradesRequest::execute`] to send.
+    pub fn option_latest_trades<S: AsRef<str>>(
         &self,
-        symbols: &[&str],
-    ) -> crate::Result<std::collections::HashMap<String, OptionTrade>> {
-        let request = self
-            .request(Method::GET, "v1beta1/options/trades/latest")?
-            .query(&[("symbols", symbols.join(","))]);
-        let response: LatestTradesResponse = self.send_and_deserialize(request).await?;
-        Ok(response.trades)
+        symbols: &[S],
+    ) -> OptionLatestTradesRequest<'_> {
+        OptionLatestTradesRequest {
+            client: self,
+            all_symbols: symbols.iter().map(|s| s.as_ref().to_string()).collect(),
+            chunk_size: MAX_SYMBOLS_PER_REQUEST,
+            symbols: String::new(),
+            feed: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AccountType;
+    use serial_test::serial;
+    use std::env;
+
+    fn paper_client() -> MarketDataClient {
+        unsafe {
+            if env::var("ALPACA_PAPER_API_KEY_ID").is_err() {
+                env::set_var("ALPACA_PAPER_API_KEY_ID", "test_key_id");
+            }
+            if env::var("ALPACA_PAPER_API_SECRET_KEY").is_err() {
+                env::set_var("ALPACA_PAPER_API_SECRET_KEY", "test_secret_key");
+            }
+        }
+        MarketDataClient::new(AccountType::Paper).unwrap()
+    }
+
+    #[test]
+    #[serial]
+    fn filters_serialize_to_query_without_limit() {
+        let client = paper_client();
+        let start = DateTime::parse_from_rfc3339("2026-01-02T14:30:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let request = client
+            .option_trades(&["SPY250321C00500000", "SPY250321P00500000"])
+            .start(start)
+            .limit(50)
+            .sort(SortDirection::Desc);
+        let query = serde_urlencoded::to_string(&request).unwrap();
+        assert_eq!(
+            query,
+            "symbols=SPY250321C00500000%2CSPY250321P00500000&start=2026-01-02T14%3A30%3A00Z&sort=desc"
+        );
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn limit_zero_short_circuits_without_request() {
+        let client = paper_client();
+        let result = client
+            .option_trades(&["SPY250321C00500000"])
+            .limit(0)
+            .execute()
+            .await
+            .unwrap();
+        assert!(result.is_empty());
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn paginates_and_caps_each_symbol() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, Respond, ResponseTemplate};
+
+        const CALL: &str = "SPY250321C00500000";
+        const PUT: &str = "SPY250321P00500000";
+
+        fn trade(price: f64) -> String {
+            format!(r#"{{"t":"2026-05-07T13:30:00Z","x":"C","p":{price},"s":1}}"#)
+        }
+
+        struct Pages;
+        impl Respond for Pages {
+            fn respond(&self, request: &wiremock::Request) -> ResponseTemplate {
+                let query: HashMap<_, _> = request.url.query_pairs().into_owned().collect();
+                let body = if !query.contains_key("page_token") {
+                    // Page 1: the call reaches the cap of 2, the put gets 1.
+                    format!(
+                        r#"{{"trades":{{"{CALL}":[{},{}],"{PUT}":[{}]}},"next_page_token":"p2"}}"#,
+                        trade(1.0),
+                        trade(2.0),
+                        trade(10.0)
+                    )
+                } else {
+                    format!(
+                        r#"{{"trades":{{"{PUT}":[{},{}]}},"next_page_token":"p3"}}"#,
+                        trade(11.0),
+                        trade(12.0)
+                    )
+                };
+                ResponseTemplate::new(200).set_body_raw(body, "application/json")
+            }
+        }
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v1beta1/options/trades"))
+            .respond_with(Pages)
+            .mount(&server)
+            .await;
+
+        let client = paper_client().with_base_url(&server.uri());
+        let result = client
+            .option_trades(&[CALL, PUT])
+            .limit(2)
+            .execute()
+            .await
+            .unwrap();
+
+        let prices =
+            |symbol: &str| -> Vec<f64> { result[symbol].iter().map(|t| t.price).collect() };
+        assert_eq!(prices(CALL), vec![1.0, 2.0]);
+        assert_eq!(prices(PUT), vec![10.0, 11.0]);
     }
 }
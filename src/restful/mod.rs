@@ -1,3 +1,7 @@
+mod dry_run;
+pub use dry_run::DryRunCall;
+mod live_safety;
+pub use live_safety::{LiveMutation, LiveOperation, LiveSafety, LiveSafetyViolation};
 /// Market data endpoint types and methods.
 pub mod market_data;
 mod market_data_client;
@@ -14,6 +18,15 @@ pub(crate) use crate::serde_helpers::{
     string_as_optional_decimal, string_as_optional_u64, unix_seconds_vec_as_datetimes,
 };
 
+/// Comma-join symbols for a `symbols` query parameter.
+pub(crate) fn join_symbols<S: AsRef<str>>(symbols: &[S]) -> String {
+    symbols
+        .iter()
+        .map(AsRef::as_ref)
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
 /// Sort direction shared across endpoints that accept ordering hints.
 #[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
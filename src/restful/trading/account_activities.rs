@@ -53,9 +53,9 @@ pub enum ActivityType {
     OptionAssignment,
     /// Option corporate action (`OPCA`).
     OptionCorporateAction,
-    /// Option exercise (`OPEXP`).
+    /// Option exercise (`OPXRC`).
     OptionExercise,
-    /// Option expiration (`OPXRC`).
+    /// Option expiration (`OPEXP`).
     OptionExpiration,
     /// Splits (`SPLIT`).
     Split,
@@ -80,8 +80,8 @@ impl ActivityType {
             Self::Fee => "FEE",
             Self::OptionAssignment => "OPASN",
             Self::OptionCorporateAction => "OPCA",
-            Self::OptionExercise => "OPEXP",
-            Self::OptionExpiration => "OPXRC",
+            Self::OptionExercise => "OPXRC",
+            Self::OptionExpiration => "OPEXP",
             Self::Split => "SPLIT",
             Self::Other(raw) => raw,
         }
@@ -118,8 +118,8 @@ impl<'de> Deserialize<'de> for ActivityType {
             "FEE" => Self::Fee,
             "OPASN" => Self::OptionAssignment,
             "OPCA" => Self::OptionCorporateAction,
-            "OPEXP" => Self::OptionExercise,
-            "OPXRC" => Self::OptionExpiration,
+            "OPXRC" => Self::OptionExercise,
+            "OPEXP" => Self::OptionExpiration,
             "SPLIT" => Self::Split,
             _ => Self::Other(raw),
         })
@@ -446,8 +446,8 @@ mod tests {
             (ActivityType::Fee, "\"FEE\""),
             (ActivityType::OptionAssignment, "\"OPASN\""),
             (ActivityType::OptionCorporateAction, "\"OPCA\""),
-            (ActivityType::OptionExercise, "\"OPEXP\""),
-            (ActivityType::OptionExpiration, "\"OPXRC\""),
+            (ActivityType::OptionExercise, "\"OPXRC\""),
+            (ActivityType::OptionExpiration, "\"OPEXP\""),
             (ActivityType::Split, "\"SPLIT\""),
         ];
         for (variant, expected) in cases {
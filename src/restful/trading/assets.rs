@@ -1,5 +1,5 @@
-use crate::AssetId;
 use crate::restful::{TradingClient, null_def_vec, string_as_optional_decimal};
+use crate::{AssetId, CryptoPair};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
@@ -160,11 +160,32 @@ pub struct Asset {
     /// Short margin requirement percentage.
     #[serde(deserialize_with = "string_as_optional_decimal", default)]
     pub margin_requirement_short: Option<Decimal>,
+    /// Smallest quantity an order may be for (crypto).
+    #[serde(deserialize_with = "string_as_optional_decimal", default)]
+    pub min_order_size: Option<Decimal>,
+    /// Step order quantities must be a multiple of (crypto).
+    #[serde(deserialize_with = "string_as_optional_decimal", default)]
+    pub min_trade_increment: Option<Decimal>,
+    /// Step order prices must be a multiple of (crypto).
+    #[serde(deserialize_with = "string_as_optional_decimal", default)]
+    pub price_increment: Option<Decimal>,
     /// Additional asset attributes.
     #[serde(default, deserialize_with = "null_def_vec")]
     pub attributes: Vec<AssetAttribute>,
 }
 
+impl Asset {
+    /// The crypto pair this asset trades as, or `None` for other asset
+    /// classes.
+    #[must_use]
+    pub fn crypto_pair(&self) -> Option<CryptoPair> {
+        match self.class {
+            AssetClass::Crypto => CryptoPair::parse(&self.symbol).ok(),
+            _ => None,
+        }
+    }
+}
+
 /// Builder for filtering asset list requests.
 #[derive(Clone, Debug, Serialize)]
 #[must_use]
@@ -247,6 +268,16 @@ impl TradingClient {
         let request = self.request(reqwest::Method::GET, &format!("v2/assets/{symbol_or_id}"))?;
         self.send_and_deserialize(request).await
     }
+
+    /// Get the asset for a crypto pair, whose `min_order_size`,
+    /// `min_trade_increment` and `price_increment` are the pair's trading
+    /// increments (see [`order_rounding`](super::order_rounding)).
+    ///
+    /// Looks the pair up by its compact symbol, since the slashed form
+    /// cannot appear in a path segment.
+    pub async fn get_crypto_asset(&self, pair: &CryptoPair) -> crate::Result<Asset> {
+        self.get_asset(&pair.compact()).await
+    }
 }
 
 #[cfg(test)]
@@ -284,6 +315,59 @@ mod tests {
         assert_eq!(asset.borrow_status, Some(BorrowStatus::HardToBorrow));
     }
 
+    /// The pair is looked up by its compact symbol, and the returned asset
+    /// maps back to the same pair with its increments intact.
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn crypto_asset_is_fetched_by_compact_symbol() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v2/assets/BTCUSD"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "276e2673-764b-4ab6-a611-caf665ca6340",
+                "class": "crypto",
+                "exchange": "CRYPTO",
+                "symbol": "BTC/USD",
+                "name": "Bitcoin  / US Dollar",
+                "status": "active",
+                "tradable": true,
+                "marginable": false,
+                "shortable": false,
+                "easy_to_borrow": false,
+                "fractionable": true,
+                "min_order_size": "0.000027",
+                "min_trade_increment": "0.000000001",
+                "price_increment": "1",
+                "attributes": []
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        unsafe {
+            if std::env::var("ALPACA_PAPER_API_KEY_ID").is_err() {
+                std::env::set_var("ALPACA_PAPER_API_KEY_ID", "test_key_id");
+            }
+            if std::env::var("ALPACA_PAPER_API_SECRET_KEY").is_err() {
+                std::env::set_var("ALPACA_PAPER_API_SECRET_KEY", "test_secret_key");
+            }
+        }
+        let client = TradingClient::new(crate::AccountType::Paper)
+            .unwrap()
+            .with_base_url(&server.uri());
+        let pair = CryptoPair::parse("BTC/USD").unwrap();
+        let asset = client.get_crypto_asset(&pair).await.unwrap();
+        assert_eq!(asset.crypto_pair(), Some(pair));
+        assert_eq!(
+            asset.min_trade_increment,
+            Some(Decimal::from_str_exact("0.000000001").unwrap())
+        );
+        assert_eq!(asset.price_increment, Some(Decimal::ONE));
+    }
+
     #[test]
     fn asset_attribute_known_codes_round_trip() {
         let cases = [
@@ -0,0 +1,524 @@
+use chrono::{DateTime, Utc};
+use reqwest::Method;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::restful::TradingClient;
+use crate::serde_helpers::string_as_optional_decimal;
+use crate::{AssetId, CryptoTransferId, CryptoWalletId, WhitelistedAddressId};
+
+/// A crypto funding wallet, holding the address deposits are sent to.
+#[derive(Clone, Debug, Deserialize)]
+#[non_exhaustive]
+pub struct CryptoWallet {
+    /// Wallet ID, when Alpaca reports one.
+    #[serde(default)]
+    pub id: Option<CryptoWalletId>,
+    /// Asset ID of the coin held by this wallet.
+    #[serde(default)]
+    pub asset_id: Option<AssetId>,
+    /// Coin symbol (e.g. `BTC`), when reported.
+    #[serde(default)]
+    pub asset: Option<String>,
+    /// Blockchain network the address lives on, when reported.
+    #[serde(default)]
+    pub network: Option<String>,
+    /// Deposit address.
+    pub address: String,
+    /// Timestamp when the wallet was created.
+    #[serde(default)]
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+/// Whether a crypto transfer moved funds into or out of the account.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[non_exhaustive]
+pub enum CryptoTransferDirection {
+    /// A deposit into the account.
+    Incoming,
+    /// A withdrawal out of the account.
+    Outgoing,
+}
+
+/// Processing state of a crypto transfer.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[non_exhaustive]
+pub enum CryptoTransferStatus {
+    /// Submitted and not yet settled on chain.
+    Processing,
+    /// Settled.
+    Complete,
+    /// Rejected or failed on chain.
+    Failed,
+    /// A status this version of the crate does not know about.
+    #[serde(other)]
+    Unknown,
+}
+
+impl CryptoTransferStatus {
+    /// Whether the transfer has finished, successfully or not.
+    #[must_use]
+    pub const fn is_terminal(self) -> bool {
+        matches!(self, Self::Complete | Self::Failed)
+    }
+}
+
+/// A crypto deposit or withdrawal.
+#[derive(Clone, Debug, Deserialize)]
+#[non_exhaustive]
+pub struct CryptoTransfer {
+    /// Transfer ID.
+    pub id: CryptoTransferId,
+    /// On-chain transaction hash, once broadcast.
+    #[serde(default)]
+    pub tx_hash: Option<String>,
+    /// Deposit or withdrawal.
+    pub direction: CryptoTransferDirection,
+    /// Processing state.
+    pub status: CryptoTransferStatus,
+    /// Amount transferred, in units of `asset`.
+    pub amount: Decimal,
+    /// USD value of `amount` at the time of the transfer.
+    #[serde(deserialize_with = "string_as_optional_decimal", default)]
+    pub usd_value: Option<Decimal>,
+    /// Network (gas) fee paid, in units of `asset`.
+    #[serde(deserialize_with = "string_as_optional_decimal", default)]
+    pub network_fee: Option<Decimal>,
+    /// Alpaca fees charged, in units of `asset`.
+    #[serde(deserialize_with = "string_as_optional_decimal", default)]
+    pub fees: Option<Decimal>,
+    /// Blockchain network the transfer settled on.
+    #[serde(default)]
+    pub chain: Option<String>,
+    /// Coin symbol (e.g. `BTC`).
+    pub asset: String,
+    /// Sending address, when known.
+    #[serde(default)]
+    pub from_address: Option<String>,
+    /// Receiving address.
+    #[serde(default)]
+    pub to_address: Option<String>,
+    /// Timestamp when the transfer was created.
+    pub created_at: DateTime<Utc>,
+}
+
+/// Review state of a whitelisted withdrawal address.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[non_exhaustive]
+pub enum WhitelistedAddressStatus {
+    /// Approved; withdrawals to this address are allowed.
+    Approved,
+    /// Awaiting approval. Newly added addresses stay pending for a
+    /// cooling-off period.
+    Pending,
+    /// A status this version of the crate does not know about.
+    #[serde(other)]
+    Unknown,
+}
+
+/// An address withdrawals may be sent to.
+#[derive(Clone, Debug, Deserialize)]
+#[non_exhaustive]
+pub struct WhitelistedAddress {
+    /// Whitelisted address ID.
+    pub id: WhitelistedAddressId,
+    /// Coin symbol the address is approved for.
+    pub asset: String,
+    /// The withdrawal address.
+    pub address: String,
+    /// Blockchain network the address lives on, when reported.
+    #[serde(default)]
+    pub chain: Option<String>,
+    /// Review state.
+    pub status: WhitelistedAddressStatus,
+    /// Timestamp when the address was added.
+    pub created_at: DateTime<Utc>,
+}
+
+/// Estimated fee for a crypto withdrawal.
+#[derive(Clone, Debug, Deserialize)]
+#[non_exhaustive]
+pub struct CryptoFeeEstimate {
+    /// Estimated network fee, in units of the withdrawn asset.
+    pub fee: Decimal,
+}
+
+/// Alpaca answers `GET /v2/wallets` with a bare wallet when an asset is
+/// given and with a list otherwise.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum WalletsResponse {
+    One(CryptoWallet),
+    Many(Vec<CryptoWallet>),
+}
+
+impl From<WalletsResponse> for Vec<CryptoWallet> {
+    fn from(response: WalletsResponse) -> Self {
+        match response {
+            WalletsResponse::One(wallet) => vec![wallet],
+            WalletsResponse::Many(wallets) => wallets,
+        }
+    }
+}
+
+/// Builder for listing crypto funding wallets.
+#[derive(Debug, Serialize)]
+#[must_use]
+pub struct ListCryptoWalletsRequest<'a> {
+    #[serde(skip)]
+    client: &'a TradingClient,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    asset: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    network: Option<String>,
+}
+
+impl ListCryptoWalletsRequest<'_> {
+    /// Only return the wallet for this coin (e.g. `BTC`).
+    pub fn asset(mut self, asset: &str) -> Self {
+        self.asset = Some(asset.to_string());
+        self
+    }
+
+    /// Only return wallets on this blockchain network.
+    pub fn network(mut self, network: &str) -> Self {
+        self.network = Some(network.to_string());
+        self
+    }
+
+    /// Submit the request.
+    pub async fn execute(self) -> crate::Result<Vec<CryptoWallet>> {
+        let request = self.client.request(Method::GET, "v2/wallets")?.query(&self);
+        let response: WalletsResponse = self.client.send_and_deserialize(request).await?;
+        Ok(response.into())
+    }
+}
+
+/// Builder for listing crypto transfers.
+#[derive(Debug, Serialize)]
+#[must_use]
+pub struct ListCryptoTransfersRequest<'a> {
+    #[serde(skip)]
+    client: &'a TradingClient,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    direction: Option<CryptoTransferDirection>,
+}
+
+impl ListCryptoTransfersRequest<'_> {
+    /// Only return deposits or only withdrawals.
+    pub fn direction(mut self, direction: CryptoTransferDirection) -> Self {
+        self.direction = Some(direction);
+        self
+    }
+
+    /// Submit the request.
+    pub async fn execute(self) -> crate::Result<Vec<CryptoTransfer>> {
+        let request = self
+            .client
+            .request(Method::GET, "v2/wallets/transfers")?
+            .query(&self);
+        self.client.send_and_deserialize(request).await
+    }
+}
+
+/// Builder for withdrawing crypto to a whitelisted address.
+#[derive(Debug, Serialize)]
+#[must_use]
+pub struct CryptoWithdrawalRequest<'a> {
+    #[serde(skip)]
+    client: &'a TradingClient,
+    asset: String,
+    amount: Decimal,
+    address: String,
+}
+
+impl CryptoWithdrawalRequest<'_> {
+    /// Submit the withdrawal.
+    pub async fn execute(self) -> crate::Result<CryptoTransfer> {
+        let request = self
+            .client
+            .request(Method::POST, "v2/wallets/transfers")?
+            .json(&self);
+        self.client.send_and_deserialize(request).await
+    }
+}
+
+/// Builder for estimating the fee of a crypto withdrawal.
+#[derive(Debug, Serialize)]
+#[must_use]
+pub struct CryptoFeeEstimateRequest<'a> {
+    #[serde(skip)]
+    client: &'a TradingClient,
+    asset: String,
+    amount: Decimal,
+    to_address: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    from_address: Option<String>,
+}
+
+impl CryptoFeeEstimateRequest<'_> {
+    /// Estimate from a specific sending address rather than the account's
+    /// wallet.
+    pub fn from_address(mut self, address: &str) -> Self {
+        self.from_address = Some(address.to_string());
+        self
+    }
+
+    /// Submit the request.
+    pub async fn execute(self) -> crate::Result<CryptoFeeEstimate> {
+        let request = self
+            .client
+            .request(Method::GET, "v2/wallets/fees/estimate")?
+            .query(&self);
+        self.client.send_and_deserialize(request).await
+    }
+}
+
+impl TradingClient {
+    /// List crypto funding wallets.
+    pub fn list_crypto_wallets(&self) -> ListCryptoWalletsRequest<'_> {
+        ListCryptoWalletsRequest {
+            client: self,
+            asset: None,
+            network: None,
+        }
+    }
+
+    /// Get the deposit address for `asset`, or `None` when the account has
+    /// no wallet for it.
+    pub async fn crypto_deposit_address(&self, asset: &str) -> crate::Result<Option<CryptoWallet>> {
+        let wallets = self.list_crypto_wallets().asset(asset).execute().await?;
+        Ok(wallets.into_iter().next())
+    }
+
+    /// List crypto deposits and withdrawals.
+    pub fn list_crypto_transfers(&self) -> ListCryptoTransfersRequest<'_> {
+        ListCryptoTransfersRequest {
+            client: self,
+            direction: None,
+        }
+    }
+
+    /// Get a crypto transfer by ID, e.g. to poll its status.
+    pub async fn get_crypto_transfer(
+        &self,
+        transfer_id: &CryptoTransferId,
+    ) -> crate::Result<CryptoTransfer> {
+        let request = self.request(Method::GET, &format!("v2/wallets/transfers/{transfer_id}"))?;
+        self.send_and_deserialize(request).await
+    }
+
+    /// Withdraw `amount` of `asset` to `address`, which must already be
+    /// whitelisted and approved.
+    ///
+    /// ```ignore
+    /// let transfer = client
+    ///     .crypto_withdrawal("BTC", Decimal::new(5, 2), "bc1q...")
+    ///     .execute().await?;
+    /// ```
+    pub fn crypto_withdrawal(
+        &self,
+        asset: &str,
+        amount: Decimal,
+        address: &str,
+    ) -> CryptoWithdrawalRequest<'_> {
+        CryptoWithdrawalRequest {
+            client: self,
+            asset: asset.to_string(),
+            amount,
+            address: address.to_string(),
+        }
+    }
+
+    /// Estimate the network fee for withdrawing `amount` of `asset` to
+    /// `to_address`.
+    pub fn crypto_fee_estimate(
+        &self,
+        asset: &str,
+        amount: Decimal,
+        to_address: &str,
+    ) -> CryptoFeeEstimateRequest<'_> {
+        CryptoFeeEstimateRequest {
+            client: self,
+            asset: asset.to_string(),
+            amount,
+            to_address: to_address.to_string(),
+            from_address: None,
+        }
+    }
+
+    /// List whitelisted withdrawal addresses.
+    pub async fn list_whitelisted_addresses(&self) -> crate::Result<Vec<WhitelistedAddress>> {
+        let request = self.request(Method::GET, "v2/wallets/whitelists")?;
+        self.send_and_deserialize(request).await
+    }
+
+    /// Request that `address` be whitelisted for withdrawals of `asset`.
+    /// The address starts out [`WhitelistedAddressStatus::Pending`].
+    pub async fn add_whitelisted_address(
+        &self,
+        asset: &str,
+        address: &str,
+    ) -> crate::Result<WhitelistedAddress> {
+        #[derive(Serialize)]
+        struct Body<'a> {
+            asset: &'a str,
+            address: &'a str,
+        }
+        let request = self
+            .request(Method::POST, "v2/wallets/whitelists")?
+            .json(&Body { asset, address });
+        self.send_and_deserialize(request).await
+    }
+
+    /// Remove a whitelisted withdrawal address.
+    pub async fn remove_whitelisted_address(
+        &self,
+        address_id: &WhitelistedAddressId,
+    ) -> crate::Result<()> {
+        let request = self.request(
+            Method::DELETE,
+            &format!("v2/wallets/whitelists/{address_id}"),
+        )?;
+        self.send_no_body(request).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AccountType;
+    use serial_test::serial;
+    use std::env;
+    use wiremock::matchers::{body_json, method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn paper_client() -> TradingClient {
+        unsafe {
+            if env::var("ALPACA_PAPER_API_KEY_ID").is_err() {
+                env::set_var("ALPACA_PAPER_API_KEY_ID", "test_key_id");
+            }
+            if env::var("ALPACA_PAPER_API_SECRET_KEY").is_err() {
+                env::set_var("ALPACA_PAPER_API_SECRET_KEY", "test_secret_key");
+            }
+        }
+        TradingClient::new(AccountType::Paper).unwrap()
+    }
+
+    fn dec(s: &str) -> Decimal {
+        Decimal::from_str_exact(s).unwrap()
+    }
+
+    fn transfer_json(status: &str) -> serde_json::Value {
+        serde_json::json!({
+            "id": "c3f1b2a4-0000-4000-8000-000000000001",
+            "tx_hash": null,
+            "direction": "OUTGOING",
+            "status": status,
+            "amount": "0.05",
+            "usd_value": "5162.52",
+            "network_fee": "0.0001",
+            "fees": "0",
+            "chain": "BTC",
+            "asset": "BTC",
+            "from_address": null,
+            "to_address": "bc1qexample",
+            "created_at": "2026-05-07T13:30:00Z"
+        })
+    }
+
+    #[test]
+    fn transfer_decodes_string_amounts_as_decimals() {
+        let transfer: CryptoTransfer = serde_json::from_value(transfer_json("PROCESSING")).unwrap();
+        assert_eq!(transfer.amount, dec("0.05"));
+        assert_eq!(transfer.usd_value, Some(dec("5162.52")));
+        assert_eq!(transfer.network_fee, Some(dec("0.0001")));
+        assert_eq!(transfer.direction, CryptoTransferDirection::Outgoing);
+        assert!(!transfer.status.is_terminal());
+        assert!(transfer.tx_hash.is_none());
+    }
+
+    #[test]
+    fn unknown_statuses_decode_to_catch_all() {
+        let transfer: CryptoTransfer = serde_json::from_value(transfer_json("CANCELED")).unwrap();
+        assert_eq!(transfer.status, CryptoTransferStatus::Unknown);
+        assert!(!transfer.status.is_terminal());
+        let status: WhitelistedAddressStatus = serde_json::from_str("\"REJECTED\"").unwrap();
+        assert_eq!(status, WhitelistedAddressStatus::Unknown);
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn withdrawal_posts_amount_as_string_and_polls_status() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v2/wallets/transfers"))
+            .and(body_json(serde_json::json!({
+                "asset": "BTC",
+                "amount": "0.05",
+                "address": "bc1qexample"
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(transfer_json("PROCESSING")))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(
+                "/v2/wallets/transfers/c3f1b2a4-0000-4000-8000-000000000001",
+            ))
+            .respond_with(ResponseTemplate::new(200).set_body_json(transfer_json("COMPLETE")))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = paper_client().with_base_url(&server.uri());
+        let submitted = client
+            .crypto_withdrawal("BTC", dec("0.05"), "bc1qexample")
+            .execute()
+            .await
+            .unwrap();
+        let settled = client.get_crypto_transfer(&submitted.id).await.unwrap();
+        assert_eq!(settled.status, CryptoTransferStatus::Complete);
+        assert!(settled.status.is_terminal());
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn deposit_address_accepts_single_wallet_response() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v2/wallets"))
+            .and(query_param("asset", "ETH"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "asset_id": "35f33a69-f5d6-4dc9-b158-4485e5e92e4b",
+                "address": "0xexample",
+                "created_at": "2026-05-07T13:30:00Z"
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v2/wallets/fees/estimate"))
+            .and(query_param("asset", "ETH"))
+            .and(query_param("amount", "1.5"))
+            .and(query_param("to_address", "0xelsewhere"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(serde_json::json!({"fee": "0.00042"})),
+            )
+            .mount(&server)
+            .await;
+
+        let client = paper_client().with_base_url(&server.uri());
+        let wallet = client.crypto_deposit_address("ETH").await.unwrap().unwrap();
+        assert_eq!(wallet.address, "0xexample");
+        assert!(wallet.id.is_none());
+        let estimate = client
+            .crypto_fee_estimate("ETH", dec("1.5"), "0xelsewhere")
+            .execute()
+            .await
+            .unwrap();
+        assert_eq!(estimate.fee, dec("0.00042"));
+    }
+}
@@ -10,13 +10,25 @@ pub mod assets;
 pub mod calendar;
 /// Market clock endpoint types and methods.
 pub mod clock;
+/// Crypto wallets, transfers and whitelisted withdrawal addresses.
+pub mod crypto_funding;
+/// Option expiration, exercise and assignment handling.
+pub mod option_lifecycle;
+/// Multi-leg option strategies built from option contracts.
+pub mod option_strategies;
 /// Options contracts endpoint types and methods.
 pub mod options_contracts;
+/// Price and quantity rounding to asset increments.
+pub mod order_rounding;
+/// Pre-trade order validation against asset metadata.
+pub mod order_validation;
 /// Orders endpoint types and methods.
 pub mod orders;
 /// Portfolio history endpoint types and methods.
 pub mod portfolio_history;
 /// Positions endpoint types and methods.
 pub mod positions;
+/// Pre-trade risk limits and the daily-loss kill switch.
+pub mod risk_guard;
 /// Watchlists endpoint types and methods.
 pub mod watchlists;
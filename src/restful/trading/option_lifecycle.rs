@@ -0,0 +1,798 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use rust_decimal::Decimal;
+
+use crate::error::{Error, Result};
+use crate::restful::TradingClient;
+use crate::restful::trading::account_activities::{Activity, ActivityType};
+use crate::restful::trading::options_contracts::{OptionContract, OptionType};
+use crate::restful::trading::orders::Side;
+use crate::restful::trading::positions::{Position, PositionSide};
+use crate::{ActivityId, AssetClass, OptionSymbol};
+
+/// Shares delivered per standard contract.
+const SHARES_PER_CONTRACT: Decimal = Decimal::ONE_HUNDRED;
+
+/// Where the underlying trades relative to an option's strike.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Moneyness {
+    /// Exercising would pay off.
+    InTheMoney,
+    /// The underlying sits exactly on the strike.
+    AtTheMoney,
+    /// Exercising would lose money.
+    OutOfTheMoney,
+}
+
+impl Moneyness {
+    /// Classify a contract given the underlying price.
+    pub fn of(option_type: OptionType, strike: Decimal, underlying_price: Decimal) -> Self {
+        let gain = match option_type {
+            OptionType::Call => underlying_price - strike,
+            OptionType::Put => strike - underlying_price,
+        };
+        match gain.cmp(&Decimal::ZERO) {
+            std::cmp::Ordering::Greater => Self::InTheMoney,
+            std::cmp::Ordering::Equal => Self::AtTheMoney,
+            std::cmp::Ordering::Less => Self::OutOfTheMoney,
+        }
+    }
+}
+
+/// An option position close to expiration.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct ExpiringPosition {
+    /// The broker's position.
+    pub position: Position,
+    /// The parsed contract symbol.
+    pub contract: OptionSymbol,
+    /// Calendar days until expiration; `0` on expiration day.
+    pub days_to_expiration: i64,
+    /// Underlying price used for the moneyness figures, if one was given.
+    pub underlying_price: Option<Decimal>,
+    /// Moneyness at `underlying_price`.
+    pub moneyness: Option<Moneyness>,
+    /// Per-share value of exercising now; zero unless in the money.
+    pub intrinsic_value: Option<Decimal>,
+}
+
+impl ExpiringPosition {
+    /// Pick out the option positions expiring within `within_days`
+    /// calendar days of `as_of`, soonest first.
+    ///
+    /// `underlying_prices` maps underlying symbols (e.g. `SPY`) to their
+    /// latest price; positions whose underlying is missing get no
+    /// moneyness. An adjusted contract's root (e.g. `SPY1`) is looked up
+    /// by the underlying it was derived from, its root without the
+    /// trailing digits.
+    pub fn select(
+        positions: &[Position],
+        as_of: NaiveDate,
+        within_days: i64,
+        underlying_prices: &HashMap<String, Decimal>,
+    ) -> Vec<Self> {
+        let mut expiring: Vec<Self> = positions
+            .iter()
+            .filter(|position| position.asset_class == AssetClass::UsOption)
+            .filter_map(|position| {
+                let contract = OptionSymbol::parse(&position.symbol).ok()?;
+                let days_to_expiration = (contract.expiration() - as_of).num_days();
+                if !(0..=within_days).contains(&days_to_expiration) {
+                    return None;
+                }
+                let underlying = contract
+                    .root()
+                    .trim_end_matches(|c: char| c.is_ascii_digit());
+                let underlying_price = underlying_prices.get(underlying).copied();
+                let moneyness = underlying_price
+                    .map(|price| Moneyness::of(contract.option_type(), contract.strike(), price));
+                let intrinsic_value = underlying_price.map(|price| {
+                    match contract.option_type() {
+                        OptionType::Call => price - contract.strike(),
+                        OptionType::Put => contract.strike() - price,
+                    }
+                    .max(Decimal::ZERO)
+                });
+                Some(Self {
+                    position: position.clone(),
+                    contract,
+                    days_to_expiration,
+                    underlying_price,
+                    moneyness,
+                    intrinsic_value,
+                })
+            })
+            .collect();
+        expiring.sort_by(|a, b| a.contract.cmp(&b.contract));
+        expiring
+    }
+
+    fn is_long(&self) -> bool {
+        self.position.side != PositionSide::Short
+    }
+}
+
+/// What to tell the broker about a long position on expiration day.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ExerciseInstruction {
+    /// Exercise the contracts.
+    Exercise,
+    /// Let the contracts lapse even if they would be auto-exercised.
+    DoNotExercise,
+}
+
+/// An instruction [`TradingClient::apply_exercise_policy`] sent.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct AppliedInstruction {
+    /// The option contract symbol.
+    pub symbol: String,
+    /// What was sent.
+    pub instruction: ExerciseInstruction,
+}
+
+/// Rules for instructing long positions on expiration day.
+///
+/// The OCC exercises contracts that finish a cent or more in the money
+/// automatically. A policy overrides that: exercise contracts above a
+/// chosen intrinsic value explicitly, and decline ones too marginal to be
+/// worth taking delivery for. With no rules set it sends nothing. Short
+/// positions and positions without an underlying price are never touched.
+#[derive(Clone, Debug)]
+#[must_use]
+pub struct ExercisePolicy {
+    exercise_at: Option<Decimal>,
+    decline_below: Option<Decimal>,
+    cutoff_before_close: Duration,
+}
+
+impl Default for ExercisePolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ExercisePolicy {
+    /// A policy that sends nothing, with a 30-minute cutoff.
+    pub fn new() -> Self {
+        Self {
+            exercise_at: None,
+            decline_below: None,
+            cutoff_before_close: Duration::minutes(30),
+        }
+    }
+
+    /// Exercise long contracts whose intrinsic value per share is at least
+    /// `min_intrinsic`.
+    pub fn exercise_at(mut self, min_intrinsic: Decimal) -> Self {
+        self.exercise_at = Some(min_intrinsic);
+        self
+    }
+
+    /// Submit do-not-exercise for in-the-money long contracts whose
+    /// intrinsic value per share is below `min_intrinsic`.
+    pub fn decline_below(mut self, min_intrinsic: Decimal) -> Self {
+        self.decline_below = Some(min_intrinsic);
+        self
+    }
+
+    /// How long before the expiration-day close instructions stop being
+    /// sent. Defaults to 30 minutes, matching the broker's 3:30 p.m. ET
+    /// deadline.
+    pub fn cutoff_before_close(mut self, cutoff: Duration) -> Self {
+        self.cutoff_before_close = cutoff;
+        self
+    }
+
+    /// The instruction this policy gives for `position`, if any. Only long
+    /// positions expiring today are considered; exercise wins when both
+    /// thresholds match.
+    pub fn decide(&self, position: &ExpiringPosition) -> Option<ExerciseInstruction> {
+        if position.days_to_expiration != 0 || !position.is_long() {
+            return None;
+        }
+        let intrinsic = position.intrinsic_value?;
+        if self.exercise_at.is_some_and(|min| intrinsic >= min) && !intrinsic.is_zero() {
+            return Some(ExerciseInstruction::Exercise);
+        }
+        if position.moneyness == Some(Moneyness::InTheMoney)
+            && self.decline_below.is_some_and(|min| intrinsic < min)
+        {
+            return Some(ExerciseInstruction::DoNotExercise);
+        }
+        None
+    }
+}
+
+/// What an assignment, exercise or expiration did to the account.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct OptionSettlement {
+    /// The activity this was read from.
+    pub activity_id: ActivityId,
+    /// Assignment, exercise or expiration.
+    pub activity_type: ActivityType,
+    /// The option contract.
+    pub contract: OptionSymbol,
+    /// Contracts removed from the option position.
+    pub contracts: Decimal,
+    /// The stock trade the event produced, if any.
+    pub stock: Option<StockDelivery>,
+}
+
+/// Shares bought or sold at the strike when a contract is exercised or
+/// assigned.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct StockDelivery {
+    /// The underlying symbol.
+    pub symbol: String,
+    /// Whether shares were received (buy) or delivered (sell).
+    pub side: Side,
+    /// Number of shares, always positive.
+    pub qty: Decimal,
+    /// Price per share: the strike.
+    pub price: Decimal,
+}
+
+impl OptionSettlement {
+    /// Interpret an `OPASN`, `OPXRC` or `OPEXP` activity. Returns `None`
+    /// for any other activity.
+    ///
+    /// Exercising a call or being assigned on a put buys 100 shares of the
+    /// contract's root per contract at the strike; exercising a put or
+    /// being assigned on a call sells them. Expiration delivers nothing.
+    /// Adjusted contracts, whose roots end in a digit (`SPY1`), deliver
+    /// something else; settle those with
+    /// [`from_activity_with_contract`](Self::from_activity_with_contract).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidOptionSymbol`] if the activity's symbol is
+    /// not an OCC option symbol, or [`Error::NonStandardDeliverable`] if it
+    /// names an adjusted contract.
+    pub fn from_activity(activity: &Activity) -> Option<Result<Self>> {
+        if !is_settlement(activity) {
+            return None;
+        }
+        let symbol = activity.symbol.as_deref().unwrap_or_default();
+        Some(OptionSymbol::parse(symbol).and_then(|contract| {
+            if contract.root().ends_with(|c: char| c.is_ascii_digit()) {
+                return Err(Error::NonStandardDeliverable {
+                    symbol: symbol.to_string(),
+                });
+            }
+            let delivery = Delivery {
+                symbol: contract.root().to_string(),
+                shares_per_contract: SHARES_PER_CONTRACT,
+                price: contract.strike(),
+            };
+            Ok(Self::settle(activity, contract, delivery))
+        }))
+    }
+
+    /// Interpret an `OPASN`, `OPXRC` or `OPEXP` activity for `contract`,
+    /// delivering what the contract's deliverables say rather than
+    /// assuming 100 shares of its root. Returns `None` for any other
+    /// activity.
+    ///
+    /// A contract listed without deliverables delivers `multiplier` shares
+    /// of its underlying per contract. One with a single equity
+    /// deliverable delivers that security's amount per contract, priced so
+    /// the delivery costs the strike times the multiplier.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidOptionSymbol`] if the activity's symbol is
+    /// not an OCC option symbol, or [`Error::NonStandardDeliverable`] if
+    /// the contract delivers cash or more than one security.
+    pub fn from_activity_with_contract(
+        activity: &Activity,
+        contract: &OptionContract,
+    ) -> Option<Result<Self>> {
+        if !is_settlement(activity) {
+            return None;
+        }
+        let symbol = activity.symbol.as_deref().unwrap_or_default();
+        Some(OptionSymbol::parse(symbol).and_then(|parsed| {
+            let delivery = match contract.deliverables.as_deref() {
+                None | Some([]) => Delivery {
+                    symbol: contract.underlying_symbol.clone(),
+                    shares_per_contract: contract.multiplier,
+                    price: contract.strike_price,
+                },
+                Some([deliverable])
+                    if deliverable.deliverable_type == "equity"
+                        && deliverable.amount > Decimal::ZERO =>
+                {
+                    Delivery {
+                        symbol: deliverable.symbol.clone(),
+                        shares_per_contract: deliverable.amount,
+                        price: contract.strike_price * contract.multiplier / deliverable.amount,
+                    }
+                }
+                Some(_) => {
+                    return Err(Error::NonStandardDeliverable {
+                        symbol: symbol.to_string(),
+                    });
+                }
+            };
+            Ok(Self::settle(activity, parsed, delivery))
+        }))
+    }
+
+    fn settle(activity: &Activity, contract: OptionSymbol, delivery: Delivery) -> Self {
+        let contracts = activity.qty.unwrap_or_default().abs();
+        let side = match (&activity.activity_type, contract.option_type()) {
+            (ActivityType::OptionExercise, OptionType::Call)
+            | (ActivityType::OptionAssignment, OptionType::Put) => Some(Side::Buy),
+            (ActivityType::OptionExercise, OptionType::Put)
+            | (ActivityType::OptionAssignment, OptionType::Call) => Some(Side::Sell),
+            _ => None,
+        };
+        let stock = side.map(|side| StockDelivery {
+            symbol: delivery.symbol,
+            side,
+            qty: contracts * delivery.shares_per_contract,
+            price: delivery.price,
+        });
+        Self {
+            activity_id: activity.id.clone(),
+            activity_type: activity.activity_type.clone(),
+            contract,
+            contracts,
+            stock,
+        }
+    }
+}
+
+/// What one contract delivers when exercised or assigned.
+struct Delivery {
+    symbol: String,
+    shares_per_contract: Decimal,
+    price: Decimal,
+}
+
+fn is_settlement(activity: &Activity) -> bool {
+    matches!(
+        activity.activity_type,
+        ActivityType::OptionAssignment
+            | ActivityType::OptionExercise
+            | ActivityType::OptionExpiration
+    )
+}
+
+impl TradingClient {
+    /// List open option positions expiring within `within_days` calendar
+    /// days of `as_of`. See [`ExpiringPosition::select`].
+    pub async fn expiring_option_positions(
+        &self,
+        as_of: NaiveDate,
+        within_days: i64,
+        underlying_prices: &HashMap<String, Decimal>,
+    ) -> Result<Vec<ExpiringPosition>> {
+        let positions = self.list_positions().await?;
+        Ok(ExpiringPosition::select(
+            &positions,
+            as_of,
+            within_days,
+            underlying_prices,
+        ))
+    }
+
+    /// Send the exercise and do-not-exercise instructions `policy` gives
+    /// for `positions`, returning what was sent.
+    ///
+    /// The market clock is checked first, so nothing is sent once the
+    /// policy's cutoff before the expiration-day close has passed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::ExerciseCutoffPassed`] if the cutoff has passed, or
+    /// the first error an instruction request fails with. Once an
+    /// instruction has gone through, a later failure is returned as
+    /// [`Error::ExercisePolicyIncomplete`] carrying the instructions sent.
+    pub async fn apply_exercise_policy(
+        &self,
+        positions: &[ExpiringPosition],
+        policy: &ExercisePolicy,
+    ) -> Result<Vec<AppliedInstruction>> {
+        let decisions: Vec<(&ExpiringPosition, ExerciseInstruction)> = positions
+            .iter()
+            .filter_map(|position| Some((position, policy.decide(position)?)))
+            .collect();
+        if decisions.is_empty() {
+            return Ok(Vec::new());
+        }
+        let clock = self.get_clock().await?;
+        let mut applied = Vec::with_capacity(decisions.len());
+        for (position, instruction) in decisions {
+            let deadline = clock.next_close - policy.cutoff_before_close;
+            let symbol = &position.position.symbol;
+            let sent = if past_cutoff(
+                clock.timestamp,
+                clock.next_close,
+                deadline,
+                position.contract.expiration(),
+            ) {
+                Err(Error::ExerciseCutoffPassed {
+                    symbol: symbol.clone(),
+                    deadline,
+                })
+            } else {
+                match instruction {
+                    ExerciseInstruction::Exercise => self.exercise_option(symbol).await,
+                    ExerciseInstruction::DoNotExercise => self.do_not_exercise(symbol).await,
+                }
+            };
+            if let Err(error) = sent {
+                return Err(if applied.is_empty() {
+                    error
+                } else {
+                    Error::ExercisePolicyIncomplete {
+                        applied,
+                        source: Box::new(error),
+                    }
+                });
+            }
+            applied.push(AppliedInstruction {
+                symbol: position.position.symbol.clone(),
+                instruction,
+            });
+        }
+        Ok(applied)
+    }
+}
+
+/// Whether instructions for a contract expiring on `expiration` are too
+/// late. A next close after the expiration date means that session is
+/// over.
+fn past_cutoff(
+    now: DateTime<Utc>,
+    next_close: DateTime<Utc>,
+    deadline: DateTime<Utc>,
+    expiration: NaiveDate,
+) -> bool {
+    next_close.date_naive() > expiration
+        || (next_close.date_naive() == expiration && now > deadline)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AccountType;
+    use serial_test::serial;
+    use std::env;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn dec(s: &str) -> Decimal {
+        Decimal::from_str_exact(s).unwrap()
+    }
+
+    fn position(symbol: &str, qty: &str, side: &str) -> serde_json::Value {
+        serde_json::json!({
+            "asset_id": "904837e3-3b76-47ec-b432-046db621571b",
+            "symbol": symbol,
+            "exchange": "NYSE",
+            "asset_class": "us_option",
+            "asset_marginable": false,
+            "avg_entry_price": "1.10",
+            "qty": qty,
+            "qty_available": qty,
+            "side": side,
+            "market_value": "110",
+            "cost_basis": "110",
+            "unrealized_pl": "0",
+            "unrealized_plpc": "0",
+            "unrealized_intraday_pl": "0",
+            "unrealized_intraday_plpc": "0",
+            "current_price": "1.10",
+            "lastday_price": "1.10",
+            "change_today": "0"
+        })
+    }
+
+    fn positions() -> Vec<Position> {
+        [
+            position("SPY250117C00500000", "2", "long"),
+            position("SPY250117P00505000", "1", "long"),
+            position("SPY250117C00520000", "-1", "short"),
+            position("SPY250221C00500000", "1", "long"),
+        ]
+        .into_iter()
+        .map(|json| serde_json::from_value(json).unwrap())
+        .collect()
+    }
+
+    #[test]
+    fn selects_expiring_positions_and_applies_policy() {
+        let today = NaiveDate::from_ymd_opt(2025, 1, 17).unwrap();
+        let prices = HashMap::from([("SPY".to_string(), dec("504.90"))]);
+        let expiring = ExpiringPosition::select(&positions(), today, 7, &prices);
+        let symbols: Vec<&str> = expiring.iter().map(|p| p.contract.as_str()).collect();
+        assert_eq!(
+            symbols,
+            [
+                "SPY250117C00500000",
+                "SPY250117C00520000",
+                "SPY250117P00505000"
+            ]
+        );
+        assert_eq!(expiring[0].moneyness, Some(Moneyness::InTheMoney));
+        assert_eq!(expiring[0].intrinsic_value, Some(dec("4.90")));
+        assert_eq!(expiring[1].moneyness, Some(Moneyness::OutOfTheMoney));
+        assert_eq!(expiring[2].intrinsic_value, Some(dec("0.10")));
+
+        let adjusted: Position =
+            serde_json::from_value(position("SPY1250117C00500000", "1", "long")).unwrap();
+        let adjusted = ExpiringPosition::select(&[adjusted], today, 7, &prices);
+        assert_eq!(adjusted[0].underlying_price, Some(dec("504.90")));
+
+        let policy = ExercisePolicy::new()
+            .exercise_at(dec("1"))
+            .decline_below(dec("0.25"));
+        let decisions: Vec<_> = expiring.iter().map(|p| policy.decide(p)).collect();
+        assert_eq!(
+            decisions,
+            [
+                Some(ExerciseInstruction::Exercise),
+                None,
+                Some(ExerciseInstruction::DoNotExercise)
+            ]
+        );
+        assert!(ExercisePolicy::new().decide(&expiring[0]).is_none());
+    }
+
+    #[test]
+    fn settles_assignment_exercise_and_expiration_activities() {
+        let activity = |kind: &str, symbol: &str, qty: &str| -> Activity {
+            serde_json::from_value(serde_json::json!({
+                "id": format!("20250117000000000::{kind}"),
+                "activity_type": kind,
+                "symbol": symbol,
+                "qty": qty,
+                "date": "2025-01-17",
+            }))
+            .unwrap()
+        };
+
+        let assigned =
+            OptionSettlement::from_activity(&activity("OPASN", "SPY250117C00520000", "-1"))
+                .unwrap()
+                .unwrap();
+        assert_eq!(assigned.contracts, dec("1"));
+        let stock = assigned.stock.as_ref().unwrap();
+        assert_eq!((stock.symbol.as_str(), stock.side), ("SPY", Side::Sell));
+        assert_eq!((stock.qty, stock.price), (dec("100"), dec("520")));
+
+        let exercised =
+            OptionSettlement::from_activity(&activity("OPXRC", "SPY250117P00505000", "1"))
+                .unwrap()
+                .unwrap();
+        assert_eq!(exercised.stock.unwrap().side, Side::Sell);
+
+        let expired =
+            OptionSettlement::from_activity(&activity("OPEXP", "SPY250117C00530000", "2"))
+                .unwrap()
+                .unwrap();
+        assert_eq!(expired.contracts, dec("2"));
+        assert!(expired.stock.is_none());
+
+        assert!(OptionSettlement::from_activity(&activity("DIV", "SPY", "1")).is_none());
+        assert!(matches!(
+            OptionSettlement::from_activity(&activity("OPASN", "SPY1250117C00520000", "-1")),
+            Some(Err(Error::NonStandardDeliverable { .. }))
+        ));
+
+        let mut ledger = crate::PositionLedger::new(crate::CostBasisMethod::Fifo);
+        ledger.apply_fill("SPY250117C00520000", Side::Sell, dec("1"), dec("2.00"));
+        assert!(ledger.apply_option_settlement(&assigned));
+        assert!(!ledger.apply_option_settlement(&assigned));
+        let option = ledger.position("SPY250117C00520000").unwrap();
+        assert!(option.is_flat());
+        assert_eq!(option.realized_pl, dec("200"));
+        let shares = ledger.position("SPY").unwrap();
+        assert_eq!(shares.qty(), dec("-100"));
+        assert_eq!(shares.avg_cost(), Some(dec("520")));
+    }
+
+    #[test]
+    fn settles_adjusted_contracts_by_their_deliverables() {
+        let activity: Activity = serde_json::from_value(serde_json::json!({
+            "id": "20250117000000000::OPXRC",
+            "activity_type": "OPXRC",
+            "symbol": "XYZ1250117C00030000",
+            "qty": "2",
+            "date": "2025-01-17",
+        }))
+        .unwrap();
+        let mut contract: OptionContract = serde_json::from_value(serde_json::json!({
+            "id": "contract-1",
+            "symbol": "XYZ1250117C00030000",
+            "name": "XYZ1 Jan 17 2025 30 Call",
+            "status": "active",
+            "tradable": true,
+            "expiration_date": "2025-01-17",
+            "root_symbol": "XYZ1",
+            "underlying_symbol": "XYZ",
+            "type": "call",
+            "style": "american",
+            "strike_price": "30",
+            "multiplier": "100",
+            "deliverables": [{
+                "type": "equity",
+                "symbol": "ABC",
+                "amount": "150",
+                "allocation_percentage": "100",
+                "settlement_type": "T+1",
+                "settlement_method": "CCC",
+                "delayed_settlement": false
+            }]
+        }))
+        .unwrap();
+
+        let settlement = OptionSettlement::from_activity_with_contract(&activity, &contract)
+            .unwrap()
+            .unwrap();
+        let stock = settlement.stock.unwrap();
+        assert_eq!((stock.symbol.as_str(), stock.side), ("ABC", Side::Buy));
+        assert_eq!((stock.qty, stock.price), (dec("300"), dec("20")));
+
+        contract.deliverables = None;
+        let stock = OptionSettlement::from_activity_with_contract(&activity, &contract)
+            .unwrap()
+            .unwrap()
+            .stock
+            .unwrap();
+        assert_eq!(stock.symbol, "XYZ");
+        assert_eq!((stock.qty, stock.price), (dec("200"), dec("30")));
+
+        let mut cash = contract.clone();
+        cash.deliverables = Some(vec![
+            serde_json::from_value(serde_json::json!({
+                "type": "cash",
+                "symbol": "USD",
+                "amount": "500",
+                "allocation_percentage": "100",
+                "settlement_type": "T+1",
+                "settlement_method": "CCC",
+                "delayed_settlement": false
+            }))
+            .unwrap(),
+        ]);
+        assert!(matches!(
+            OptionSettlement::from_activity_with_contract(&activity, &cash),
+            Some(Err(Error::NonStandardDeliverable { .. }))
+        ));
+    }
+
+    /// A failure partway through still reports the instructions that went
+    /// through.
+    #[tokio::test]
+    #[serial]
+    async fn apply_exercise_policy_reports_instructions_sent_before_a_failure() {
+        unsafe {
+            if env::var("ALPACA_PAPER_API_KEY_ID").is_err() {
+                env::set_var("ALPACA_PAPER_API_KEY_ID", "test_key_id");
+            }
+            if env::var("ALPACA_PAPER_API_SECRET_KEY").is_err() {
+                env::set_var("ALPACA_PAPER_API_SECRET_KEY", "test_secret_key");
+            }
+        }
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v2/clock"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "timestamp": "2025-01-17T18:00:00Z",
+                "is_open": true,
+                "next_open": "2025-01-21T14:30:00Z",
+                "next_close": "2025-01-17T21:00:00Z",
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/v2/positions/SPY250117C00500000/exercise"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/v2/positions/SPY250117P00505000/exercise"))
+            .respond_with(ResponseTemplate::new(403))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = TradingClient::new(AccountType::Paper)
+            .unwrap()
+            .with_base_url(&server.uri());
+        let today = NaiveDate::from_ymd_opt(2025, 1, 17).unwrap();
+        let prices = HashMap::from([("SPY".to_string(), dec("504.90"))]);
+        let expiring = ExpiringPosition::select(&positions(), today, 0, &prices);
+        let policy = ExercisePolicy::new().exercise_at(dec("0.05"));
+
+        let err = client
+            .apply_exercise_policy(&expiring, &policy)
+            .await
+            .unwrap_err();
+        let Error::ExercisePolicyIncomplete { applied, source } = err else {
+            panic!("expected a partial run, got {err:?}");
+        };
+        assert_eq!(
+            applied,
+            [AppliedInstruction {
+                symbol: "SPY250117C00500000".to_string(),
+                instruction: ExerciseInstruction::Exercise,
+            }]
+        );
+        assert!(
+            matches!(*source, Error::ApiError { status: 403, .. }),
+            "{source:?}"
+        );
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn apply_exercise_policy_respects_cutoff() {
+        unsafe {
+            if env::var("ALPACA_PAPER_API_KEY_ID").is_err() {
+                env::set_var("ALPACA_PAPER_API_KEY_ID", "test_key_id");
+            }
+            if env::var("ALPACA_PAPER_API_SECRET_KEY").is_err() {
+                env::set_var("ALPACA_PAPER_API_SECRET_KEY", "test_secret_key");
+            }
+        }
+        let server = MockServer::start().await;
+        // 3:45 p.m. ET on expiration day: 15 minutes before the close.
+        Mock::given(method("GET"))
+            .and(path("/v2/clock"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "timestamp": "2025-01-17T20:45:00Z",
+                "is_open": true,
+                "next_open": "2025-01-21T14:30:00Z",
+                "next_close": "2025-01-17T21:00:00Z",
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/v2/positions/SPY250117C00500000/exercise"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = TradingClient::new(AccountType::Paper)
+            .unwrap()
+            .with_base_url(&server.uri());
+        let today = NaiveDate::from_ymd_opt(2025, 1, 17).unwrap();
+        let prices = HashMap::from([("SPY".to_string(), dec("504.90"))]);
+        let expiring = ExpiringPosition::select(&positions()[..1], today, 0, &prices);
+        let policy = ExercisePolicy::new().exercise_at(dec("1"));
+
+        let err = client
+            .apply_exercise_policy(&expiring, &policy)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::ExerciseCutoffPassed { .. }), "{err:?}");
+
+        let applied = client
+            .apply_exercise_policy(
+                &expiring,
+                &policy.cutoff_before_close(Duration::minutes(10)),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            applied,
+            [AppliedInstruction {
+                symbol: "SPY250117C00500000".to_string(),
+                instruction: ExerciseInstruction::Exercise,
+            }]
+        );
+    }
+}
@@ -0,0 +1,539 @@
+use std::collections::HashMap;
+
+use rust_decimal::Decimal;
+use rust_decimal::prelude::FromPrimitive;
+
+use crate::error::{Error, Result};
+use crate::restful::TradingClient;
+use crate::restful::market_data::options::OptionQuote;
+use crate::restful::trading::options_contracts::{OptionContract, OptionType};
+use crate::restful::trading::orders::{CreateMultiLegOrderRequest, OrderLeg, PositionIntent, Side};
+
+/// One leg of an [`OptionStrategy`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct StrategyLeg {
+    /// Option contract symbol.
+    pub symbol: String,
+    /// Call or put.
+    pub option_type: OptionType,
+    /// Strike price.
+    pub strike: Decimal,
+    /// Whether the leg is bought or sold when the strategy is opened.
+    pub side: Side,
+    /// Contracts of this leg per unit of the strategy.
+    pub ratio_qty: Decimal,
+}
+
+impl StrategyLeg {
+    fn from_contract(contract: &OptionContract, side: Side, ratio_qty: u32) -> Self {
+        Self {
+            symbol: contract.symbol.clone(),
+            option_type: contract.option_type,
+            strike: contract.strike_price,
+            side,
+            ratio_qty: Decimal::from(ratio_qty),
+        }
+    }
+
+    /// `+1` for a bought leg, `-1` for a sold one, times the ratio.
+    fn signed_ratio(&self) -> Decimal {
+        match self.side {
+            Side::Buy => self.ratio_qty,
+            Side::Sell => -self.ratio_qty,
+        }
+    }
+
+    fn intrinsic(&self, spot: Decimal) -> Decimal {
+        match self.option_type {
+            OptionType::Call => (spot - self.strike).max(Decimal::ZERO),
+            OptionType::Put => (self.strike - spot).max(Decimal::ZERO),
+        }
+    }
+}
+
+/// Risk profile of an [`OptionStrategy`] held to expiration.
+///
+/// Money amounts are per unit of the strategy, in dollars (per-share
+/// premium times the contract multiplier).
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct StrategyProfile {
+    /// Most the position can make, or `None` if the gain is unlimited.
+    pub max_profit: Option<Decimal>,
+    /// Most the position can lose, as a positive amount, or `None` if the
+    /// loss is unlimited.
+    pub max_loss: Option<Decimal>,
+    /// Underlying prices at which the position breaks even, ascending.
+    pub breakevens: Vec<Decimal>,
+}
+
+/// A multi-leg option position with validated legs, ready to submit as an
+/// `mleg` order.
+///
+/// Each constructor checks that the contracts share an underlying,
+/// expiration and multiplier, that the call/put types fit the strategy and
+/// that the strikes are ordered. `side` picks whether the strategy is
+/// bought or sold: buying a straddle buys both legs, selling an iron
+/// condor collects the credit.
+///
+/// Net prices follow Alpaca's convention for `mleg` limit prices: positive
+/// is a debit paid, negative a credit received.
+///
+/// ```ignore
+/// let spread = OptionStrategy::vertical(&long_call, &short_call)?;
+/// let price = spread.suggested_limit_price(&quotes)?;
+/// let order = client.option_strategy_order(&spread, price).qty(Decimal::ONE);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OptionStrategy {
+    underlying: String,
+    multiplier: Decimal,
+    legs: Vec<StrategyLeg>,
+}
+
+impl OptionStrategy {
+    /// A vertical spread: buy `long`, sell `short`, same type and
+    /// expiration, different strikes. Whether it is a debit or credit
+    /// spread follows from the strikes.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidStrategy`] if the types differ or the
+    /// strikes are equal, or if the contracts do not share an underlying,
+    /// expiration and multiplier.
+    pub fn vertical(long: &OptionContract, short: &OptionContract) -> Result<Self> {
+        if long.option_type != short.option_type {
+            return Err(invalid("vertical legs must both be calls or both be puts"));
+        }
+        if long.strike_price == short.strike_price {
+            return Err(invalid("vertical legs must have different strikes"));
+        }
+        Self::build(&[(long, Side::Buy, 1), (short, Side::Sell, 1)])
+    }
+
+    /// A straddle: a call and a put at the same strike.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidStrategy`] if the types or strikes do not
+    /// fit, or if the contracts do not share an underlying, expiration and
+    /// multiplier.
+    pub fn straddle(call: &OptionContract, put: &OptionContract, side: Side) -> Result<Self> {
+        expect_type(call, OptionType::Call)?;
+        expect_type(put, OptionType::Put)?;
+        if call.strike_price != put.strike_price {
+            return Err(invalid("straddle legs must share a strike"));
+        }
+        Self::build(&[(call, side, 1), (put, side, 1)])
+    }
+
+    /// A strangle: an out-of-the-money put below an out-of-the-money call.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidStrategy`] if the types do not fit or the
+    /// put strike is not below the call strike, or if the contracts do not
+    /// share an underlying, expiration and multiplier.
+    pub fn strangle(put: &OptionContract, call: &OptionContract, side: Side) -> Result<Self> {
+        expect_type(put, OptionType::Put)?;
+        expect_type(call, OptionType::Call)?;
+        if put.strike_price >= call.strike_price {
+            return Err(invalid("strangle put strike must be below the call strike"));
+        }
+        Self::build(&[(put, side, 1), (call, side, 1)])
+    }
+
+    /// An iron condor from four strikes, lowest first: a put spread on
+    /// `put_wing`/`put_body` and a call spread on `call_body`/`call_wing`.
+    /// Selling it sells the bodies and buys the wings for a credit.
+    ///
+    /// Equal body strikes make an iron butterfly.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidStrategy`] if the types do not fit or the
+    /// strikes are not ascending, or if the contracts do not share an
+    /// underlying, expiration and multiplier.
+    pub fn iron_condor(
+        put_wing: &OptionContract,
+        put_body: &OptionContract,
+        call_body: &OptionContract,
+        call_wing: &OptionContract,
+        side: Side,
+    ) -> Result<Self> {
+        expect_type(put_wing, OptionType::Put)?;
+        expect_type(put_body, OptionType::Put)?;
+        expect_type(call_body, OptionType::Call)?;
+        expect_type(call_wing, OptionType::Call)?;
+        if put_wing.strike_price >= put_body.strike_price
+            || put_body.strike_price > call_body.strike_price
+            || call_body.strike_price >= call_wing.strike_price
+        {
+            return Err(invalid(
+                "iron condor strikes must ascend from put wing to call wing",
+            ));
+        }
+        let body = side;
+        let wing = opposite(side);
+        Self::build(&[
+            (put_wing, wing, 1),
+            (put_body, body, 1),
+            (call_body, body, 1),
+            (call_wing, wing, 1),
+        ])
+    }
+
+    /// A 1:2:1 butterfly of one type with equally spaced strikes. Buying it
+    /// buys the wings and sells two of the body.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidStrategy`] if the types differ, the strikes
+    /// are not ascending or the wings are uneven, or if the contracts do
+    /// not share an underlying, expiration and multiplier.
+    pub fn butterfly(
+        lower: &OptionContract,
+        middle: &OptionContract,
+        upper: &OptionContract,
+        side: Side,
+    ) -> Result<Self> {
+        expect_type(middle, lower.option_type)?;
+        expect_type(upper, lower.option_type)?;
+        if lower.strike_price >= middle.strike_price || middle.strike_price >= upper.strike_price {
+            return Err(invalid("butterfly strikes must ascend"));
+        }
+        if middle.strike_price - lower.strike_price != upper.strike_price - middle.strike_price {
+            return Err(invalid("butterfly wings must be equally wide"));
+        }
+        let wing = side;
+        let body = opposite(side);
+        Self::build(&[(lower, wing, 1), (middle, body, 2), (upper, wing, 1)])
+    }
+
+    fn build(legs: &[(&OptionContract, Side, u32)]) -> Result<Self> {
+        let (first, _, _) = legs[0];
+        for (contract, _, _) in &legs[1..] {
+            if contract.underlying_symbol != first.underlying_symbol {
+                return Err(invalid("all legs must share an underlying"));
+            }
+            if contract.expiration_date != first.expiration_date {
+                return Err(invalid("all legs must share an expiration"));
+            }
+            if contract.multiplier != first.multiplier {
+                return Err(invalid("all legs must share a contract multiplier"));
+            }
+        }
+        Ok(Self {
+            underlying: first.underlying_symbol.clone(),
+            multiplier: first.multiplier,
+            legs: legs
+                .iter()
+                .map(|(contract, side, ratio)| StrategyLeg::from_contract(contract, *side, *ratio))
+                .collect(),
+        })
+    }
+
+    /// The underlying symbol.
+    pub fn underlying(&self) -> &str {
+        &self.underlying
+    }
+
+    /// The legs, in the order the strategy's constructor takes them.
+    pub fn legs(&self) -> &[StrategyLeg] {
+        &self.legs
+    }
+
+    /// Legs for an order that opens the strategy.
+    pub fn order_legs(&self) -> Vec<OrderLeg> {
+        self.legs
+            .iter()
+            .map(|leg| {
+                let intent = match leg.side {
+                    Side::Buy => PositionIntent::BuyToOpen,
+                    Side::Sell => PositionIntent::SellToOpen,
+                };
+                OrderLeg::new(&leg.symbol, leg.side, leg.ratio_qty, intent)
+            })
+            .collect()
+    }
+
+    /// Legs for an order that closes a previously opened strategy: every
+    /// side is flipped.
+    pub fn closing_order_legs(&self) -> Vec<OrderLeg> {
+        self.legs
+            .iter()
+            .map(|leg| {
+                let (side, intent) = match leg.side {
+                    Side::Buy => (Side::Sell, PositionIntent::SellToClose),
+                    Side::Sell => (Side::Buy, PositionIntent::BuyToClose),
+                };
+                OrderLeg::new(&leg.symbol, side, leg.ratio_qty, intent)
+            })
+            .collect()
+    }
+
+    /// Max profit, max loss and breakevens at expiration when opened at
+    /// `net_price` per share (positive for a debit, negative for a credit).
+    pub fn profile(&self, net_price: Decimal) -> StrategyProfile {
+        let mut strikes: Vec<Decimal> = self.legs.iter().map(|leg| leg.strike).collect();
+        strikes.push(Decimal::ZERO);
+        strikes.sort();
+        strikes.dedup();
+        let value = |spot: Decimal| -> Decimal {
+            self.legs
+                .iter()
+                .map(|leg| leg.signed_ratio() * leg.intrinsic(spot))
+                .sum::<Decimal>()
+                - net_price
+        };
+        let points: Vec<(Decimal, Decimal)> = strikes.iter().map(|&s| (s, value(s))).collect();
+        // Above the highest strike only calls still move.
+        let slope: Decimal = self
+            .legs
+            .iter()
+            .filter(|leg| leg.option_type == OptionType::Call)
+            .map(StrategyLeg::signed_ratio)
+            .sum();
+
+        let mut breakevens = Vec::new();
+        for pair in points.windows(2) {
+            let ((a, va), (b, vb)) = (pair[0], pair[1]);
+            if va.is_zero() {
+                breakevens.push(a);
+            } else if va * vb < Decimal::ZERO {
+                breakevens.push(a + (b - a) * -va / (vb - va));
+            }
+        }
+        let (last, v_last) = points[points.len() - 1];
+        if v_last.is_zero() {
+            breakevens.push(last);
+        } else if v_last * slope < Decimal::ZERO {
+            breakevens.push(last - v_last / slope);
+        }
+
+        let best = points.iter().map(|(_, v)| *v).max().unwrap_or_default();
+        let worst = points.iter().map(|(_, v)| *v).min().unwrap_or_default();
+        StrategyProfile {
+            max_profit: (slope <= Decimal::ZERO).then(|| best * self.multiplier),
+            max_loss: (slope >= Decimal::ZERO).then(|| -worst * self.multiplier),
+            breakevens,
+        }
+    }
+
+    /// Net price from the quote midpoints, rounded to the cent. A starting
+    /// point for the `mleg` limit price.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidStrategy`] if `quotes` lacks a leg's symbol.
+    pub fn suggested_limit_price(&self, quotes: &HashMap<String, OptionQuote>) -> Result<Decimal> {
+        self.net_price(quotes, |_, quote| (quote.bid_price + quote.ask_price) / 2.0)
+    }
+
+    /// Net price paying the ask on bought legs and hitting the bid on sold
+    /// ones: what the strategy costs to open immediately.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidStrategy`] if `quotes` lacks a leg's symbol.
+    pub fn natural_price(&self, quotes: &HashMap<String, OptionQuote>) -> Result<Decimal> {
+        self.net_price(quotes, |side, quote| match side {
+            Side::Buy => quote.ask_price,
+            Side::Sell => quote.bid_price,
+        })
+    }
+
+    fn net_price(
+        &self,
+        quotes: &HashMap<String, OptionQuote>,
+        price: impl Fn(Side, &OptionQuote) -> f64,
+    ) -> Result<Decimal> {
+        let mut total = Decimal::ZERO;
+        for leg in &self.legs {
+            let quote = quotes
+                .get(&leg.symbol)
+                .ok_or_else(|| Error::InvalidStrategy(format!("no quote for {}", leg.symbol)))?;
+            let leg_price = Decimal::from_f64(price(leg.side, quote)).ok_or_else(|| {
+                Error::InvalidStrategy(format!("unusable quote for {}", leg.symbol))
+            })?;
+            total += leg.signed_ratio() * leg_price;
+        }
+        Ok(total.round_dp(2))
+    }
+}
+
+impl TradingClient {
+    /// Begin an `mleg` order opening `strategy` at the net `limit_price`
+    /// (positive for a debit, negative for a credit). Set the number of
+    /// strategy units with [`CreateMultiLegOrderRequest::qty`].
+    pub fn option_strategy_order(
+        &self,
+        strategy: &OptionStrategy,
+        limit_price: Decimal,
+    ) -> CreateMultiLegOrderRequest<'_> {
+        self.mleg_limit_order(strategy.order_legs(), limit_price)
+    }
+}
+
+fn invalid(reason: &str) -> Error {
+    Error::InvalidStrategy(reason.to_string())
+}
+
+fn expect_type(contract: &OptionContract, option_type: OptionType) -> Result<()> {
+    if contract.option_type == option_type {
+        Ok(())
+    } else {
+        Err(Error::InvalidStrategy(format!(
+            "{} is a {:?}, expected a {:?}",
+            contract.symbol, contract.option_type, option_type
+        )))
+    }
+}
+
+fn opposite(side: Side) -> Side {
+    match side {
+        Side::Buy => Side::Sell,
+        Side::Sell => Side::Buy,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dec(s: &str) -> Decimal {
+        Decimal::from_str_exact(s).unwrap()
+    }
+
+    fn contract(symbol: &str) -> OptionContract {
+        let parsed = crate::OptionSymbol::parse(symbol).unwrap();
+        serde_json::from_value(serde_json::json!({
+            "id": "c0ffee",
+            "symbol": symbol,
+            "name": symbol,
+            "status": "active",
+            "tradable": true,
+            "expiration_date": parsed.expiration(),
+            "underlying_symbol": parsed.root(),
+            "type": parsed.option_type(),
+            "style": "american",
+            "strike_price": parsed.strike().to_string(),
+            "multiplier": "100",
+        }))
+        .unwrap()
+    }
+
+    fn quote(bid: f64, ask: f64) -> OptionQuote {
+        serde_json::from_value(serde_json::json!({
+            "t": "2025-01-10T15:00:00Z",
+            "bx": "C", "bp": bid, "bs": 10,
+            "ax": "C", "ap": ask, "as": 10,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn vertical_spread_legs_profile_and_price() {
+        let long = contract("SPY250117C00500000");
+        let short = contract("SPY250117C00510000");
+        let spread = OptionStrategy::vertical(&long, &short).unwrap();
+
+        let legs = spread.order_legs();
+        assert_eq!(legs[0].position_intent, PositionIntent::BuyToOpen);
+        assert_eq!(legs[1].side, Side::Sell);
+        let closing = spread.closing_order_legs();
+        assert_eq!(closing[0].position_intent, PositionIntent::SellToClose);
+        assert_eq!(closing[1].position_intent, PositionIntent::BuyToClose);
+
+        let profile = spread.profile(dec("4.00"));
+        assert_eq!(profile.max_profit, Some(dec("600")));
+        assert_eq!(profile.max_loss, Some(dec("400")));
+        assert_eq!(profile.breakevens, vec![dec("504")]);
+
+        let quotes = HashMap::from([
+            (long.symbol.clone(), quote(6.0, 6.2)),
+            (short.symbol.clone(), quote(2.0, 2.1)),
+        ]);
+        assert_eq!(spread.suggested_limit_price(&quotes).unwrap(), dec("4.05"));
+        assert_eq!(spread.natural_price(&quotes).unwrap(), dec("4.2"));
+        assert!(matches!(
+            spread.suggested_limit_price(&HashMap::new()),
+            Err(Error::InvalidStrategy(_))
+        ));
+    }
+
+    #[test]
+    fn short_strategies_profile_unlimited_and_bounded_risk() {
+        let straddle = OptionStrategy::straddle(
+            &contract("SPY250117C00500000"),
+            &contract("SPY250117P00500000"),
+            Side::Sell,
+        )
+        .unwrap();
+        let profile = straddle.profile(dec("-12"));
+        assert_eq!(profile.max_profit, Some(dec("1200")));
+        assert_eq!(profile.max_loss, None);
+        assert_eq!(profile.breakevens, vec![dec("488"), dec("512")]);
+
+        let condor = OptionStrategy::iron_condor(
+            &contract("SPY250117P00480000"),
+            &contract("SPY250117P00490000"),
+            &contract("SPY250117C00510000"),
+            &contract("SPY250117C00520000"),
+            Side::Sell,
+        )
+        .unwrap();
+        let sides: Vec<Side> = condor.legs().iter().map(|leg| leg.side).collect();
+        assert_eq!(sides, [Side::Buy, Side::Sell, Side::Sell, Side::Buy]);
+        let profile = condor.profile(dec("-3"));
+        assert_eq!(profile.max_profit, Some(dec("300")));
+        assert_eq!(profile.max_loss, Some(dec("700")));
+        assert_eq!(profile.breakevens, vec![dec("487"), dec("513")]);
+
+        let butterfly = OptionStrategy::butterfly(
+            &contract("SPY250117C00490000"),
+            &contract("SPY250117C00500000"),
+            &contract("SPY250117C00510000"),
+            Side::Buy,
+        )
+        .unwrap();
+        assert_eq!(butterfly.legs()[1].ratio_qty, dec("2"));
+        let profile = butterfly.profile(dec("2"));
+        assert_eq!(profile.max_profit, Some(dec("800")));
+        assert_eq!(profile.max_loss, Some(dec("200")));
+        assert_eq!(profile.breakevens, vec![dec("492"), dec("508")]);
+    }
+
+    #[test]
+    fn rejects_mismatched_legs() {
+        let cases = [
+            OptionStrategy::vertical(
+                &contract("SPY250117C00500000"),
+                &contract("SPY250117P00510000"),
+            ),
+            OptionStrategy::vertical(
+                &contract("SPY250117C00500000"),
+                &contract("SPY250221C00510000"),
+            ),
+            OptionStrategy::vertical(
+                &contract("SPY250117C00500000"),
+                &contract("QQQ250117C00510000"),
+            ),
+            OptionStrategy::strangle(
+                &contract("SPY250117P00510000"),
+                &contract("SPY250117C00500000"),
+                Side::Buy,
+            ),
+            OptionStrategy::butterfly(
+                &contract("SPY250117C00490000"),
+                &contract("SPY250117C00500000"),
+                &contract("SPY250117C00520000"),
+                Side::Buy,
+            ),
+        ];
+        for case in cases {
+            assert!(matches!(case, Err(Error::InvalidStrategy(_))), "{case:?}");
+        }
+    }
+}
@@ -1,33 +1,13 @@
-use crate::OptionContractId;
 use crate::restful::{
     TradingClient, string_as_decimal, string_as_optional_decimal, string_as_optional_u64,
 };
+use crate::{OptionContractId, OptionSymbol};
 use chrono::NaiveDate;
 use reqwest::Method;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
-/// Option contract type.
-#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
-#[serde(rename_all = "lowercase")]
-#[non_exhaustive]
-pub enum OptionType {
-    /// Call option.
-    Call,
-    /// Put option.
-    Put,
-}
-
-/// Option exercise style.
-#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
-#[serde(rename_all = "lowercase")]
-#[non_exhaustive]
-pub enum OptionStyle {
-    /// American-style (exercisable any time before expiry).
-    American,
-    /// European-style (exercisable only at expiry).
-    European,
-}
+pub use crate::options::{OptionStyle, OptionType};
 
 /// Option contract status.
 #[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
@@ -128,6 +108,27 @@ pub struct OptionContract {
     pub deliverables: Option<Vec<OptionDeliverable>>,
 }
 
+impl OptionContract {
+    /// The contract's [`OptionSymbol`], parsed from
+    /// [`symbol`](Self::symbol).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidOptionSymbol`](crate::Error::InvalidOptionSymbol)
+    /// if the symbol is not a valid OCC symbol.
+    pub fn option_symbol(&self) -> crate::Result<OptionSymbol> {
+        self.symbol.parse()
+    }
+}
+
+impl TryFrom<&OptionContract> for OptionSymbol {
+    type Error = crate::Error;
+
+    fn try_from(contract: &OptionContract) -> crate::Result<Self> {
+        contract.option_symbol()
+    }
+}
+
 /// Response wrapper for paginated option contract listings.
 #[derive(Debug, Deserialize)]
 struct OptionContractsResponse {
@@ -311,9 +312,16 @@ impl TradingClient {
         }
     }
 
-    /// Get a specific option contract by symbol or ID.
-    pub async fn get_option_contract(&self, symbol_or_id: &str) -> crate::Result<OptionContract> {
-        let request = self.request(Method::GET, &format!("v2/options/contracts/{symbol_or_id}"))?;
+    /// Get a specific option contract by symbol or ID. Pass an
+    /// [`OptionSymbol`] to look up the contract it names.
+    pub async fn get_option_contract(
+        &self,
+        symbol_or_id: impl AsRef<str>,
+    ) -> crate::Result<OptionContract> {
+        let request = self.request(
+            Method::GET,
+            &format!("v2/options/contracts/{}", symbol_or_id.as_ref()),
+        )?;
         self.send_and_deserialize(request).await
     }
 }
@@ -408,5 +416,11 @@ mod tests {
             Decimal::from_str_exact("100").unwrap()
         );
         assert!(!deliverables[0].delayed_settlement);
+
+        let symbol = OptionSymbol::try_from(&contract).unwrap();
+        assert_eq!(symbol.root(), "AAPL");
+        assert_eq!(symbol.expiration(), contract.expiration_date);
+        assert_eq!(symbol.option_type(), contract.option_type);
+        assert_eq!(symbol.strike(), contract.strike_price);
     }
 }
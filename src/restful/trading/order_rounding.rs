@@ -0,0 +1,199 @@
+use rust_decimal::{Decimal, RoundingStrategy};
+
+use crate::AssetClass;
+use crate::restful::trading::assets::Asset;
+use crate::restful::trading::orders::Side;
+
+/// Equity quantities accept at most nine decimal places.
+const FRACTIONAL_SHARE_INCREMENT: Decimal = Decimal::from_parts(1, 0, 0, false, 9);
+/// Reg NMS Rule 612: quotes of $1.00 or more trade in whole cents.
+const PENNY: Decimal = Decimal::from_parts(1, 0, 0, false, 2);
+/// Reg NMS Rule 612: quotes below $1.00 may use four decimal places.
+const SUB_PENNY: Decimal = Decimal::from_parts(1, 0, 0, false, 4);
+/// Option premiums of $3.00 or more trade in nickels.
+const NICKEL: Decimal = Decimal::from_parts(5, 0, 0, false, 2);
+/// Premium at which option quotes move from pennies to nickels.
+const OPTION_NICKEL_THRESHOLD: Decimal = Decimal::from_parts(3, 0, 0, false, 0);
+
+/// Which way to round a price or quantity onto its increment.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum RoundingMode {
+    /// Toward negative infinity.
+    Down,
+    /// Toward positive infinity.
+    Up,
+    /// To the closest increment, halves away from zero.
+    Nearest,
+    /// Toward the less aggressive price for the order's side: down for
+    /// buys, up for sells. A passively rounded limit never pays more (or
+    /// receives less) than the unrounded one.
+    #[default]
+    Passive,
+    /// Toward the more aggressive price for the order's side: up for buys,
+    /// down for sells.
+    Aggressive,
+}
+
+impl RoundingMode {
+    fn strategy(self, side: Side) -> RoundingStrategy {
+        match (self, side) {
+            (Self::Down, _) | (Self::Passive, Side::Buy) | (Self::Aggressive, Side::Sell) => {
+                RoundingStrategy::ToNegativeInfinity
+            }
+            (Self::Up, _) | (Self::Passive, Side::Sell) | (Self::Aggressive, Side::Buy) => {
+                RoundingStrategy::ToPositiveInfinity
+            }
+            (Self::Nearest, _) => RoundingStrategy::MidpointAwayFromZero,
+        }
+    }
+}
+
+/// The price step for `asset` at `price`.
+///
+/// Crypto uses the asset's `price_increment`; options trade in cents below
+/// $3.00 and nickels from $3.00; equities follow Reg NMS tick rules — a
+/// cent at $1.00 and above, $0.0001 below.
+pub fn price_increment(asset: &Asset, price: Decimal) -> Decimal {
+    match (asset.class, asset.price_increment) {
+        (AssetClass::Crypto, Some(increment)) => increment,
+        (AssetClass::UsOption, _) if price.abs() < OPTION_NICKEL_THRESHOLD => PENNY,
+        (AssetClass::UsOption, _) => NICKEL,
+        _ if price.abs() < Decimal::ONE => SUB_PENNY,
+        _ => PENNY,
+    }
+}
+
+/// The quantity step for `asset`: the crypto `min_trade_increment`, one
+/// share for assets that are not fractionable, otherwise the finest
+/// fractional share Alpaca accepts.
+pub fn qty_increment(asset: &Asset) -> Decimal {
+    match asset.min_trade_increment {
+        Some(increment) if !increment.is_zero() => increment,
+        _ if asset.fractionable => FRACTIONAL_SHARE_INCREMENT,
+        _ => Decimal::ONE,
+    }
+}
+
+/// Round `price` onto `asset`'s price increment for an order on `side`.
+pub fn round_price(asset: &Asset, price: Decimal, side: Side, mode: RoundingMode) -> Decimal {
+    round_to_increment(price, price_increment(asset, price), mode.strategy(side))
+}
+
+/// Round `qty` onto `asset`'s quantity increment. Side-relative modes treat
+/// the quantity like a buy price: [`Passive`](RoundingMode::Passive) rounds
+/// down, never sizing the order above what was asked for.
+pub fn round_qty(asset: &Asset, qty: Decimal, mode: RoundingMode) -> Decimal {
+    round_to_increment(qty, qty_increment(asset), mode.strategy(Side::Buy))
+}
+
+/// Round `value` to a multiple of `increment` using `strategy`.
+/// A non-positive increment leaves the value unchanged.
+pub(crate) fn round_to_increment(
+    value: Decimal,
+    increment: Decimal,
+    strategy: RoundingStrategy,
+) -> Decimal {
+    if increment <= Decimal::ZERO {
+        return value;
+    }
+    ((value / increment).round_dp_with_strategy(0, strategy) * increment).normalize()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn asset(class: &str, fractionable: bool, increments: Option<(&str, &str)>) -> Asset {
+        let mut json = serde_json::json!({
+            "id": "asset-1",
+            "class": class,
+            "exchange": "NASDAQ",
+            "symbol": "AAPL",
+            "name": "Apple Inc.",
+            "status": "active",
+            "tradable": true,
+            "marginable": true,
+            "shortable": true,
+            "easy_to_borrow": true,
+            "fractionable": fractionable,
+            "attributes": []
+        });
+        if let Some((qty, price)) = increments {
+            json["min_order_size"] = qty.into();
+            json["min_trade_increment"] = qty.into();
+            json["price_increment"] = price.into();
+        }
+        serde_json::from_value(json).unwrap()
+    }
+
+    fn dec(s: &str) -> Decimal {
+        Decimal::from_str_exact(s).unwrap()
+    }
+
+    #[test]
+    fn equity_prices_follow_reg_nms_ticks() {
+        let aapl = asset("us_equity", true, None);
+        let price = dec("150.1234");
+        assert_eq!(
+            round_price(&aapl, price, Side::Buy, RoundingMode::Passive),
+            dec("150.12")
+        );
+        assert_eq!(
+            round_price(&aapl, price, Side::Sell, RoundingMode::Passive),
+            dec("150.13")
+        );
+        assert_eq!(
+            round_price(&aapl, price, Side::Buy, RoundingMode::Aggressive),
+            dec("150.13")
+        );
+        assert_eq!(
+            round_price(&aapl, dec("0.123456"), Side::Buy, RoundingMode::Nearest),
+            dec("0.1235")
+        );
+    }
+
+    #[test]
+    fn option_premiums_use_penny_then_nickel_ticks() {
+        let option = asset("us_option", false, None);
+        assert_eq!(
+            round_price(&option, dec("0.5678"), Side::Buy, RoundingMode::Passive),
+            dec("0.56")
+        );
+        assert_eq!(
+            round_price(&option, dec("2.999"), Side::Sell, RoundingMode::Passive),
+            dec("3")
+        );
+        assert_eq!(
+            round_price(&option, dec("3.12"), Side::Buy, RoundingMode::Passive),
+            dec("3.1")
+        );
+        assert_eq!(
+            round_price(&option, dec("3.12"), Side::Sell, RoundingMode::Passive),
+            dec("3.15")
+        );
+    }
+
+    #[test]
+    fn quantities_follow_asset_increments() {
+        let whole = asset("us_equity", false, None);
+        assert_eq!(
+            round_qty(&whole, dec("10.7"), RoundingMode::Passive),
+            dec("10")
+        );
+        let fractional = asset("us_equity", true, None);
+        assert_eq!(
+            round_qty(&fractional, dec("0.1234567891"), RoundingMode::Down),
+            dec("0.123456789")
+        );
+        let btc = asset("crypto", true, Some(("0.0001", "1")));
+        assert_eq!(
+            round_qty(&btc, dec("0.12345"), RoundingMode::Up),
+            dec("0.1235")
+        );
+        assert_eq!(
+            round_price(&btc, dec("64321.7"), Side::Buy, RoundingMode::Passive),
+            dec("64321")
+        );
+    }
+}
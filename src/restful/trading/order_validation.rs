@@ -0,0 +1,530 @@
+use rust_decimal::Decimal;
+
+use crate::AssetClass;
+use crate::crypto::position_symbol;
+use crate::restful::trading::assets::{Asset, BorrowStatus, Status};
+use crate::restful::trading::orders::{
+    OrderLeg, OrderType, Side, StopLoss, TakeProfit, TimeInForce,
+};
+
+/// One reason an order would be rejected, found before sending it.
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+#[non_exhaustive]
+pub enum OrderViolation {
+    /// The order's symbol is not the validated asset's symbol.
+    #[error("order symbol {order} does not match asset {asset}")]
+    AssetMismatch {
+        /// Symbol of the asset validated against.
+        asset: String,
+        /// Symbol on the order.
+        order: String,
+    },
+    /// The asset is inactive or not tradable.
+    #[error("asset is not tradable")]
+    NotTradable,
+    /// Neither a quantity nor, where allowed, a notional amount was set.
+    #[error("qty is required")]
+    MissingQty,
+    /// A quantity or price that must be positive is not.
+    #[error("{field} must be positive, got {value}")]
+    NotPositive {
+        /// Request field at fault.
+        field: &'static str,
+        /// The rejected value.
+        value: Decimal,
+    },
+    /// A fractional quantity on an asset that is not fractionable, or a
+    /// fractional contract count on an options order.
+    #[error("{field} must be a whole number, got {value}")]
+    FractionalQty {
+        /// Request field at fault.
+        field: &'static str,
+        /// The rejected value.
+        value: Decimal,
+    },
+    /// A quantity below the asset's `min_order_size`.
+    #[error("qty {qty} is below the minimum order size {min}")]
+    BelowMinOrderSize {
+        /// The rejected quantity.
+        qty: Decimal,
+        /// The asset's minimum order size.
+        min: Decimal,
+    },
+    /// A notional order on an asset that is not fractionable.
+    #[error("notional orders need a fractionable asset")]
+    NotionalNotFractionable,
+    /// A notional order that is not a market order.
+    #[error("notional orders must be market orders, got {0:?}")]
+    NotionalRequiresMarket(OrderType),
+    /// A fractional or notional equity order that is not a day order.
+    #[error("fractional orders must be day orders, got {0:?}")]
+    FractionalRequiresDay(TimeInForce),
+    /// A sell that would open or extend a short on an asset that cannot be
+    /// shorted.
+    #[error("asset is not shortable")]
+    NotShortable,
+    /// A sell that would open or extend a short on a hard-to-borrow asset.
+    #[error("asset is hard to borrow")]
+    HardToBorrow,
+    /// A time in force crypto orders do not accept (only `gtc` and `ioc`
+    /// are).
+    #[error("crypto orders accept gtc or ioc, got {0:?}")]
+    CryptoTimeInForce(TimeInForce),
+    /// Extended hours on something other than a day limit order.
+    #[error("extended hours needs a day limit order, got {order_type:?} {time_in_force:?}")]
+    ExtendedHoursRequiresDayLimit {
+        /// The order's type.
+        order_type: OrderType,
+        /// The order's time in force.
+        time_in_force: TimeInForce,
+    },
+    /// Take-profit, entry and stop-loss prices are not in profit-to-loss
+    /// order for the order's side, or a stop-loss limit is on the wrong
+    /// side of its stop.
+    #[error(
+        "bracket prices out of order: take profit {take_profit:?}, entry {entry:?}, stop loss {stop_loss:?}"
+    )]
+    InconsistentBracket {
+        /// Take-profit limit price.
+        take_profit: Option<Decimal>,
+        /// Entry limit or stop price, for non-market entries.
+        entry: Option<Decimal>,
+        /// Stop-loss stop price.
+        stop_loss: Option<Decimal>,
+    },
+    /// A multi-leg order without 2–4 legs.
+    #[error("multi-leg orders need 2 to 4 legs, got {0}")]
+    LegCount(usize),
+    /// A time in force options orders do not accept (only `day` is).
+    #[error("options orders must be day orders, got {0:?}")]
+    OptionsTimeInForce(TimeInForce),
+    /// Extended hours requested on an options order.
+    #[error("options orders cannot trade in extended hours")]
+    OptionsExtendedHours,
+}
+
+/// Pre-trade checks for orders on one asset.
+///
+/// Build it from a cached [`Asset`] (from
+/// [`TradingClient::get_asset`](crate::TradingClient::get_asset)) and pass
+/// it to `validate` on an order builder, or attach it with
+/// `validate_against` so `execute` refuses to send an order that fails.
+/// Every problem found is reported in one
+/// [`Error::InvalidOrder`](crate::Error::InvalidOrder).
+///
+/// Whether a sell opens a short depends on the current position, so the
+/// shortability checks only run once it is supplied with
+/// [`position_qty`](Self::position_qty).
+#[derive(Clone, Copy, Debug)]
+#[must_use]
+pub struct OrderValidator<'a> {
+    asset: &'a Asset,
+    position_qty: Option<Decimal>,
+}
+
+impl<'a> OrderValidator<'a> {
+    /// Validate orders against `asset`.
+    pub fn new(asset: &'a Asset) -> Self {
+        Self {
+            asset,
+            position_qty: None,
+        }
+    }
+
+    /// The signed quantity currently held (negative when short), enabling
+    /// the short-sale checks.
+    pub fn position_qty(mut self, qty: Decimal) -> Self {
+        self.position_qty = Some(qty);
+        self
+    }
+
+    /// The asset orders are validated against.
+    pub fn asset(&self) -> &'a Asset {
+        self.asset
+    }
+
+    pub(crate) fn check_order(&self, order: &OrderFields<'_>) -> Vec<OrderViolation> {
+        let asset = self.asset;
+        let crypto = asset.class == AssetClass::Crypto;
+        let mut violations = Vec::new();
+
+        if position_symbol(&asset.symbol) != position_symbol(order.symbol) {
+            violations.push(OrderViolation::AssetMismatch {
+                asset: asset.symbol.clone(),
+                order: order.symbol.to_string(),
+            });
+        }
+        if !asset.tradable || asset.status != Status::Active {
+            violations.push(OrderViolation::NotTradable);
+        }
+        if order.qty.is_none() && order.notional.is_none() {
+            violations.push(OrderViolation::MissingQty);
+        }
+        positive(&mut violations, "qty", order.qty);
+        positive(&mut violations, "notional", order.notional);
+        positive(&mut violations, "limit_price", order.limit_price);
+        positive(&mut violations, "stop_price", order.stop_price);
+
+        let fractional = order.qty.is_some_and(|qty| !qty.fract().is_zero());
+        if let Some(qty) = order.qty.filter(|_| fractional && !asset.fractionable) {
+            violations.push(OrderViolation::FractionalQty {
+                field: "qty",
+                value: qty,
+            });
+        }
+        below_min_size(&mut violations, asset, order.qty);
+        if order.notional.is_some() {
+            if !asset.fractionable {
+                violations.push(OrderViolation::NotionalNotFractionable);
+            }
+            if order.order_type != OrderType::Market {
+                violations.push(OrderViolation::NotionalRequiresMarket(order.order_type));
+            }
+        }
+        if (fractional || order.notional.is_some())
+            && asset.class == AssetClass::UsEquity
+            && order.time_in_force != TimeInForce::Day
+        {
+            violations.push(OrderViolation::FractionalRequiresDay(order.time_in_force));
+        }
+
+        if crypto && !matches!(order.time_in_force, TimeInForce::Gtc | TimeInForce::Ioc) {
+            violations.push(OrderViolation::CryptoTimeInForce(order.time_in_force));
+        }
+        if order.extended_hours
+            && (order.order_type != OrderType::Limit || order.time_in_force != TimeInForce::Day)
+        {
+            violations.push(OrderViolation::ExtendedHoursRequiresDayLimit {
+                order_type: order.order_type,
+                time_in_force: order.time_in_force,
+            });
+        }
+
+        if self.opens_short(order) {
+            if !asset.shortable {
+                violations.push(OrderViolation::NotShortable);
+            } else if !asset.easy_to_borrow
+                || asset.borrow_status == Some(BorrowStatus::HardToBorrow)
+            {
+                violations.push(OrderViolation::HardToBorrow);
+            }
+        }
+
+        if let Some(violation) = check_bracket(order) {
+            violations.push(violation);
+        }
+        violations
+    }
+
+    pub(crate) fn check_replacement(
+        &self,
+        qty: Option<Decimal>,
+        limit_price: Option<Decimal>,
+        stop_price: Option<Decimal>,
+        trail: Option<Decimal>,
+        time_in_force: Option<TimeInForce>,
+    ) -> Vec<OrderViolation> {
+        let asset = self.asset;
+        let mut violations = Vec::new();
+        if !asset.tradable || asset.status != Status::Active {
+            violations.push(OrderViolation::NotTradable);
+        }
+        positive(&mut violations, "qty", qty);
+        positive(&mut violations, "limit_price", limit_price);
+        positive(&mut violations, "stop_price", stop_price);
+        positive(&mut violations, "trail", trail);
+        if let Some(qty) = qty.filter(|qty| !qty.fract().is_zero() && !asset.fractionable) {
+            violations.push(OrderViolation::FractionalQty {
+                field: "qty",
+                value: qty,
+            });
+        }
+        below_min_size(&mut violations, asset, qty);
+        if let Some(tif) = time_in_force.filter(|tif| {
+            asset.class == AssetClass::Crypto && !matches!(tif, TimeInForce::Gtc | TimeInForce::Ioc)
+        }) {
+            violations.push(OrderViolation::CryptoTimeInForce(tif));
+        }
+        violations
+    }
+
+    /// Whether the sell would leave the position short (or shorter).
+    fn opens_short(&self, order: &OrderFields<'_>) -> bool {
+        let (Some(position), Some(qty)) = (self.position_qty, order.qty) else {
+            return false;
+        };
+        order.side == Side::Sell && qty > position.max(Decimal::ZERO)
+    }
+}
+
+/// The parts of a new single-leg order the validator looks at.
+pub(crate) struct OrderFields<'r> {
+    pub(crate) symbol: &'r str,
+    pub(crate) side: Side,
+    pub(crate) order_type: OrderType,
+    pub(crate) time_in_force: TimeInForce,
+    pub(crate) qty: Option<Decimal>,
+    pub(crate) notional: Option<Decimal>,
+    pub(crate) limit_price: Option<Decimal>,
+    pub(crate) stop_price: Option<Decimal>,
+    pub(crate) extended_hours: bool,
+    pub(crate) take_profit: Option<&'r TakeProfit>,
+    pub(crate) stop_loss: Option<&'r StopLoss>,
+}
+
+pub(crate) fn check_multi_leg(
+    legs: &[OrderLeg],
+    qty: Option<Decimal>,
+    time_in_force: TimeInForce,
+    extended_hours: bool,
+) -> Vec<OrderViolation> {
+    let mut violations = Vec::new();
+    if !(2..=4).contains(&legs.len()) {
+        violations.push(OrderViolation::LegCount(legs.len()));
+    }
+    match qty {
+        None => violations.push(OrderViolation::MissingQty),
+        Some(qty) => {
+            positive(&mut violations, "qty", Some(qty));
+            whole(&mut violations, "qty", qty);
+        }
+    }
+    for leg in legs {
+        positive(&mut violations, "ratio_qty", Some(leg.ratio_qty));
+        whole(&mut violations, "ratio_qty", leg.ratio_qty);
+    }
+    if time_in_force != TimeInForce::Day {
+        violations.push(OrderViolation::OptionsTimeInForce(time_in_force));
+    }
+    if extended_hours {
+        violations.push(OrderViolation::OptionsExtendedHours);
+    }
+    violations
+}
+
+pub(crate) fn into_result(violations: Vec<OrderViolation>) -> crate::Result<()> {
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(crate::Error::InvalidOrder(violations))
+    }
+}
+
+fn positive(violations: &mut Vec<OrderViolation>, field: &'static str, value: Option<Decimal>) {
+    if let Some(value) = value.filter(|value| *value <= Decimal::ZERO) {
+        violations.push(OrderViolation::NotPositive { field, value });
+    }
+}
+
+fn below_min_size(violations: &mut Vec<OrderViolation>, asset: &Asset, qty: Option<Decimal>) {
+    if let (Some(qty), Some(min)) = (qty, asset.min_order_size)
+        && qty > Decimal::ZERO
+        && qty < min
+    {
+        violations.push(OrderViolation::BelowMinOrderSize { qty, min });
+    }
+}
+
+fn whole(violations: &mut Vec<OrderViolation>, field: &'static str, value: Decimal) {
+    if !value.fract().is_zero() {
+        violations.push(OrderViolation::FractionalQty { field, value });
+    }
+}
+
+/// Take-profit, entry and stop must run from most to least favourable: for
+/// a buy, `take_profit > entry > stop`, for a sell the reverse. A
+/// stop-limit loss must have its limit at or beyond its stop.
+fn check_bracket(order: &OrderFields<'_>) -> Option<OrderViolation> {
+    if order.take_profit.is_none() && order.stop_loss.is_none() {
+        return None;
+    }
+    let take_profit = order.take_profit.map(|tp| tp.limit_price);
+    let stop_loss = order.stop_loss.map(|sl| sl.stop_price);
+    let entry = match order.order_type {
+        OrderType::Limit | OrderType::StopLimit => order.limit_price,
+        OrderType::Stop => order.stop_price,
+        _ => None,
+    };
+    // Prices from most to least favourable, flipped to ascending for sells.
+    let mut ladder: Vec<Decimal> = [take_profit, entry, stop_loss]
+        .into_iter()
+        .flatten()
+        .collect();
+    if order.side == Side::Sell {
+        ladder.reverse();
+    }
+    let ordered = ladder.windows(2).all(|pair| pair[0] > pair[1]);
+    let stop_limit_ok = order.stop_loss.is_none_or(|sl| {
+        sl.limit_price.is_none_or(|limit| match order.side {
+            Side::Buy => limit <= sl.stop_price,
+            Side::Sell => limit >= sl.stop_price,
+        })
+    });
+    (!ordered || !stop_limit_ok).then_some(OrderViolation::InconsistentBracket {
+        take_profit,
+        entry,
+        stop_loss,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn asset(class: &str, fractionable: bool, shortable: bool) -> Asset {
+        serde_json::from_value(serde_json::json!({
+            "id": "asset-1",
+            "class": class,
+            "exchange": "NASDAQ",
+            "symbol": "AAPL",
+            "name": "Apple Inc.",
+            "status": "active",
+            "tradable": true,
+            "marginable": true,
+            "shortable": shortable,
+            "easy_to_borrow": shortable,
+            "fractionable": fractionable,
+            "attributes": []
+        }))
+        .unwrap()
+    }
+
+    fn dec(s: &str) -> Decimal {
+        Decimal::from_str_exact(s).unwrap()
+    }
+
+    fn market_buy(qty: &str) -> OrderFields<'static> {
+        OrderFields {
+            symbol: "AAPL",
+            side: Side::Buy,
+            order_type: OrderType::Market,
+            time_in_force: TimeInForce::Day,
+            qty: Some(dec(qty)),
+            notional: None,
+            limit_price: None,
+            stop_price: None,
+            extended_hours: false,
+            take_profit: None,
+            stop_loss: None,
+        }
+    }
+
+    #[test]
+    fn equity_order_rules() {
+        let whole_only = asset("us_equity", false, false);
+        let validator = OrderValidator::new(&whole_only).position_qty(Decimal::ZERO);
+        assert!(validator.check_order(&market_buy("10")).is_empty());
+
+        let mut order = market_buy("1.5");
+        order.side = Side::Sell;
+        order.time_in_force = TimeInForce::Gtc;
+        order.extended_hours = true;
+        assert_eq!(
+            validator.check_order(&order),
+            vec![
+                OrderViolation::FractionalQty {
+                    field: "qty",
+                    value: dec("1.5")
+                },
+                OrderViolation::FractionalRequiresDay(TimeInForce::Gtc),
+                OrderViolation::ExtendedHoursRequiresDayLimit {
+                    order_type: OrderType::Market,
+                    time_in_force: TimeInForce::Gtc
+                },
+                OrderViolation::NotShortable,
+            ]
+        );
+
+        let mut notional = market_buy("1");
+        notional.qty = None;
+        notional.notional = Some(dec("100"));
+        notional.order_type = OrderType::Limit;
+        notional.limit_price = Some(dec("150"));
+        assert_eq!(
+            validator.check_order(&notional),
+            vec![
+                OrderViolation::NotionalNotFractionable,
+                OrderViolation::NotionalRequiresMarket(OrderType::Limit),
+            ]
+        );
+    }
+
+    #[test]
+    fn orders_need_qty_or_notional() {
+        let aapl = asset("us_equity", true, true);
+        let mut order = market_buy("1");
+        order.qty = None;
+        assert_eq!(
+            OrderValidator::new(&aapl).check_order(&order),
+            vec![OrderViolation::MissingQty]
+        );
+    }
+
+    #[test]
+    fn crypto_orders_need_gtc_or_ioc() {
+        let mut btc = asset("crypto", true, false);
+        btc.symbol = "BTC/USD".to_string();
+        let mut order = market_buy("0.01");
+        order.symbol = "BTCUSD";
+        assert_eq!(
+            OrderValidator::new(&btc).check_order(&order),
+            vec![OrderViolation::CryptoTimeInForce(TimeInForce::Day)]
+        );
+        order.time_in_force = TimeInForce::Gtc;
+        assert!(OrderValidator::new(&btc).check_order(&order).is_empty());
+        btc.min_order_size = Some(dec("0.1"));
+        assert_eq!(
+            OrderValidator::new(&btc).check_order(&order),
+            vec![OrderViolation::BelowMinOrderSize {
+                qty: dec("0.01"),
+                min: dec("0.1")
+            }]
+        );
+    }
+
+    #[test]
+    fn bracket_prices_must_be_ordered_for_the_side() {
+        let tp = TakeProfit::new(dec("110"));
+        let sl = StopLoss::new(dec("95"));
+        let mut order = market_buy("1");
+        order.order_type = OrderType::Limit;
+        order.limit_price = Some(dec("100"));
+        order.take_profit = Some(&tp);
+        order.stop_loss = Some(&sl);
+        assert_eq!(check_bracket(&order), None);
+
+        order.side = Side::Sell;
+        assert!(matches!(
+            check_bracket(&order),
+            Some(OrderViolation::InconsistentBracket { .. })
+        ));
+
+        let sl = StopLoss::with_limit(dec("95"), dec("96"));
+        order.side = Side::Buy;
+        order.stop_loss = Some(&sl);
+        assert!(check_bracket(&order).is_some());
+    }
+
+    #[test]
+    fn multi_leg_rules() {
+        let legs = vec![OrderLeg::new(
+            "AAPL250620C00150000",
+            Side::Buy,
+            dec("1.5"),
+            crate::orders::PositionIntent::BuyToOpen,
+        )];
+        assert_eq!(
+            check_multi_leg(&legs, None, TimeInForce::Gtc, true),
+            vec![
+                OrderViolation::LegCount(1),
+                OrderViolation::MissingQty,
+                OrderViolation::FractionalQty {
+                    field: "ratio_qty",
+                    value: dec("1.5")
+                },
+                OrderViolation::OptionsTimeInForce(TimeInForce::Gtc),
+                OrderViolation::OptionsExtendedHours,
+            ]
+        );
+    }
+}
@@ -1,10 +1,17 @@
+use crate::restful::trading::assets::Asset;
+use crate::restful::trading::order_rounding::{self, RoundingMode};
+use crate::restful::trading::order_validation::{self, OrderFields, OrderValidator};
 use crate::restful::{SortDirection, TradingClient};
 use crate::{AssetClass, ClientOrderId, OrderId};
 use chrono::{DateTime, Utc};
 use reqwest::Method;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
-use std::marker::PhantomData;
+use std::{
+    marker::PhantomData,
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
 
 pub use crate::orders::{
     Order, OrderClass, OrderStatus, OrderType, PositionIntent, Side, TimeInForce,
@@ -14,12 +21,12 @@ pub use crate::orders::{
 /// ([`qty`][CreateOrderRequest::qty] or
 /// [`notional`][CreateOrderRequest::notional]) chosen yet.
 /// `execute()` is unavailable in this state.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub enum Draft {}
 
 /// Marker for a [`CreateOrderRequest`] that has had a size chosen and
 /// is ready to submit. `execute()` is only available in this state.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub enum Ready {}
 
 fn infer_order_class(has_take_profit: bool, has_stop_loss: bool) -> Option<OrderClass> {
@@ -166,6 +173,8 @@ pub struct CreateMultiLegOrderRequest<'a> {
     extended_hours: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     client_order_id: Option<ClientOrderId>,
+    #[serde(skip)]
+    validate: bool,
 }
 
 impl CreateMultiLegOrderRequest<'_> {
@@ -194,8 +203,47 @@ impl CreateMultiLegOrderRequest<'_> {
         self
     }
 
+    /// Make [`execute`](Self::execute) run [`validate`](Self::validate)
+    /// first and refuse to send an order that fails.
+    pub fn validated(mut self) -> Self {
+        self.validate = true;
+        self
+    }
+
+    /// Check the order against Alpaca's multi-leg rules (2–4 legs, whole
+    /// contract quantities, day orders only, no extended hours) without
+    /// sending it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidOrder`](crate::Error::InvalidOrder) listing
+    /// every rule the order breaks.
+    pub fn validate(&self) -> crate::Result<()> {
+        order_validation::into_result(order_validation::check_multi_leg(
+            &self.legs,
+            self.qty,
+            self.time_in_force,
+            self.extended_hours.unwrap_or(false),
+        ))
+    }
+
+    pub(crate) fn legs(&self) -> &[OrderLeg] {
+        &self.legs
+    }
+
+    pub(crate) fn quantity(&self) -> Option<Decimal> {
+        self.qty
+    }
+
+    pub(crate) fn net_limit_price(&self) -> Decimal {
+        self.limit_price
+    }
+
     /// Submit the multi-leg order.
     pub async fn execute(self) -> crate::Result<Order> {
+        if self.validate {
+            self.validate()?;
+        }
         let request = self.client.request(Method::POST, "v2/orders")?.json(&self);
         self.client.send_and_deserialize(request).await
     }
@@ -218,7 +266,7 @@ impl CreateMultiLegOrderRequest<'_> {
 /// [`client_order_id`][Self::client_order_id],
 /// [`take_profit`][Self::take_profit], [`stop_loss`][Self::stop_loss],
 /// [`order_class`][Self::order_class]) are genuinely optional.
-#[derive(Debug, Serialize)]
+#[derive(Clone, Debug, Serialize)]
 #[must_use]
 pub struct CreateOrderRequest<'a, S = Draft> {
     #[serde(skip)]
@@ -251,6 +299,10 @@ pub struct CreateOrderRequest<'a, S = Draft> {
     #[serde(skip_serializing_if = "Option::is_none")]
     stop_loss: Option<StopLoss>,
     #[serde(skip)]
+    validator: Option<OrderValidator<'a>>,
+    #[serde(skip)]
+    rounding: Option<&'a Asset>,
+    #[serde(skip)]
     _marker: PhantomData<S>,
 }
 
@@ -277,6 +329,8 @@ impl<'a, S> CreateOrderRequest<'a, S> {
             order_class: self.order_class,
             take_profit: self.take_profit,
             stop_loss: self.stop_loss,
+            validator: self.validator,
+            rounding: self.rounding,
             _marker: PhantomData,
         }
     }
@@ -339,18 +393,282 @@ impl<'a, S> CreateOrderRequest<'a, S> {
         self.order_class = Some(class);
         self
     }
+
+    /// Make `execute()` check the order with `validator` first and refuse
+    /// to send it if any rule is broken. See
+    /// [`validate`](CreateOrderRequest::validate).
+    pub fn validate_against(mut self, validator: OrderValidator<'a>) -> Self {
+        self.validator = Some(validator);
+        self
+    }
+
+    /// Round prices and sizes onto `asset`'s increments when the order is
+    /// sent, before any validation.
+    ///
+    /// Quantities round down and notionals down to the cent, so the order
+    /// is never larger than asked for. Limit prices, including the
+    /// take-profit and any stop-loss limit, round passively for the side
+    /// that will trade them (see [`RoundingMode::Passive`]); stop and trail
+    /// prices round to the nearest tick.
+    pub fn round_to_asset(mut self, asset: &'a Asset) -> Self {
+        self.rounding = Some(asset);
+        self
+    }
 }
 
 impl CreateOrderRequest<'_, Ready> {
-    /// Submit the order.
-    pub async fn execute(mut self) -> crate::Result<Order> {
+    /// Check the order against `validator`'s asset and Alpaca's order rules
+    /// without sending it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidOrder`](crate::Error::InvalidOrder) listing
+    /// every rule the order breaks.
+    pub fn validate(&self, validator: &OrderValidator<'_>) -> crate::Result<()> {
+        order_validation::into_result(validator.check_order(&self.fields()))
+    }
+
+    /// Borrowed view of the fields pre-trade checks look at.
+    pub(crate) fn fields(&self) -> OrderFields<'_> {
+        OrderFields {
+            symbol: &self.symbol,
+            side: self.side,
+            order_type: self.order_type,
+            time_in_force: self.time_in_force,
+            qty: self.qty,
+            notional: self.notional,
+            limit_price: self.limit_price,
+            stop_price: self.stop_price,
+            extended_hours: self.extended_hours.unwrap_or(false),
+            take_profit: self.take_profit.as_ref(),
+            stop_loss: self.stop_loss.as_ref(),
+        }
+    }
+
+    /// Infer the order class, apply [`round_to_asset`](Self::round_to_asset)
+    /// and run the attached validator, if any.
+    pub(crate) fn prepare(&mut self) -> crate::Result<()> {
         if self.order_class.is_none() {
             self.order_class =
                 infer_order_class(self.take_profit.is_some(), self.stop_loss.is_some());
         }
+        if let Some(asset) = self.rounding {
+            self.round(asset);
+        }
+        match &self.validator {
+            Some(validator) => self.validate(validator),
+            None => Ok(()),
+        }
+    }
+
+    fn round(&mut self, asset: &Asset) {
+        let side = self.side;
+        let exit = match side {
+            Side::Buy => Side::Sell,
+            Side::Sell => Side::Buy,
+        };
+        let price = |price: Decimal, side: Side, mode: RoundingMode| {
+            order_rounding::round_price(asset, price, side, mode)
+        };
+        self.qty = self
+            .qty
+            .map(|qty| order_rounding::round_qty(asset, qty, RoundingMode::Down));
+        self.notional = self.notional.map(|notional| {
+            order_rounding::round_to_increment(
+                notional,
+                Decimal::new(1, 2),
+                rust_decimal::RoundingStrategy::ToZero,
+            )
+        });
+        self.limit_price = self
+            .limit_price
+            .map(|limit| price(limit, side, RoundingMode::Passive));
+        self.stop_price = self
+            .stop_price
+            .map(|stop| price(stop, side, RoundingMode::Nearest));
+        self.trail_price = self
+            .trail_price
+            .map(|trail| price(trail, side, RoundingMode::Nearest));
+        if let Some(take_profit) = &mut self.take_profit {
+            take_profit.limit_price = price(take_profit.limit_price, exit, RoundingMode::Passive);
+        }
+        if let Some(stop_loss) = &mut self.stop_loss {
+            stop_loss.stop_price = price(stop_loss.stop_price, exit, RoundingMode::Nearest);
+            stop_loss.limit_price = stop_loss
+                .limit_price
+                .map(|limit| price(limit, exit, RoundingMode::Passive));
+        }
+    }
+
+    /// Submit the order.
+    pub async fn execute(mut self) -> crate::Result<Order> {
+        self.prepare()?;
         let request = self.client.request(Method::POST, "v2/orders")?.json(&self);
         self.client.send_and_deserialize(request).await
     }
+
+    /// Submit the order at most once, retrying ambiguous failures with the
+    /// default [`IdempotencyPolicy`].
+    ///
+    /// See [`execute_idempotent_with`](Self::execute_idempotent_with).
+    pub async fn execute_idempotent(self) -> crate::Result<Order> {
+        self.execute_idempotent_with(IdempotencyPolicy::default())
+            .await
+    }
+
+    /// Submit the order at most once, keyed on its client order ID.
+    ///
+    /// A [`client_order_id`](Self::client_order_id) is generated if none was
+    /// set. When a submission fails in a way that leaves its outcome unknown
+    /// — a transport error such as a timeout or connection reset, an
+    /// unreadable response, or an HTTP 5xx — the order is looked up with
+    /// [`TradingClient::get_order_by_client_id`] before anything is
+    /// resubmitted: if Alpaca has it, that order is returned; only a 404
+    /// leads to another submission. Should a resubmission be rejected
+    /// because the client order ID is already taken, an earlier attempt
+    /// landed late, and it is looked up and returned instead. Other
+    /// failures that are not ambiguous (e.g. a 422 for insufficient buying
+    /// power) are returned straight away.
+    ///
+    /// Returns the last error once `policy.max_attempts` requests (submits
+    /// and lookups together) have been spent.
+    pub async fn execute_idempotent_with(
+        mut self,
+        policy: IdempotencyPolicy,
+    ) -> crate::Result<Order> {
+        self.prepare()?;
+        let client_order_id = self
+            .client_order_id
+            .get_or_insert_with(generate_client_order_id)
+            .clone();
+        let client = self.client;
+        let max_attempts = policy.max_attempts.max(1);
+        let mut attempts = 0;
+        let mut resubmitting = false;
+        loop {
+            attempts += 1;
+            let request = client.request(Method::POST, "v2/orders")?.json(&self);
+            let error = match policy.run(client.send_and_deserialize(request)).await {
+                Ok(order) => return Ok(order),
+                // An earlier submission landed after its lookup missed it.
+                Err(error) if resubmitting && is_duplicate_client_order_id(&error) => error,
+                Err(error) if !is_ambiguous(&error) => return Err(error),
+                Err(error) => error,
+            };
+            resubmitting = true;
+            // The submit may have landed; find out before sending it again.
+            loop {
+                if attempts >= max_attempts {
+                    return Err(error);
+                }
+                tokio::time::sleep(policy.backoff).await;
+                attempts += 1;
+                let lookup = client.get_order_by_client_id(&client_order_id);
+                match policy.run(lookup).await {
+                    Ok(order) => return Ok(order),
+                    Err(crate::Error::ApiError { status: 404, .. }) => break,
+                    Err(lookup_error) if is_ambiguous(&lookup_error) => {}
+                    Err(lookup_error) => return Err(lookup_error),
+                }
+            }
+            if attempts >= max_attempts {
+                return Err(error);
+            }
+            tokio::time::sleep(policy.backoff).await;
+        }
+    }
+}
+
+/// Retry settings for [`CreateOrderRequest::execute_idempotent_with`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[must_use]
+#[non_exhaustive]
+pub struct IdempotencyPolicy {
+    /// Total requests (submits and lookups) to make before giving up.
+    /// Defaults to 5.
+    pub max_attempts: u32,
+    /// Pause before each lookup or resubmission. Defaults to 500ms.
+    pub backoff: Duration,
+    /// Deadline for each individual request; one that runs over counts as
+    /// an ambiguous failure. Defaults to 10s. `None` waits indefinitely.
+    pub request_timeout: Option<Duration>,
+}
+
+impl Default for IdempotencyPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            backoff: Duration::from_millis(500),
+            request_timeout: Some(Duration::from_secs(10)),
+        }
+    }
+}
+
+impl IdempotencyPolicy {
+    /// Start from the defaults.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set [`max_attempts`](Self::max_attempts).
+    pub fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    /// Set [`backoff`](Self::backoff).
+    pub fn backoff(mut self, backoff: Duration) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Set [`request_timeout`](Self::request_timeout).
+    pub fn request_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.request_timeout = timeout;
+        self
+    }
+
+    async fn run<T>(&self, request: impl Future<Output = crate::Result<T>>) -> crate::Result<T> {
+        match self.request_timeout {
+            Some(timeout) => tokio::time::timeout(timeout, request)
+                .await
+                .unwrap_or(Err(crate::Error::RequestTimeout(timeout))),
+            None => request.await,
+        }
+    }
+}
+
+/// Whether a failed request might still have taken effect server-side.
+fn is_ambiguous(error: &crate::Error) -> bool {
+    match error {
+        crate::Error::ReqwestSend(_)
+        | crate::Error::ReqwestDeserialize(_)
+        | crate::Error::RequestTimeout(_) => true,
+        crate::Error::ApiError { status, .. } => *status >= 500,
+        _ => false,
+    }
+}
+
+/// Whether Alpaca rejected a submission because its client order ID is
+/// already in use.
+fn is_duplicate_client_order_id(error: &crate::Error) -> bool {
+    matches!(
+        error,
+        crate::Error::ApiError { status: 422, body }
+            if body.to_ascii_lowercase().contains("client_order_id must be unique")
+    )
+}
+
+/// A client order ID unique to this process run: wall-clock nanoseconds,
+/// process ID and a counter, well inside Alpaca's 128-character limit.
+pub(crate) fn generate_client_order_id() -> ClientOrderId {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let nanos = Utc::now().timestamp_nanos_opt().unwrap_or_default();
+    ClientOrderId::new(format!(
+        "oxa-{nanos:x}-{:x}-{:x}",
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    ))
 }
 
 /// Builder for listing orders with filters.
@@ -507,9 +825,11 @@ pub struct ReplaceOrderRequest<'a> {
     trail: Option<Decimal>,
     #[serde(skip_serializing_if = "Option::is_none")]
     client_order_id: Option<ClientOrderId>,
+    #[serde(skip)]
+    validator: Option<OrderValidator<'a>>,
 }
 
-impl ReplaceOrderRequest<'_> {
+impl<'a> ReplaceOrderRequest<'a> {
     /// Set the new quantity.
     pub fn qty(mut self, qty: Decimal) -> Self {
         self.qty = Some(qty);
@@ -546,8 +866,58 @@ impl ReplaceOrderRequest<'_> {
         self
     }
 
+    /// Make [`execute`](Self::execute) check the replacement with
+    /// `validator` first and refuse to send it if any rule is broken.
+    pub fn validate_against(mut self, validator: OrderValidator<'a>) -> Self {
+        self.validator = Some(validator);
+        self
+    }
+
+    /// Check the replacement's quantity, prices and time in force against
+    /// `validator`'s asset without sending it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidOrder`](crate::Error::InvalidOrder) listing
+    /// every rule the replacement breaks.
+    pub fn validate(&self, validator: &OrderValidator<'_>) -> crate::Result<()> {
+        order_validation::into_result(validator.check_replacement(
+            self.qty,
+            self.limit_price,
+            self.stop_price,
+            self.trail,
+            self.time_in_force,
+        ))
+    }
+
+    /// The order being replaced.
+    pub(crate) fn order_id(&self) -> &OrderId {
+        &self.order_id
+    }
+
+    /// `original` as it will stand once this replacement is applied, for
+    /// pre-trade checks.
+    pub(crate) fn fields<'r>(&self, original: &'r Order) -> OrderFields<'r> {
+        OrderFields {
+            symbol: &original.symbol,
+            side: original.side,
+            order_type: original.order_type,
+            time_in_force: self.time_in_force.unwrap_or(original.time_in_force),
+            qty: self.qty.or(original.qty),
+            notional: original.notional,
+            limit_price: self.limit_price.or(original.limit_price),
+            stop_price: self.stop_price.or(original.stop_price),
+            extended_hours: original.extended_hours,
+            take_profit: None,
+            stop_loss: None,
+        }
+    }
+
     /// Submit the replacement.
     pub async fn execute(self) -> crate::Result<Order> {
+        if let Some(validator) = &self.validator {
+            self.validate(validator)?;
+        }
         let order_id = &self.order_id;
         let path = format!("v2/orders/{order_id}");
         let request = self.client.request(Method::PATCH, &path)?.json(&self);
@@ -562,13 +932,13 @@ impl TradingClient {
     /// on top.
     fn new_create_order_request(
         &self,
-        symbol: &str,
+        symbol: impl AsRef<str>,
         side: Side,
         order_type: OrderType,
     ) -> CreateOrderRequest<'_, Draft> {
         CreateOrderRequest {
             client: self,
-            symbol: symbol.to_string(),
+            symbol: symbol.as_ref().to_string(),
             side,
             order_type,
             time_in_force: TimeInForce::Day,
@@ -583,6 +953,8 @@ impl TradingClient {
             order_class: None,
             take_profit: None,
             stop_loss: None,
+            validator: None,
+            rounding: None,
             _marker: PhantomData,
         }
     }
@@ -596,7 +968,7 @@ impl TradingClient {
     ///     .qty(dec!(10))
     ///     .execute().await?;
     /// ```
-    pub fn market_order(&self, symbol: &str, side: Side) -> CreateOrderRequest<'_> {
+    pub fn market_order(&self, symbol: impl AsRef<str>, side: Side) -> CreateOrderRequest<'_> {
         self.new_create_order_request(symbol, side, OrderType::Market)
     }
 
@@ -612,7 +984,7 @@ impl TradingClient {
     /// ```
     pub fn limit_order(
         &self,
-        symbol: &str,
+        symbol: impl AsRef<str>,
         side: Side,
         limit_price: Decimal,
     ) -> CreateOrderRequest<'_> {
@@ -624,7 +996,7 @@ impl TradingClient {
     /// Submit a stop order that triggers at `stop_price`.
     pub fn stop_order(
         &self,
-        symbol: &str,
+        symbol: impl AsRef<str>,
         side: Side,
         stop_price: Decimal,
     ) -> CreateOrderRequest<'_> {
@@ -637,7 +1009,7 @@ impl TradingClient {
     /// rests as a limit order at `limit_price`.
     pub fn stop_limit_order(
         &self,
-        symbol: &str,
+        symbol: impl AsRef<str>,
         side: Side,
         stop_price: Decimal,
         limit_price: Decimal,
@@ -655,7 +1027,7 @@ impl TradingClient {
     /// [`trailing_stop_order_by_percent`][Self::trailing_stop_order_by_percent].
     pub fn trailing_stop_order_by_price(
         &self,
-        symbol: &str,
+        symbol: impl AsRef<str>,
         side: Side,
         trail_price: Decimal,
     ) -> CreateOrderRequest<'_> {
@@ -671,7 +1043,7 @@ impl TradingClient {
     /// [`trailing_stop_order_by_price`][Self::trailing_stop_order_by_price].
     pub fn trailing_stop_order_by_percent(
         &self,
-        symbol: &str,
+        symbol: impl AsRef<str>,
         side: Side,
         trail_percent: Decimal,
     ) -> CreateOrderRequest<'_> {
@@ -703,6 +1075,7 @@ impl TradingClient {
             qty: None,
             extended_hours: None,
             client_order_id: None,
+            validate: false,
         }
     }
 
@@ -794,6 +1167,7 @@ impl TradingClient {
             time_in_force: None,
             trail: None,
             client_order_id: None,
+            validator: None,
         }
     }
 }
@@ -1246,4 +1620,309 @@ mod tests {
             assert_eq!(parsed, variant);
         }
     }
+
+    fn order_json(client_order_id: &str) -> serde_json::Value {
+        serde_json::json!({
+            "id": "order-1",
+            "client_order_id": client_order_id,
+            "created_at": "2024-01-02T15:30:00Z",
+            "updated_at": "2024-01-02T15:30:00Z",
+            "submitted_at": "2024-01-02T15:30:00Z",
+            "filled_at": null,
+            "expired_at": null,
+            "canceled_at": null,
+            "asset_id": "asset-1",
+            "symbol": "AAPL",
+            "qty": "1",
+            "filled_qty": "0",
+            "type": "market",
+            "side": "buy",
+            "time_in_force": "day",
+            "status": "accepted",
+            "extended_hours": false,
+            "order_class": "",
+            "legs": null
+        })
+    }
+
+    fn fast_retries() -> IdempotencyPolicy {
+        IdempotencyPolicy::new().backoff(Duration::ZERO)
+    }
+
+    /// A 5xx on submit must not resubmit when the lookup shows the order
+    /// already landed.
+    #[tokio::test]
+    #[serial]
+    async fn idempotent_submit_returns_order_that_landed_despite_error() {
+        use wiremock::matchers::{method, path, query_param};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v2/orders"))
+            .respond_with(ResponseTemplate::new(504))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v2/orders:by_client_order_id"))
+            .and(query_param("client_order_id", "once"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(order_json("once")))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = paper_client().with_base_url(&server.uri());
+        let order = client
+            .market_order("AAPL", Side::Buy)
+            .qty(dec("1"))
+            .client_order_id("once")
+            .execute_idempotent_with(fast_retries())
+            .await
+            .unwrap();
+        assert_eq!(order.client_order_id.as_str(), "once");
+    }
+
+    /// When the lookup 404s the order is resubmitted under the same
+    /// generated client order ID.
+    #[tokio::test]
+    #[serial]
+    async fn idempotent_submit_resubmits_after_lookup_miss() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v2/orders"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/v2/orders"))
+            .respond_with(|request: &wiremock::Request| {
+                let body: serde_json::Value = request.body_json().unwrap();
+                let id = body["client_order_id"].as_str().unwrap();
+                ResponseTemplate::new(200).set_body_json(order_json(id))
+            })
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v2/orders:by_client_order_id"))
+            .respond_with(ResponseTemplate::new(404))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = paper_client().with_base_url(&server.uri());
+        let order = client
+            .market_order("AAPL", Side::Buy)
+            .qty(dec("1"))
+            .execute_idempotent_with(fast_retries())
+            .await
+            .unwrap();
+
+        let submitted: Vec<serde_json::Value> = server
+            .received_requests()
+            .await
+            .unwrap()
+            .iter()
+            .filter(|request| request.method.as_str() == "POST")
+            .map(|request| request.body_json().unwrap())
+            .collect();
+        assert_eq!(submitted.len(), 2);
+        assert_eq!(
+            submitted[0]["client_order_id"],
+            submitted[1]["client_order_id"]
+        );
+        assert_eq!(
+            submitted[0]["client_order_id"].as_str(),
+            Some(order.client_order_id.as_str())
+        );
+    }
+
+    /// The backoff is paid before the resubmission as well as before the
+    /// lookup.
+    #[tokio::test]
+    #[serial]
+    async fn idempotent_submit_backs_off_before_resubmitting() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v2/orders"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/v2/orders"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(order_json("paced")))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v2/orders:by_client_order_id"))
+            .respond_with(ResponseTemplate::new(404))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = paper_client().with_base_url(&server.uri());
+        let backoff = Duration::from_millis(200);
+        let started = std::time::Instant::now();
+        client
+            .market_order("AAPL", Side::Buy)
+            .qty(dec("1"))
+            .client_order_id("paced")
+            .execute_idempotent_with(IdempotencyPolicy::new().backoff(backoff))
+            .await
+            .unwrap();
+        // One pause before the lookup and one before the resubmission.
+        assert!(started.elapsed() >= backoff * 2);
+    }
+
+    /// A resubmission rejected as a duplicate means an earlier submit
+    /// landed late; the existing order is looked up and returned.
+    #[tokio::test]
+    #[serial]
+    async fn idempotent_submit_returns_late_landing_order_on_duplicate_id() {
+        use wiremock::matchers::{method, path, query_param};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v2/orders"))
+            .respond_with(ResponseTemplate::new(502))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/v2/orders"))
+            .respond_with(
+                ResponseTemplate::new(422).set_body_string(
+                    r#"{"code":40010001,"message":"client_order_id must be unique"}"#,
+                ),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v2/orders:by_client_order_id"))
+            .and(query_param("client_order_id", "late"))
+            .respond_with(ResponseTemplate::new(404))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v2/orders:by_client_order_id"))
+            .and(query_param("client_order_id", "late"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(order_json("late")))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = paper_client().with_base_url(&server.uri());
+        let order = client
+            .market_order("AAPL", Side::Buy)
+            .qty(dec("1"))
+            .client_order_id("late")
+            .execute_idempotent_with(fast_retries())
+            .await
+            .unwrap();
+        assert_eq!(order.client_order_id.as_str(), "late");
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn idempotent_submit_returns_rejections_without_lookup() {
+        use wiremock::matchers::method;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(422).set_body_string("insufficient buying power"))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = paper_client().with_base_url(&server.uri());
+        let result = client
+            .market_order("AAPL", Side::Buy)
+            .qty(dec("1"))
+            .execute_idempotent_with(fast_retries())
+            .await;
+        assert!(matches!(
+            result,
+            Err(crate::Error::ApiError { status: 422, .. })
+        ));
+    }
+
+    /// An attached validator stops `execute` before anything is sent.
+    #[tokio::test]
+    #[serial]
+    async fn validate_against_rejects_before_sending() {
+        let asset: crate::restful::trading::assets::Asset =
+            serde_json::from_value(serde_json::json!({
+                "id": "asset-1",
+                "class": "us_equity",
+                "exchange": "NYSE",
+                "symbol": "BRK.A",
+                "name": "Berkshire Hathaway",
+                "status": "active",
+                "tradable": true,
+                "marginable": true,
+                "shortable": true,
+                "easy_to_borrow": true,
+                "fractionable": false,
+                "attributes": []
+            }))
+            .unwrap();
+        let client = paper_client();
+        let result = client
+            .market_order("BRK.A", Side::Buy)
+            .qty(dec("0.5"))
+            .validate_against(OrderValidator::new(&asset))
+            .execute()
+            .await;
+        match result {
+            Err(crate::Error::InvalidOrder(violations)) => assert_eq!(violations.len(), 1),
+            other => panic!("expected Err(InvalidOrder), got {other:?}"),
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn round_to_asset_rounds_size_and_prices_on_send() {
+        let asset: crate::restful::trading::assets::Asset =
+            serde_json::from_value(serde_json::json!({
+                "id": "asset-1",
+                "class": "us_equity",
+                "exchange": "NASDAQ",
+                "symbol": "AAPL",
+                "name": "Apple Inc.",
+                "status": "active",
+                "tradable": true,
+                "marginable": true,
+                "shortable": true,
+                "easy_to_borrow": true,
+                "fractionable": false,
+                "attributes": []
+            }))
+            .unwrap();
+        let client = paper_client();
+        let mut request = client
+            .limit_order("AAPL", Side::Buy, dec("150.129"))
+            .qty(dec("10.6"))
+            .take_profit(TakeProfit::new(dec("160.001")))
+            .stop_loss(StopLoss::with_limit(dec("140.004"), dec("139.999")))
+            .round_to_asset(&asset);
+        request.prepare().unwrap();
+        let value = serde_json::to_value(&request).unwrap();
+        assert_eq!(value["qty"], "10");
+        assert_eq!(value["limit_price"], "150.12");
+        assert_eq!(value["take_profit"]["limit_price"], "160.01");
+        assert_eq!(value["stop_loss"]["stop_price"], "140");
+        assert_eq!(value["stop_loss"]["limit_price"], "140");
+    }
 }
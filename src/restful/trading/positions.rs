@@ -1,5 +1,5 @@
 use crate::restful::{TradingClient, string_as_decimal, trading::assets::Exchange};
-use crate::{AssetClass, AssetId};
+use crate::{AssetClass, AssetId, CryptoPair};
 use reqwest::Method;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
@@ -89,6 +89,18 @@ pub struct Position {
     pub change_today: Decimal,
 }
 
+impl Position {
+    /// The crypto pair held, parsed from the compact symbol (`BTCUSD`)
+    /// positions report, or `None` for equities and options.
+    #[must_use]
+    pub fn crypto_pair(&self) -> Option<CryptoPair> {
+        match self.asset_class {
+            AssetClass::Crypto => CryptoPair::parse(&self.symbol).ok(),
+            _ => None,
+        }
+    }
+}
+
 /// Builder for closing a specific position.
 #[derive(Debug, Serialize)]
 #[must_use]
@@ -164,8 +176,11 @@ impl TradingClient {
     }
 
     /// Get a specific open position by symbol or asset ID.
-    pub async fn get_position(&self, symbol_or_id: &str) -> crate::Result<Position> {
-        let request = self.request(Method::GET, &format!("v2/positions/{symbol_or_id}"))?;
+    pub async fn get_position(&self, symbol_or_id: impl AsRef<str>) -> crate::Result<Position> {
+        let request = self.request(
+            Method::GET,
+            &format!("v2/positions/{}", symbol_or_id.as_ref()),
+        )?;
         self.send_and_deserialize(request).await
     }
 
@@ -180,10 +195,10 @@ impl TradingClient {
     /// // Close 50% of position
     /// let order = client.close_position("AAPL").percentage(dec!(50)).execute().await?;
     /// ```
-    pub fn close_position(&self, symbol_or_id: &str) -> ClosePositionRequest<'_> {
+    pub fn close_position(&self, symbol_or_id: impl AsRef<str>) -> ClosePositionRequest<'_> {
         ClosePositionRequest {
             client: self,
-            symbol_or_id: symbol_or_id.to_string(),
+            symbol_or_id: symbol_or_id.as_ref().to_string(),
             qty: None,
             percentage: None,
         }
@@ -204,19 +219,28 @@ impl TradingClient {
     }
 
     /// Exercise an options position.
-    pub async fn exercise_option(&self, symbol_or_contract_id: &str) -> crate::Result<()> {
+    pub async fn exercise_option(
+        &self,
+        symbol_or_contract_id: impl AsRef<str>,
+    ) -> crate::Result<()> {
         let request = self.request(
             Method::POST,
-            &format!("v2/positions/{symbol_or_contract_id}/exercise"),
+            &format!("v2/positions/{}/exercise", symbol_or_contract_id.as_ref()),
         )?;
         self.send_no_body(request).await
     }
 
     /// Submit a do-not-exercise instruction for an options position.
-    pub async fn do_not_exercise(&self, symbol_or_contract_id: &str) -> crate::Result<()> {
+    pub async fn do_not_exercise(
+        &self,
+        symbol_or_contract_id: impl AsRef<str>,
+    ) -> crate::Result<()> {
         let request = self.request(
             Method::POST,
-            &format!("v2/positions/{symbol_or_contract_id}/do-not-exercise"),
+            &format!(
+                "v2/positions/{}/do-not-exercise",
+                symbol_or_contract_id.as_ref()
+            ),
         )?;
         self.send_no_body(request).await
     }
@@ -0,0 +1,1058 @@
+use rust_decimal::Decimal;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{
+        Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+};
+
+use crate::crypto::position_symbol;
+use crate::restful::trading::order_validation::OrderFields;
+use crate::restful::trading::orders::{
+    CreateMultiLegOrderRequest, CreateOrderRequest, Order, OrderLeg, OrderStatusFilter, OrderType,
+    Ready, ReplaceOrderRequest, Side,
+};
+use crate::restful::trading::positions::{Position, PositionSide};
+use crate::restful::{SortDirection, TradingClient};
+use crate::{AssetClass, OptionSymbol, OrderId};
+
+/// A limit a [`RiskGuard`] found an order would breach.
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+#[non_exhaustive]
+pub enum RiskViolation {
+    /// The daily-loss kill switch has tripped; no orders are sent until it
+    /// is [reset](RiskGuard::reset_kill_switch).
+    #[error("kill switch tripped")]
+    KillSwitchTripped,
+    /// The day's loss reached the configured maximum. Trips the kill switch.
+    #[error("daily loss {loss} reached the limit {max}")]
+    DailyLoss {
+        /// `last_equity - equity`.
+        loss: Decimal,
+        /// Configured maximum.
+        max: Decimal,
+    },
+    /// The symbol is denied, or missing from the allow list.
+    #[error("symbol {0} is not allowed")]
+    SymbolNotAllowed(String),
+    /// Too many orders are already open.
+    #[error("{open} orders open, limit {max}")]
+    TooManyOpenOrders {
+        /// Orders currently open.
+        open: usize,
+        /// Configured maximum.
+        max: usize,
+    },
+    /// The order's value is above the per-order limit.
+    #[error("order notional {notional} exceeds {max}")]
+    OrderNotional {
+        /// Estimated order value.
+        notional: Decimal,
+        /// Configured maximum.
+        max: Decimal,
+    },
+    /// The order would take the position past the per-symbol limit.
+    #[error("position in {symbol} would reach {qty}, limit {max}")]
+    PositionSize {
+        /// Symbol of the position.
+        symbol: String,
+        /// Absolute quantity after the order fills.
+        qty: Decimal,
+        /// Configured maximum.
+        max: Decimal,
+    },
+    /// The order would take gross exposure past the account limit.
+    #[error("gross exposure would reach {exposure}, limit {max}")]
+    GrossExposure {
+        /// Sum of absolute position values after the order fills.
+        exposure: Decimal,
+        /// Configured maximum.
+        max: Decimal,
+    },
+    /// A value-based limit is configured but the order has no price to
+    /// value it with. Supply one with [`RiskGuard::mark`].
+    #[error("no reference price for {0}")]
+    Unpriced(String),
+}
+
+/// What a [`RiskGuard`] does when its daily-loss kill switch trips.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum KillSwitchAction {
+    /// Only refuse further orders.
+    #[default]
+    RejectOrders,
+    /// Also cancel every open order.
+    CancelOrders,
+    /// Also cancel every open order and close every position.
+    Flatten,
+}
+
+/// Limits enforced by a [`RiskGuard`]. Every limit is off until set.
+#[derive(Clone, Debug, Default)]
+#[must_use]
+pub struct RiskLimits {
+    max_order_notional: Option<Decimal>,
+    max_position_qty: Option<Decimal>,
+    max_gross_exposure: Option<Decimal>,
+    max_open_orders: Option<usize>,
+    allowed_symbols: Option<HashSet<String>>,
+    denied_symbols: HashSet<String>,
+    max_daily_loss: Option<Decimal>,
+    kill_switch_action: KillSwitchAction,
+}
+
+impl RiskLimits {
+    /// No limits.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Largest value a single order may have.
+    pub fn max_order_notional(mut self, max: Decimal) -> Self {
+        self.max_order_notional = Some(max);
+        self
+    }
+
+    /// Largest absolute quantity any one position may reach.
+    pub fn max_position_qty(mut self, max: Decimal) -> Self {
+        self.max_position_qty = Some(max);
+        self
+    }
+
+    /// Largest sum of absolute position market values.
+    pub fn max_gross_exposure(mut self, max: Decimal) -> Self {
+        self.max_gross_exposure = Some(max);
+        self
+    }
+
+    /// Most orders that may be open at once, counting the new one.
+    pub fn max_open_orders(mut self, max: usize) -> Self {
+        self.max_open_orders = Some(max);
+        self
+    }
+
+    /// Only allow orders in these symbols. Crypto pairs match in either
+    /// the slashed or the compact form.
+    pub fn allow_symbols(mut self, symbols: &[&str]) -> Self {
+        self.allowed_symbols = Some(
+            symbols
+                .iter()
+                .map(|s| position_symbol(s).into_owned())
+                .collect(),
+        );
+        self
+    }
+
+    /// Never allow orders in these symbols, even if allow-listed. Crypto
+    /// pairs match in either the slashed or the compact form.
+    pub fn deny_symbols(mut self, symbols: &[&str]) -> Self {
+        self.denied_symbols
+            .extend(symbols.iter().map(|s| position_symbol(s).into_owned()));
+        self
+    }
+
+    /// Trip the kill switch once the day's loss (`last_equity - equity`)
+    /// reaches `max`.
+    pub fn max_daily_loss(mut self, max: Decimal) -> Self {
+        self.max_daily_loss = Some(max);
+        self
+    }
+
+    /// What to do besides rejecting orders when the kill switch trips.
+    pub fn kill_switch_action(mut self, action: KillSwitchAction) -> Self {
+        self.kill_switch_action = action;
+        self
+    }
+
+    fn values_orders(&self) -> bool {
+        self.max_order_notional.is_some() || self.max_gross_exposure.is_some()
+    }
+}
+
+/// Pre-trade risk checks in front of [`TradingClient`] order submission.
+///
+/// Build orders with [`client`](Self::client) as usual and hand them to
+/// [`submit`](Self::submit), [`submit_replacement`](Self::submit_replacement)
+/// or [`submit_multi_leg`](Self::submit_multi_leg) instead of calling
+/// `execute`. Before anything is sent the guard fetches the account,
+/// positions and open orders and checks the order, as it will be sent
+/// (rounded and validated), against its [`RiskLimits`]; a breach is
+/// returned as [`Error::RiskLimit`](crate::Error::RiskLimit) and the order
+/// is dropped. Symbols are compared with crypto pairs in compact form, so
+/// `BTC/USD` and `BTCUSD` name the same position and list entry.
+///
+/// Orders are valued at their limit (or stop) price; market orders use the
+/// latest [`mark`](Self::mark), falling back to the position's current
+/// price. Option contracts count for 100 shares each. Multi-leg orders are valued at their net limit price times the
+/// 100-share contract multiplier, and that premium is what counts towards
+/// gross exposure.
+///
+/// When the day's loss reaches the configured maximum the kill switch
+/// trips: this and every later order is rejected until
+/// [`reset_kill_switch`](Self::reset_kill_switch), and the configured
+/// [`KillSwitchAction`] runs. An action that fails stays pending and is
+/// retried by every later check and
+/// [`check_kill_switch`](Self::check_kill_switch) until it succeeds.
+/// Single-order breaches only reject the order in hand.
+///
+/// On a live client behind a [`LiveSafety`](crate::restful::LiveSafety)
+/// interlock the cancel and flatten actions are bulk operations: they are
+/// refused unless allowed there and armed with
+/// [`arm_bulk_operation`](TradingClient::arm_bulk_operation) (or confirmed
+/// by its callback). A refused action stays pending, so arming the bulk
+/// operation lets the next check carry it out.
+#[derive(Debug)]
+pub struct RiskGuard {
+    client: TradingClient,
+    limits: RiskLimits,
+    tripped: AtomicBool,
+    action_pending: AtomicBool,
+    marks: Mutex<HashMap<String, Decimal>>,
+}
+
+impl RiskGuard {
+    /// Guard orders sent through `client` with `limits`.
+    pub fn new(client: TradingClient, limits: RiskLimits) -> Self {
+        Self {
+            client,
+            limits,
+            tripped: AtomicBool::new(false),
+            action_pending: AtomicBool::new(false),
+            marks: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The guarded client, for building orders and for read-only calls.
+    pub fn client(&self) -> &TradingClient {
+        &self.client
+    }
+
+    /// The limits in force.
+    pub fn limits(&self) -> &RiskLimits {
+        &self.limits
+    }
+
+    /// Record the latest price of `symbol`, used to value market orders.
+    pub fn mark(&self, symbol: &str, price: Decimal) {
+        self.marks
+            .lock()
+            .expect("marks lock poisoned")
+            .insert(position_symbol(symbol).into_owned(), price);
+    }
+
+    /// Whether the kill switch has tripped.
+    pub fn is_tripped(&self) -> bool {
+        self.tripped.load(Ordering::SeqCst)
+    }
+
+    /// Whether the kill switch has tripped but its [`KillSwitchAction`]
+    /// has not yet gone through.
+    pub fn is_action_pending(&self) -> bool {
+        self.action_pending.load(Ordering::SeqCst)
+    }
+
+    /// Re-enable order submission after the kill switch tripped. A
+    /// [`KillSwitchAction`] still pending is dropped.
+    pub fn reset_kill_switch(&self) {
+        self.action_pending.store(false, Ordering::SeqCst);
+        self.tripped.store(false, Ordering::SeqCst);
+    }
+
+    /// Check `request` against the limits and send it if it passes.
+    pub async fn submit(&self, request: CreateOrderRequest<'_, Ready>) -> crate::Result<Order> {
+        self.check(&request).await?;
+        request.execute().await
+    }
+
+    /// Check `request` against the limits without sending it.
+    pub async fn check(&self, request: &CreateOrderRequest<'_, Ready>) -> crate::Result<()> {
+        let mut prepared = request.clone();
+        prepared.prepare()?;
+        let (positions, open_orders) = self.snapshot().await?;
+        let fields = prepared.fields();
+        self.evaluate_open_orders(open_orders)
+            .and_then(|()| self.evaluate(&fields, self.mark_for(fields.symbol), &positions))
+            .map_err(crate::Error::RiskLimit)
+    }
+
+    /// Check `request` against the limits and send it if it passes.
+    ///
+    /// The replaced order is fetched and checked as it will stand after the
+    /// replacement; only the change in quantity counts towards position and
+    /// exposure limits. A replacement does not add an open order, so the
+    /// open order limit does not apply.
+    pub async fn submit_replacement(
+        &self,
+        request: ReplaceOrderRequest<'_>,
+    ) -> crate::Result<Order> {
+        self.check_replacement(&request).await?;
+        request.execute().await
+    }
+
+    /// Check `request` against the limits without sending it.
+    pub async fn check_replacement(&self, request: &ReplaceOrderRequest<'_>) -> crate::Result<()> {
+        let original = self.client.get_order(request.order_id()).await?;
+        let (positions, _) = self.snapshot().await?;
+        let fields = request.fields(&original);
+        let replaced = original.qty.unwrap_or_default();
+        self.evaluate_replacing(&fields, self.mark_for(fields.symbol), &positions, replaced)
+            .map_err(crate::Error::RiskLimit)
+    }
+
+    /// Check `request` against the limits and send it if it passes.
+    pub async fn submit_multi_leg(
+        &self,
+        request: CreateMultiLegOrderRequest<'_>,
+    ) -> crate::Result<Order> {
+        self.check_multi_leg(&request).await?;
+        request.execute().await
+    }
+
+    /// Check `request` against the limits without sending it.
+    pub async fn check_multi_leg(
+        &self,
+        request: &CreateMultiLegOrderRequest<'_>,
+    ) -> crate::Result<()> {
+        let (positions, open_orders) = self.snapshot().await?;
+        self.evaluate_open_orders(open_orders)
+            .and_then(|()| {
+                self.evaluate_multi_leg(
+                    request.legs(),
+                    request.quantity(),
+                    request.net_limit_price(),
+                    &positions,
+                )
+            })
+            .map_err(crate::Error::RiskLimit)
+    }
+
+    /// Fetch the account and trip the kill switch if the day's loss has
+    /// reached the limit. Call it periodically to act on losses between
+    /// orders; it also retries a pending [`KillSwitchAction`].
+    pub async fn check_kill_switch(&self) -> crate::Result<()> {
+        let account = self.client.get_account().await?;
+        self.check_daily_loss(account.equity, account.last_equity)
+            .await
+    }
+
+    /// Refuse everything once tripped, then fetch the positions and count
+    /// the open orders after checking the day's loss.
+    async fn snapshot(&self) -> crate::Result<(Vec<Position>, usize)> {
+        if self.is_tripped() {
+            self.run_pending_action().await?;
+            return Err(crate::Error::RiskLimit(RiskViolation::KillSwitchTripped));
+        }
+        let (account, positions, open_orders) = futures::try_join!(
+            self.client.get_account(),
+            self.client.list_positions(),
+            count_open_orders(&self.client),
+        )?;
+        self.check_daily_loss(account.equity, account.last_equity)
+            .await?;
+        Ok((positions, open_orders))
+    }
+
+    fn mark_for(&self, symbol: &str) -> Option<Decimal> {
+        self.marks
+            .lock()
+            .expect("marks lock poisoned")
+            .get(position_symbol(symbol).as_ref())
+            .copied()
+    }
+
+    async fn check_daily_loss(&self, equity: Decimal, last_equity: Decimal) -> crate::Result<()> {
+        let tripped = self.trip_on_loss(equity, last_equity);
+        self.run_pending_action().await?;
+        tripped.map_err(crate::Error::RiskLimit)
+    }
+
+    /// Run the [`KillSwitchAction`] if one is pending, leaving it pending
+    /// if it fails.
+    async fn run_pending_action(&self) -> crate::Result<()> {
+        if !self.action_pending.swap(false, Ordering::SeqCst) {
+            return Ok(());
+        }
+        let result = match self.limits.kill_switch_action {
+            KillSwitchAction::RejectOrders => Ok(()),
+            KillSwitchAction::CancelOrders => self.client.cancel_all_orders().await.map(drop),
+            KillSwitchAction::Flatten => self
+                .client
+                .close_all_positions()
+                .cancel_orders(true)
+                .execute()
+                .await
+                .map(drop),
+        };
+        if result.is_err() {
+            self.action_pending.store(true, Ordering::SeqCst);
+        }
+        result
+    }
+
+    /// Trip the kill switch if the loss limit is reached. Only the call
+    /// that trips it reports [`RiskViolation::DailyLoss`] and queues the
+    /// kill switch action.
+    fn trip_on_loss(&self, equity: Decimal, last_equity: Decimal) -> Result<(), RiskViolation> {
+        let Some(max) = self.limits.max_daily_loss else {
+            return Ok(());
+        };
+        let loss = last_equity - equity;
+        if loss < max {
+            return Ok(());
+        }
+        if self.tripped.swap(true, Ordering::SeqCst) {
+            return Err(RiskViolation::KillSwitchTripped);
+        }
+        if self.limits.kill_switch_action != KillSwitchAction::RejectOrders {
+            self.action_pending.store(true, Ordering::SeqCst);
+        }
+        Err(RiskViolation::DailyLoss { loss, max })
+    }
+
+    fn evaluate_open_orders(&self, open_orders: usize) -> Result<(), RiskViolation> {
+        match self.limits.max_open_orders {
+            Some(max) if open_orders >= max => Err(RiskViolation::TooManyOpenOrders {
+                open: open_orders,
+                max,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    fn evaluate_symbol(&self, symbol: &str) -> Result<(), RiskViolation> {
+        let limits = &self.limits;
+        let key = position_symbol(symbol);
+        let allowed = limits
+            .allowed_symbols
+            .as_ref()
+            .is_none_or(|allowed| allowed.contains(key.as_ref()));
+        if !allowed || limits.denied_symbols.contains(key.as_ref()) {
+            return Err(RiskViolation::SymbolNotAllowed(symbol.to_string()));
+        }
+        Ok(())
+    }
+
+    fn evaluate_position_qty(
+        &self,
+        symbol: &str,
+        held: Decimal,
+        change: Decimal,
+    ) -> Result<(), RiskViolation> {
+        let Some(max) = self.limits.max_position_qty else {
+            return Ok(());
+        };
+        let after = (held + change).abs();
+        if after > max && after > held.abs() {
+            return Err(RiskViolation::PositionSize {
+                symbol: symbol.to_string(),
+                qty: after,
+                max,
+            });
+        }
+        Ok(())
+    }
+
+    fn evaluate(
+        &self,
+        order: &OrderFields<'_>,
+        mark: Option<Decimal>,
+        positions: &[Position],
+    ) -> Result<(), RiskViolation> {
+        self.evaluate_replacing(order, mark, positions, Decimal::ZERO)
+    }
+
+    /// [`evaluate`](Self::evaluate) an order that replaces one for
+    /// `replaced` units, so only the difference adds to the position.
+    fn evaluate_replacing(
+        &self,
+        order: &OrderFields<'_>,
+        mark: Option<Decimal>,
+        positions: &[Position],
+        replaced: Decimal,
+    ) -> Result<(), RiskViolation> {
+        let limits = &self.limits;
+        let symbol = order.symbol;
+        self.evaluate_symbol(symbol)?;
+
+        let position = find_position(positions, symbol);
+        let held = position.map_or(Decimal::ZERO, signed_qty);
+        let multiplier = if OptionSymbol::parse(symbol).is_ok()
+            || position.is_some_and(|p| p.asset_class == AssetClass::UsOption)
+        {
+            OPTION_CONTRACT_MULTIPLIER
+        } else {
+            Decimal::ONE
+        };
+        let price = match order.order_type {
+            OrderType::Limit | OrderType::StopLimit => order.limit_price,
+            OrderType::Stop => order.stop_price,
+            _ => None,
+        }
+        .or(mark)
+        .or(position.map(|p| p.current_price));
+        // Value of one unit: a share, a coin or a contract.
+        let unit_value = price.map(|price| price * multiplier);
+        let qty = match (order.qty, order.notional, unit_value) {
+            (Some(qty), _, _) => Some(qty),
+            (None, Some(notional), Some(value)) if !value.is_zero() => Some(notional / value),
+            _ => None,
+        };
+        let signed = |qty: Decimal| match order.side {
+            Side::Buy => qty,
+            Side::Sell => -qty,
+        };
+
+        if let Some(qty) = qty {
+            self.evaluate_position_qty(symbol, held, signed(qty - replaced))?;
+        }
+
+        if !limits.values_orders() {
+            return Ok(());
+        }
+        let notional = match (order.notional, qty, unit_value) {
+            (Some(notional), _, _) => notional,
+            (None, Some(qty), Some(value)) => qty * value,
+            _ => return Err(RiskViolation::Unpriced(symbol.to_string())),
+        };
+        if let Some(max) = limits.max_order_notional
+            && notional > max
+        {
+            return Err(RiskViolation::OrderNotional { notional, max });
+        }
+        if let (Some(max), Some(qty), Some(value)) = (limits.max_gross_exposure, qty, unit_value) {
+            let gross: Decimal = positions.iter().map(|p| p.market_value.abs()).sum();
+            let before = position.map_or(Decimal::ZERO, |p| p.market_value.abs());
+            let after = ((held + signed(qty - replaced)) * value).abs();
+            let exposure = gross - before + after;
+            if exposure > max && after > before {
+                return Err(RiskViolation::GrossExposure { exposure, max });
+            }
+        }
+        Ok(())
+    }
+
+    fn evaluate_multi_leg(
+        &self,
+        legs: &[OrderLeg],
+        qty: Option<Decimal>,
+        limit_price: Decimal,
+        positions: &[Position],
+    ) -> Result<(), RiskViolation> {
+        let limits = &self.limits;
+        for leg in legs {
+            self.evaluate_symbol(&leg.symbol)?;
+        }
+        let Some(qty) = qty else {
+            return match legs.first() {
+                Some(leg) if limits.values_orders() => {
+                    Err(RiskViolation::Unpriced(leg.symbol.clone()))
+                }
+                _ => Ok(()),
+            };
+        };
+        for leg in legs {
+            let held = find_position(positions, &leg.symbol).map_or(Decimal::ZERO, signed_qty);
+            let contracts = qty * leg.ratio_qty;
+            let change = match leg.side {
+                Side::Buy => contracts,
+                Side::Sell => -contracts,
+            };
+            self.evaluate_position_qty(&leg.symbol, held, change)?;
+        }
+
+        let notional = (limit_price * qty * OPTION_CONTRACT_MULTIPLIER).abs();
+        if let Some(max) = limits.max_order_notional
+            && notional > max
+        {
+            return Err(RiskViolation::OrderNotional { notional, max });
+        }
+        if let Some(max) = limits.max_gross_exposure {
+            let gross: Decimal = positions.iter().map(|p| p.market_value.abs()).sum();
+            let exposure = gross + notional;
+            if exposure > max {
+                return Err(RiskViolation::GrossExposure { exposure, max });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Shares per standard option contract.
+const OPTION_CONTRACT_MULTIPLIER: Decimal = Decimal::ONE_HUNDRED;
+
+/// Largest page the list-orders endpoint returns.
+const ORDER_PAGE_SIZE: usize = 500;
+
+/// Count every open order, following the `after_order_id` cursor past
+/// full pages.
+async fn count_open_orders(client: &TradingClient) -> crate::Result<usize> {
+    let mut count = 0;
+    let mut cursor: Option<OrderId> = None;
+    loop {
+        let request = client
+            .list_orders()
+            .status(OrderStatusFilter::Open)
+            .direction(SortDirection::Asc)
+            .limit(ORDER_PAGE_SIZE);
+        let request = match cursor.take() {
+            Some(id) => request.after_order_id(id),
+            None => request,
+        };
+        let page = request.execute().await?;
+        count += page.len();
+        match page.last() {
+            Some(last) if page.len() >= ORDER_PAGE_SIZE => cursor = Some(last.id.clone()),
+            _ => return Ok(count),
+        }
+    }
+}
+
+fn find_position<'p>(positions: &'p [Position], symbol: &str) -> Option<&'p Position> {
+    let key = position_symbol(symbol);
+    positions.iter().find(|p| position_symbol(&p.symbol) == key)
+}
+
+/// Quantity held, negative when short.
+fn signed_qty(position: &Position) -> Decimal {
+    match position.side {
+        PositionSide::Short => -position.qty.abs(),
+        _ => position.qty.abs(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AccountType;
+    use crate::orders::PositionIntent;
+    use crate::restful::trading::assets::Asset;
+    use crate::restful::trading::orders::TimeInForce;
+    use serial_test::serial;
+    use wiremock::matchers::{method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn dec(s: &str) -> Decimal {
+        Decimal::from_str_exact(s).unwrap()
+    }
+
+    fn guard(limits: RiskLimits) -> RiskGuard {
+        let api_key = crate::ApiKey::new("test_key_id", "test_secret_key");
+        let client = TradingClient::new_with_credentials(AccountType::Paper, api_key).unwrap();
+        RiskGuard::new(client, limits)
+    }
+
+    fn mock_guard(server: &MockServer, limits: RiskLimits) -> RiskGuard {
+        let api_key = crate::ApiKey::new("test_key_id", "test_secret_key");
+        let client = TradingClient::new_with_credentials(AccountType::Paper, api_key)
+            .unwrap()
+            .with_base_url(&server.uri());
+        RiskGuard::new(client, limits)
+    }
+
+    fn account_json(equity: &str, last_equity: &str) -> serde_json::Value {
+        serde_json::json!({
+            "id": "account-1",
+            "account_number": "PA3L2HG811OS",
+            "status": "ACTIVE",
+            "crypto_status": "ACTIVE",
+            "options_approved_level": 2,
+            "options_trading_level": 2,
+            "currency": "USD",
+            "buying_power": "200000",
+            "regt_buying_power": "200000",
+            "effective_buying_power": "200000",
+            "non_marginable_buying_power": "100000",
+            "options_buying_power": "100000",
+            "bod_dtbp": "0",
+            "cash": "100000",
+            "accrued_fees": "0",
+            "portfolio_value": equity,
+            "trading_blocked": false,
+            "transfers_blocked": false,
+            "account_blocked": false,
+            "created_at": "2021-12-22T01:09:20.724911Z",
+            "trade_suspended_by_user": false,
+            "multiplier": "2",
+            "shorting_enabled": true,
+            "equity": equity,
+            "last_equity": last_equity,
+            "long_market_value": "0",
+            "short_market_value": "0",
+            "position_market_value": "0",
+            "initial_margin": "0",
+            "maintenance_margin": "0",
+            "last_maintenance_margin": "0",
+            "sma": "0",
+            "balance_asof": "2024-01-02",
+            "crypto_tier": 1,
+            "intraday_adjustments": "0",
+            "pending_reg_taf_fees": "0"
+        })
+    }
+
+    fn open_order_json(id: usize) -> serde_json::Value {
+        serde_json::json!({
+            "id": format!("order-{id}"),
+            "client_order_id": format!("client-{id}"),
+            "created_at": "2024-01-02T15:30:00Z",
+            "asset_id": "asset-1",
+            "symbol": "AAPL",
+            "qty": "1",
+            "filled_qty": "0",
+            "type": "market",
+            "side": "buy",
+            "time_in_force": "day",
+            "status": "new",
+            "extended_hours": false,
+            "legs": null
+        })
+    }
+
+    fn whole_share_asset() -> Asset {
+        serde_json::from_value(serde_json::json!({
+            "id": "asset-1",
+            "class": "us_equity",
+            "exchange": "NASDAQ",
+            "symbol": "AAPL",
+            "name": "Apple Inc.",
+            "status": "active",
+            "tradable": true,
+            "marginable": true,
+            "shortable": true,
+            "easy_to_borrow": true,
+            "fractionable": false,
+            "attributes": []
+        }))
+        .unwrap()
+    }
+
+    fn buy<'a>(symbol: &'a str, qty: &str, limit: Option<&str>) -> OrderFields<'a> {
+        OrderFields {
+            symbol,
+            side: Side::Buy,
+            order_type: if limit.is_some() {
+                OrderType::Limit
+            } else {
+                OrderType::Market
+            },
+            time_in_force: TimeInForce::Day,
+            qty: Some(dec(qty)),
+            notional: None,
+            limit_price: limit.map(dec),
+            stop_price: None,
+            extended_hours: false,
+            take_profit: None,
+            stop_loss: None,
+        }
+    }
+
+    fn position(symbol: &str, qty: &str, price: &str) -> Position {
+        let value = dec(qty) * dec(price);
+        serde_json::from_value(serde_json::json!({
+            "asset_id": "asset-1",
+            "symbol": symbol,
+            "exchange": "NASDAQ",
+            "asset_class": "us_equity",
+            "asset_marginable": true,
+            "avg_entry_price": price,
+            "qty": qty,
+            "qty_available": qty,
+            "side": "long",
+            "market_value": value.to_string(),
+            "cost_basis": value.to_string(),
+            "unrealized_pl": "0",
+            "unrealized_plpc": "0",
+            "unrealized_intraday_pl": "0",
+            "unrealized_intraday_plpc": "0",
+            "current_price": price,
+            "lastday_price": price,
+            "change_today": "0"
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn per_order_limits() {
+        let guard = guard(
+            RiskLimits::new()
+                .deny_symbols(&["GME"])
+                .max_open_orders(3)
+                .max_order_notional(dec("10000"))
+                .max_position_qty(dec("100"))
+                .max_gross_exposure(dec("20000")),
+        );
+        let positions = [position("AAPL", "60", "150"), position("MSFT", "10", "400")];
+
+        assert_eq!(
+            guard.evaluate(&buy("GME", "1", None), None, &positions),
+            Err(RiskViolation::SymbolNotAllowed("GME".to_string()))
+        );
+        assert!(matches!(
+            guard.evaluate_open_orders(3),
+            Err(RiskViolation::TooManyOpenOrders { open: 3, max: 3 })
+        ));
+        assert_eq!(guard.evaluate_open_orders(2), Ok(()));
+        // Market order valued at the position's current price.
+        assert!(
+            guard
+                .evaluate(&buy("AAPL", "30", None), None, &positions)
+                .is_ok()
+        );
+        assert!(matches!(
+            guard.evaluate(&buy("AAPL", "50", None), None, &positions),
+            Err(RiskViolation::PositionSize { .. })
+        ));
+        assert!(matches!(
+            guard.evaluate(&buy("NVDA", "20", Some("600")), None, &positions),
+            Err(RiskViolation::OrderNotional { .. })
+        ));
+        assert!(matches!(
+            guard.evaluate(&buy("NVDA", "15", Some("600")), None, &positions),
+            Err(RiskViolation::GrossExposure { .. })
+        ));
+        assert_eq!(
+            guard.evaluate(&buy("NVDA", "1", None), None, &positions),
+            Err(RiskViolation::Unpriced("NVDA".to_string()))
+        );
+        assert!(
+            guard
+                .evaluate(&buy("NVDA", "1", None), Some(dec("600")), &positions)
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn daily_loss_trips_the_kill_switch_once() {
+        let guard = guard(RiskLimits::new().max_daily_loss(dec("500")));
+        assert_eq!(guard.trip_on_loss(dec("99600"), dec("100000")), Ok(()));
+        assert_eq!(
+            guard.trip_on_loss(dec("99400"), dec("100000")),
+            Err(RiskViolation::DailyLoss {
+                loss: dec("600"),
+                max: dec("500")
+            })
+        );
+        assert!(guard.is_tripped());
+        assert_eq!(
+            guard.trip_on_loss(dec("99000"), dec("100000")),
+            Err(RiskViolation::KillSwitchTripped)
+        );
+        assert!(!guard.is_action_pending());
+        guard.reset_kill_switch();
+        assert!(!guard.is_tripped());
+    }
+
+    #[test]
+    fn crypto_symbols_match_in_either_form() {
+        let denied = guard(RiskLimits::new().deny_symbols(&["BTC/USD"]));
+        assert_eq!(
+            denied.evaluate(&buy("BTCUSD", "1", Some("60000")), None, &[]),
+            Err(RiskViolation::SymbolNotAllowed("BTCUSD".to_string()))
+        );
+
+        let guard = guard(
+            RiskLimits::new()
+                .allow_symbols(&["ETHUSD"])
+                .max_position_qty(dec("2")),
+        );
+        let positions = [position("ETHUSD", "1.5", "3000")];
+        assert!(matches!(
+            guard.evaluate(&buy("ETH/USD", "1", None), None, &positions),
+            Err(RiskViolation::PositionSize { .. })
+        ));
+        guard.mark("ETH/USD", dec("3100"));
+        assert_eq!(guard.mark_for("ETHUSD"), Some(dec("3100")));
+    }
+
+    #[test]
+    fn single_leg_option_orders_are_valued_per_contract() {
+        let guard = guard(RiskLimits::new().max_order_notional(dec("1000")));
+        assert_eq!(
+            guard.evaluate(&buy("AAPL250117C00150000", "10", Some("5")), None, &[]),
+            Err(RiskViolation::OrderNotional {
+                notional: dec("5000"),
+                max: dec("1000")
+            })
+        );
+        assert!(
+            guard
+                .evaluate(&buy("AAPL250117C00150000", "2", Some("5")), None, &[])
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn replacements_count_only_the_change_in_qty() {
+        let guard = guard(RiskLimits::new().max_position_qty(dec("100")));
+        let positions = [position("AAPL", "60", "150")];
+        let order = buy("AAPL", "45", Some("150"));
+        assert!(matches!(
+            guard.evaluate(&order, None, &positions),
+            Err(RiskViolation::PositionSize { .. })
+        ));
+        assert!(
+            guard
+                .evaluate_replacing(&order, None, &positions, dec("30"))
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn multi_leg_orders_are_checked_per_leg() {
+        let legs = [
+            OrderLeg::new(
+                "AAPL250620C00150000",
+                Side::Buy,
+                dec("2"),
+                PositionIntent::BuyToOpen,
+            ),
+            OrderLeg::new(
+                "AAPL250620C00160000",
+                Side::Sell,
+                dec("1"),
+                PositionIntent::SellToOpen,
+            ),
+        ];
+        let denied = guard(RiskLimits::new().deny_symbols(&["AAPL250620C00160000"]));
+        assert_eq!(
+            denied.evaluate_multi_leg(&legs, Some(dec("1")), dec("2"), &[]),
+            Err(RiskViolation::SymbolNotAllowed(
+                "AAPL250620C00160000".to_string()
+            ))
+        );
+
+        let guard = guard(
+            RiskLimits::new()
+                .max_position_qty(dec("5"))
+                .max_order_notional(dec("500")),
+        );
+        assert!(
+            guard
+                .evaluate_multi_leg(&legs, Some(dec("2")), dec("1"), &[])
+                .is_ok()
+        );
+        assert!(matches!(
+            guard.evaluate_multi_leg(&legs, Some(dec("3")), dec("1"), &[]),
+            Err(RiskViolation::PositionSize { .. })
+        ));
+        assert_eq!(
+            guard.evaluate_multi_leg(&legs, Some(dec("2")), dec("3"), &[]),
+            Err(RiskViolation::OrderNotional {
+                notional: dec("600"),
+                max: dec("500")
+            })
+        );
+    }
+
+    /// A kill switch action that fails stays pending and runs again on the
+    /// next check.
+    #[tokio::test]
+    #[serial]
+    async fn failed_kill_switch_action_is_retried() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v2/account"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(account_json("99000", "100000")))
+            .mount(&server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path("/v2/orders"))
+            .respond_with(ResponseTemplate::new(403))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path("/v2/orders"))
+            .respond_with(ResponseTemplate::new(207).set_body_json(serde_json::json!([])))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let guard = mock_guard(
+            &server,
+            RiskLimits::new()
+                .max_daily_loss(dec("500"))
+                .kill_switch_action(KillSwitchAction::CancelOrders),
+        );
+        let err = guard.check_kill_switch().await.unwrap_err();
+        assert!(
+            matches!(err, crate::Error::ApiError { status: 403, .. }),
+            "{err:?}"
+        );
+        assert!(guard.is_tripped());
+        assert!(guard.is_action_pending());
+
+        let err = guard.check_kill_switch().await.unwrap_err();
+        assert!(
+            matches!(
+                err,
+                crate::Error::RiskLimit(RiskViolation::KillSwitchTripped)
+            ),
+            "{err:?}"
+        );
+        assert!(!guard.is_action_pending());
+    }
+
+    /// Open orders are counted across every page, and the order is checked
+    /// as rounded rather than as built.
+    #[tokio::test]
+    #[serial]
+    async fn check_counts_every_open_order_and_uses_the_rounded_order() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v2/account"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(account_json("100000", "100000")),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v2/positions"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([])))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v2/orders"))
+            .and(query_param("after_order_id", "order-499"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(serde_json::json!([open_order_json(500)])),
+            )
+            .mount(&server)
+            .await;
+        let first_page: Vec<_> = (0..ORDER_PAGE_SIZE).map(open_order_json).collect();
+        Mock::given(method("GET"))
+            .and(path("/v2/orders"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(first_page))
+            .mount(&server)
+            .await;
+
+        let asset = whole_share_asset();
+        let guard = mock_guard(
+            &server,
+            RiskLimits::new()
+                .max_open_orders(502)
+                .max_position_qty(dec("10")),
+        );
+        let order = guard
+            .client()
+            .market_order("AAPL", Side::Buy)
+            .qty(dec("10.4"))
+            .round_to_asset(&asset);
+        guard.check(&order).await.unwrap();
+
+        let guard = mock_guard(&server, RiskLimits::new().max_open_orders(501));
+        let err = guard.check(&order).await.unwrap_err();
+        assert!(
+            matches!(
+                err,
+                crate::Error::RiskLimit(RiskViolation::TooManyOpenOrders {
+                    open: 501,
+                    max: 501
+                })
+            ),
+            "{err:?}"
+        );
+    }
+}
@@ -1,6 +1,10 @@
-use reqwest::{Client, Method, RequestBuilder, Url};
+use std::sync::Arc;
+
+use reqwest::{Client, Method, RequestBuilder, Response, Url};
 use serde::de::DeserializeOwned;
 
+use super::dry_run::{DryRun, DryRunCall, Synthesized};
+use super::live_safety::{LiveInterlock, LiveSafety};
 use crate::{AccountType, env::ApiKey, error::Error, error::Result};
 
 const KEY_ID_HEADER: &str = "APCA-API-KEY-ID";
@@ -21,6 +25,15 @@ pub struct TradingClient {
     account_type: AccountType,
     api_key: ApiKey,
     client: Client,
+    base_url: Url,
+    dry_run: Option<Arc<DryRun>>,
+    live_safety: Option<Arc<LiveInterlock>>,
+}
+
+/// What [`TradingClient::dispatch`] did with a request.
+enum Dispatched {
+    Sent(Response),
+    DryRun(Synthesized),
 }
 
 impl TradingClient {
@@ -39,19 +52,130 @@ impl TradingClient {
     ///
     /// `account_type` still selects the paper vs. live trading endpoint.
     pub fn new_with_credentials(account_type: AccountType, api_key: ApiKey) -> Result<Self> {
+        let base_url = match account_type {
+            AccountType::Paper => PAPER_TRADING_URL,
+            AccountType::Live => LIVE_TRADING_URL,
+        };
         Ok(Self {
             account_type,
             api_key,
             client: Client::new(),
+            base_url: Url::parse(base_url).expect("base URL constants are valid"),
+            dry_run: None,
+            live_safety: None,
         })
     }
 
+    /// The account type this client trades against.
+    pub fn account_type(&self) -> AccountType {
+        self.account_type
+    }
+
+    /// Switch this client into dry-run mode.
+    ///
+    /// Read-only calls still go to Alpaca, but every mutating call —
+    /// submitting, replacing or cancelling orders, closing positions,
+    /// exercising options, editing watchlists, updating the account
+    /// configuration, withdrawing crypto or changing the withdrawal
+    /// whitelist — is logged with its exact serialized payload and
+    /// answered with a synthesized response instead of being sent. New
+    /// orders come back `accepted` with `dry-run-N` ids; replacements,
+    /// closes and watchlist edits are built from the current state read
+    /// from Alpaca. Endpoints the client cannot answer fail with
+    /// [`Error::DryRunUnsupported`].
+    ///
+    /// Clones of a dry-run client share one log, available from
+    /// [`dry_run_log`](Self::dry_run_log).
+    ///
+    /// ```ignore
+    /// let client = TradingClient::new(AccountType::Live)?.with_dry_run();
+    /// let order = client.market_order("AAPL", Side::Buy).qty(dec!(1)).execute().await?;
+    /// assert_eq!(client.dry_run_log()[0].path, "v2/orders");
+    /// ```
+    pub fn with_dry_run(mut self) -> Self {
+        self.dry_run = Some(Arc::default());
+        self
+    }
+
+    /// Whether this client is in [dry-run mode](Self::with_dry_run).
+    pub fn is_dry_run(&self) -> bool {
+        self.dry_run.is_some()
+    }
+
+    /// Every mutating call intercepted so far, oldest first. Empty unless
+    /// the client is in [dry-run mode](Self::with_dry_run).
+    pub fn dry_run_log(&self) -> Vec<DryRunCall> {
+        self.dry_run
+            .as_ref()
+            .map(|dry_run| dry_run.calls())
+            .unwrap_or_default()
+    }
+
+    /// Install a [`LiveSafety`] interlock in front of every mutating call.
+    ///
+    /// Only takes effect on [`AccountType::Live`] clients; paper clients
+    /// ignore it. The client starts disarmed. Clones share the interlock
+    /// and its arming state. Refused calls are not sent (nor logged in
+    /// [dry-run mode](Self::with_dry_run)).
+    ///
+    /// ```ignore
+    /// let client = TradingClient::new(AccountType::Live)?.with_live_safety(
+    ///     LiveSafety::new().allow_symbols(&["AAPL"]).max_order_qty(dec!(10)),
+    /// );
+    /// client.arm_live_trading();
+    /// ```
+    pub fn with_live_safety(mut self, safety: LiveSafety) -> Self {
+        self.live_safety = Some(Arc::new(LiveInterlock::new(safety)));
+        self
+    }
+
+    /// Allow mutating calls through the [`LiveSafety`] interlock, subject
+    /// to its allow lists and size limits. Does nothing without one.
+    pub fn arm_live_trading(&self) {
+        if let Some(interlock) = &self.live_safety {
+            interlock.arm(true);
+        }
+    }
+
+    /// Refuse mutating calls again until re-armed or confirmed. Also
+    /// clears a pending [bulk arming](Self::arm_bulk_operation).
+    pub fn disarm_live_trading(&self) {
+        if let Some(interlock) = &self.live_safety {
+            interlock.arm(false);
+        }
+    }
+
+    /// Let the next allowed bulk call
+    /// ([`cancel_all_orders`](Self::cancel_all_orders),
+    /// [`close_all_positions`](Self::close_all_positions)) through the
+    /// [`LiveSafety`] interlock. The arming is used up by that call.
+    pub fn arm_bulk_operation(&self) {
+        if let Some(interlock) = &self.live_safety {
+            interlock.arm_bulk();
+        }
+    }
+
+    /// Whether mutating calls are gated by an armed [`LiveSafety`]
+    /// interlock. `false` when no interlock is installed.
+    pub fn is_live_trading_armed(&self) -> bool {
+        self.live_safety
+            .as_ref()
+            .is_some_and(|interlock| interlock.is_armed())
+    }
+
+    /// Point this client at an arbitrary base URL so tests can drive
+    /// endpoints against a local mock server. Crate-internal and test-only:
+    /// the public constructors always target Alpaca.
+    #[cfg(test)]
+    pub(crate) fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = Url::parse(base_url).expect("test base URL is valid");
+        self
+    }
+
     /// Build a request for the given path, which should include the
     /// version prefix (e.g. `"v2/orders"` or `"v2/account/activities"`).
     pub(crate) fn request(&self, method: Method, path: &str) -> Result<RequestBuilder> {
-        let url = Url::parse(self.base_url())
-            .expect("base URL constants are valid")
-            .join(path)?;
+        let url = self.base_url.join(path)?;
         Ok(self
             .client
             .request(method, url)
@@ -65,10 +189,19 @@ impl TradingClient {
         &self,
         request: RequestBuilder,
     ) -> Result<T> {
-        let response = request
-            .send()
-            .await
-            .map_err(|e| Error::ReqwestSend(e.into()))?;
+        match self.dispatch(request).await? {
+            Dispatched::Sent(response) => Self::deserialize(response).await,
+            Dispatched::DryRun(synthesized) => synthesized.decode(),
+        }
+    }
+
+    /// Send a request and discard the body, returning an
+    /// [`Error::ApiError`] for non-2xx status codes.
+    pub(crate) async fn send_no_body(&self, request: RequestBuilder) -> Result<()> {
+        let response = match self.dispatch(request).await? {
+            Dispatched::Sent(response) => response,
+            Dispatched::DryRun(_) => return Ok(()),
+        };
         let status = response.status();
         if !status.is_success() {
             let body = response.text().await.unwrap_or_default();
@@ -77,19 +210,46 @@ impl TradingClient {
                 body,
             });
         }
-        response
-            .json()
-            .await
-            .map_err(|e| Error::ReqwestDeserialize(e.into()))
+        Ok(())
     }
 
-    /// Send a request and discard the body, returning an
-    /// [`Error::ApiError`] for non-2xx status codes.
-    pub(crate) async fn send_no_body(&self, request: RequestBuilder) -> Result<()> {
-        let response = request
-            .send()
+    /// Send a request. Mutating requests must first pass the live-safety
+    /// interlock, if one applies, and are handed to the dry-run
+    /// interceptor when this client is in dry-run mode.
+    async fn dispatch(&self, request: RequestBuilder) -> Result<Dispatched> {
+        let interlock = self
+            .live_safety
+            .as_ref()
+            .filter(|_| self.account_type == AccountType::Live);
+        if self.dry_run.is_none() && interlock.is_none() {
+            return request
+                .send()
+                .await
+                .map(Dispatched::Sent)
+                .map_err(|e| Error::ReqwestSend(e.into()));
+        }
+        let request = request.build().map_err(|e| Error::ReqwestSend(e.into()))?;
+        if request.method() != Method::GET {
+            if let Some(interlock) = interlock {
+                interlock.check(&request)?;
+            }
+            if let Some(dry_run) = &self.dry_run {
+                return dry_run
+                    .respond(self, &request)
+                    .await
+                    .map(Dispatched::DryRun);
+            }
+        }
+        self.client
+            .execute(request)
             .await
-            .map_err(|e| Error::ReqwestSend(e.into()))?;
+            .map(Dispatched::Sent)
+            .map_err(|e| Error::ReqwestSend(e.into()))
+    }
+
+    /// Deserialize a JSON response, returning an [`Error::ApiError`] for
+    /// non-2xx status codes.
+    pub(crate) async fn deserialize<T: DeserializeOwned>(response: Response) -> Result<T> {
         let status = response.status();
         if !status.is_success() {
             let body = response.text().await.unwrap_or_default();
@@ -98,14 +258,10 @@ impl TradingClient {
                 body,
             });
         }
-        Ok(())
-    }
-
-    const fn base_url(&self) -> &'static str {
-        match self.account_type {
-            AccountType::Paper => PAPER_TRADING_URL,
-            AccountType::Live => LIVE_TRADING_URL,
-        }
+        response
+            .json()
+            .await
+            .map_err(|e| Error::ReqwestDeserialize(e.into()))
     }
 }
 
@@ -0,0 +1,618 @@
+use futures::Stream;
+use std::{
+    collections::{HashMap, HashSet},
+    pin::Pin,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    task::{Context, Poll},
+};
+use tokio::sync::{mpsc, oneshot};
+
+use crate::{
+    Error,
+    streaming::{
+        client::{StreamProtocol, StreamProtocolCodec, StreamingClient},
+        message_stream::{SubscriptionHandle, is_terminal, reader_gone},
+        split::MessageReceiver,
+        wire::StreamError,
+    },
+};
+
+macro_rules! error {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        tracing::error!($($arg)*);
+    };
+}
+
+/// Per-subscriber buffer used by [`Broadcaster::new`].
+const DEFAULT_CAPACITY: usize = 1024;
+
+/// Wildcard symbol accepted by the market-data feeds.
+const WILDCARD: &str = "*";
+
+/// Data channel a market-data message belongs to. Trade corrections and
+/// cancel errors travel on [`Channel::Trades`], since that is the channel
+/// whose subscription delivers them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub(crate) enum Channel {
+    Trades,
+    Quotes,
+    Bars,
+    DailyBars,
+    UpdatedBars,
+    Statuses,
+    Lulds,
+    Imbalances,
+    Orderbooks,
+    #[cfg(feature = "crypto-perps")]
+    Pricing,
+}
+
+/// One `(channel, symbol)` pair of interest.
+pub(crate) type Topic = (Channel, String);
+
+/// Crate-private extension of [`StreamProtocol`] for feeds whose messages
+/// each belong to a single `(channel, symbol)` pair, which is what
+/// [`Broadcaster`] routes on.
+pub(crate) trait FanOutProtocol: StreamProtocol {
+    /// Flatten a subscription list into the pairs it covers.
+    fn topics(subscriptions: &Self::Subscriptions) -> Vec<Topic>;
+
+    /// Build the subscription list covering exactly `topics`.
+    fn subscriptions(topics: &[Topic]) -> Self::Subscriptions;
+
+    /// The pair a data message was published on, or `None` for envelopes
+    /// (control, error, subscription) that carry no market data.
+    fn topic(message: &Self::Message) -> Option<(Channel, &str)>;
+
+    /// Wrap a server error so it can be delivered like any other message.
+    fn error_message(error: StreamError) -> Self::Message;
+}
+
+/// Append `(channel, symbol)` for every symbol in `list`.
+pub(crate) fn push_topics(topics: &mut Vec<Topic>, channel: Channel, list: Option<&Vec<String>>) {
+    for symbol in list.into_iter().flatten() {
+        topics.push((channel, symbol.clone()));
+    }
+}
+
+/// The symbols in `topics` on `channel`, or `None` when there are none so
+/// the field is left out of the request.
+pub(crate) fn symbols_for(topics: &[Topic], channel: Channel) -> Option<Vec<String>> {
+    let symbols: Vec<String> = topics
+        .iter()
+        .filter(|(c, _)| *c == channel)
+        .map(|(_, symbol)| symbol.clone())
+        .collect();
+    (!symbols.is_empty()).then_some(symbols)
+}
+
+enum Command<P: StreamProtocol> {
+    Register {
+        topics: Vec<Topic>,
+        reply: oneshot::Sender<Result<Registration<P>, Error>>,
+    },
+    Release(u64),
+    Shutdown(oneshot::Sender<Result<(), Error>>),
+}
+
+struct Registration<P: StreamProtocol> {
+    id: u64,
+    messages: mpsc::Receiver<P::Message>,
+    dropped: Arc<AtomicU64>,
+}
+
+/// Fans one streaming connection out to many consumers, each interested in
+/// its own set of symbols and channels.
+///
+/// Every [`subscribe`](Self::subscribe) call describes the consumer's
+/// interest with the feed's ordinary subscription list (e.g.
+/// `StockSubscriptionList::new().add_quotes("AAPL")`) and returns a
+/// [`Subscriber`] that only receives matching messages. Interest is
+/// reference-counted per `(channel, symbol)`: the server is only asked to
+/// subscribe when the first consumer wants a pair, and to unsubscribe when
+/// the last one holding it is dropped. A `"*"` interest receives every
+/// symbol on that channel.
+///
+/// Each subscriber has a bounded buffer. A subscriber that falls behind
+/// loses messages rather than stalling the others; [`Subscriber::dropped`]
+/// counts how many. Server error envelopes are delivered to every
+/// subscriber. When the connection fails the subscribers' channels close,
+/// and [`shut_down`](Self::shut_down) reports the error.
+///
+/// Available for the stock, crypto and options feeds. News articles carry
+/// several symbols each and are not routed this way.
+#[allow(
+    private_bounds,
+    reason = "See StreamingClient — bound is sealed on purpose."
+)]
+pub struct Broadcaster<P: FanOutProtocol + StreamProtocolCodec> {
+    commands: mpsc::UnboundedSender<Command<P>>,
+    handle: SubscriptionHandle<P>,
+}
+
+impl<P: FanOutProtocol + StreamProtocolCodec> Clone for Broadcaster<P> {
+    fn clone(&self) -> Self {
+        Self {
+            commands: self.commands.clone(),
+            handle: self.handle.clone(),
+        }
+    }
+}
+
+impl<P: FanOutProtocol + StreamProtocolCodec> std::fmt::Debug for Broadcaster<P> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Broadcaster").finish_non_exhaustive()
+    }
+}
+
+#[allow(
+    private_bounds,
+    reason = "See StreamingClient — bound is sealed on purpose."
+)]
+impl<P: FanOutProtocol + StreamProtocolCodec> Broadcaster<P> {
+    /// Take over `client`, buffering up to 1024 messages per subscriber.
+    ///
+    /// Subscriptions already active on `client` stay active but are not
+    /// delivered to anyone until a subscriber asks for them. Must be called
+    /// from within a Tokio runtime.
+    pub fn new(client: StreamingClient<P>) -> Self {
+        Self::with_capacity(client, DEFAULT_CAPACITY)
+    }
+
+    /// Take over `client`, buffering up to `capacity` messages per
+    /// subscriber.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn with_capacity(client: StreamingClient<P>, capacity: usize) -> Self {
+        assert!(capacity > 0, "subscriber capacity must be non-zero");
+        let (handle, receiver) = client.split();
+        let (commands, command_rx) = mpsc::unbounded_channel();
+        let dispatcher = Dispatcher {
+            handle: handle.clone(),
+            capacity,
+            next_id: 0,
+            consumers: HashMap::new(),
+            routes: HashMap::new(),
+            failure: None,
+        };
+        tokio::spawn(dispatcher.run(receiver, command_rx));
+        Self { commands, handle }
+    }
+
+    /// Register interest in everything `interest` covers and return a
+    /// receiver for the matching messages.
+    ///
+    /// Resolves once the server has confirmed any pairs nobody was
+    /// subscribed to yet. Returns [`Error::StreamingSubscribe`] if it
+    /// rejects them, in which case nothing is registered.
+    pub async fn subscribe(&self, interest: &P::Subscriptions) -> Result<Subscriber<P>, Error> {
+        let (reply, response) = oneshot::channel();
+        self.commands
+            .send(Command::Register {
+                topics: P::topics(interest),
+                reply,
+            })
+            .map_err(|_| reader_gone())?;
+        let registration = response.await.map_err(|_| reader_gone())??;
+        Ok(Subscriber {
+            id: registration.id,
+            messages: registration.messages,
+            dropped: registration.dropped,
+            commands: self.commands.clone(),
+        })
+    }
+
+    /// The union of all subscribers' interest, as last confirmed by the
+    /// server.
+    pub fn subscriptions(&self) -> P::Subscriptions {
+        self.handle.current_subscriptions()
+    }
+
+    /// Close the connection. Every subscriber's channel closes.
+    ///
+    /// If the connection had already failed, returns that failure instead.
+    pub async fn shut_down(&self) -> Result<(), Error> {
+        let (reply, response) = oneshot::channel();
+        self.commands
+            .send(Command::Shutdown(reply))
+            .map_err(|_| reader_gone())?;
+        response.await.map_err(|_| reader_gone())?
+    }
+}
+
+/// One consumer's filtered view of a [`Broadcaster`].
+///
+/// Dropping it releases its interest; pairs no other subscriber holds are
+/// unsubscribed on the server.
+#[allow(
+    private_bounds,
+    reason = "See StreamingClient — bound is sealed on purpose."
+)]
+pub struct Subscriber<P: FanOutProtocol + StreamProtocolCodec> {
+    id: u64,
+    messages: mpsc::Receiver<P::Message>,
+    dropped: Arc<AtomicU64>,
+    commands: mpsc::UnboundedSender<Command<P>>,
+}
+
+impl<P: FanOutProtocol + StreamProtocolCodec> std::fmt::Debug for Subscriber<P> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Subscriber")
+            .field("id", &self.id)
+            .finish_non_exhaustive()
+    }
+}
+
+#[allow(
+    private_bounds,
+    reason = "See StreamingClient — bound is sealed on purpose."
+)]
+impl<P: FanOutProtocol + StreamProtocolCodec> Subscriber<P> {
+    /// Receive the next matching message, or `None` once the connection
+    /// has ended.
+    pub async fn recv(&mut self) -> Option<P::Message> {
+        self.messages.recv().await
+    }
+
+    /// Number of messages discarded because this subscriber's buffer was
+    /// full.
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+
+impl<P: FanOutProtocol + StreamProtocolCodec> Stream for Subscriber<P> {
+    type Item = P::Message;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.messages.poll_recv(cx)
+    }
+}
+
+impl<P: FanOutProtocol + StreamProtocolCodec> Drop for Subscriber<P> {
+    fn drop(&mut self) {
+        let _ = self.commands.send(Command::Release(self.id));
+    }
+}
+
+struct Consumer<P: StreamProtocol> {
+    sender: mpsc::Sender<P::Message>,
+    topics: Vec<Topic>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl<P: StreamProtocol> Consumer<P> {
+    fn deliver(&self, message: &P::Message) {
+        if let Err(mpsc::error::TrySendError::Full(_)) = self.sender.try_send(message.clone()) {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+        // A closed channel means the subscriber was dropped; its release
+        // is already queued.
+    }
+}
+
+/// Background task behind [`Broadcaster`]. Registration, release and
+/// routing all happen here, one at a time, so the reference counts and the
+/// server's subscription list can never disagree.
+struct Dispatcher<P: FanOutProtocol + StreamProtocolCodec> {
+    handle: SubscriptionHandle<P>,
+    capacity: usize,
+    next_id: u64,
+    consumers: HashMap<u64, Consumer<P>>,
+    /// Consumer ids per `(channel, symbol)`; the vector's length is the
+    /// pair's reference count.
+    routes: HashMap<Channel, HashMap<String, Vec<u64>>>,
+    /// Terminal error that ended the connection, kept for `shut_down`.
+    failure: Option<Error>,
+}
+
+impl<P: FanOutProtocol + StreamProtocolCodec> Dispatcher<P> {
+    async fn run(
+        mut self,
+        receiver: MessageReceiver<P>,
+        mut commands: mpsc::UnboundedReceiver<Command<P>>,
+    ) {
+        let mut receiver = Some(receiver);
+        loop {
+            tokio::select! {
+                biased;
+                command = commands.recv() => match command {
+                    Some(Command::Register { topics, reply }) => {
+                        let result = self.register(topics, receiver.is_some()).await;
+                        let _ = reply.send(result);
+                    }
+                    Some(Command::Release(id)) => self.release(id, receiver.is_some()).await,
+                    Some(Command::Shutdown(reply)) => {
+                        let result = match receiver.take() {
+                            Some(receiver) => receiver.shut_down().await,
+                            None => self.failure.take().map_or(Ok(()), Err),
+                        };
+                        self.disconnect();
+                        let _ = reply.send(result);
+                    }
+                    None => {
+                        // Broadcaster and every subscriber dropped.
+                        if let Some(receiver) = receiver.take() {
+                            let _ = receiver.shut_down().await;
+                        }
+                        return;
+                    }
+                },
+                message = next_message(&mut receiver) => match message {
+                    Some(Ok(message)) => self.dispatch(&message),
+                    Some(Err(Error::StreamingError(stream_error))) => {
+                        let message = P::error_message(stream_error);
+                        for consumer in self.consumers.values() {
+                            consumer.deliver(&message);
+                        }
+                    }
+                    Some(Err(e)) if is_terminal(&e) => {
+                        error!("Broadcast connection ended: {e}");
+                        self.failure = Some(e);
+                    }
+                    Some(Err(_e)) => {
+                        error!("Dropping undeliverable streaming error: {_e}");
+                    }
+                    None => {
+                        receiver = None;
+                        self.disconnect();
+                    }
+                },
+            }
+        }
+    }
+
+    async fn register(
+        &mut self,
+        topics: Vec<Topic>,
+        connected: bool,
+    ) -> Result<Registration<P>, Error> {
+        if !connected {
+            return Err(reader_gone());
+        }
+        let topics: Vec<Topic> = topics
+            .into_iter()
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        let added: Vec<Topic> = topics
+            .iter()
+            .filter(|(channel, symbol)| {
+                self.routes
+                    .get(channel)
+                    .is_none_or(|symbols| !symbols.contains_key(symbol))
+            })
+            .cloned()
+            .collect();
+        if !added.is_empty() {
+            self.handle
+                .add_subscriptions(&P::subscriptions(&added))
+                .await?;
+        }
+
+        let id = self.next_id;
+        self.next_id += 1;
+        for (channel, symbol) in &topics {
+            self.routes
+                .entry(*channel)
+                .or_default()
+                .entry(symbol.clone())
+                .or_default()
+                .push(id);
+        }
+        let (sender, messages) = mpsc::channel(self.capacity);
+        let dropped = Arc::new(AtomicU64::new(0));
+        self.consumers.insert(
+            id,
+            Consumer {
+                sender,
+                topics,
+                dropped: Arc::clone(&dropped),
+            },
+        );
+        Ok(Registration {
+            id,
+            messages,
+            dropped,
+        })
+    }
+
+    async fn release(&mut self, id: u64, connected: bool) {
+        let Some(consumer) = self.consumers.remove(&id) else {
+            return;
+        };
+        let mut removed = Vec::new();
+        for (channel, symbol) in consumer.topics {
+            let Some(symbols) = self.routes.get_mut(&channel) else {
+                continue;
+            };
+            if let Some(ids) = symbols.get_mut(&symbol) {
+                ids.retain(|other| *other != id);
+                if ids.is_empty() {
+                    symbols.remove(&symbol);
+                    removed.push((channel, symbol));
+                }
+            }
+        }
+        if connected
+            && !removed.is_empty()
+            && let Err(_e) = self
+                .handle
+                .remove_subscriptions(&P::subscriptions(&removed))
+                .await
+        {
+            error!("Failed to unsubscribe released symbols: {_e}");
+        }
+    }
+
+    fn dispatch(&self, message: &P::Message) {
+        let Some((channel, symbol)) = P::topic(message) else {
+            return;
+        };
+        let Some(symbols) = self.routes.get(&channel) else {
+            return;
+        };
+        let mut recipients: Vec<u64> = Vec::new();
+        for key in [symbol, WILDCARD] {
+            for id in symbols.get(key).into_iter().flatten() {
+                if !recipients.contains(id) {
+                    recipients.push(*id);
+                }
+            }
+        }
+        for id in recipients {
+            if let Some(consumer) = self.consumers.get(&id) {
+                consumer.deliver(message);
+            }
+        }
+    }
+
+    /// Forget every subscriber, closing their channels.
+    fn disconnect(&mut self) {
+        self.consumers.clear();
+        self.routes.clear();
+    }
+}
+
+async fn next_message<P: StreamProtocol + StreamProtocolCodec>(
+    receiver: &mut Option<MessageReceiver<P>>,
+) -> Option<Result<P::Message, Error>> {
+    match receiver {
+        Some(receiver) => receiver.recv().await,
+        None => std::future::pending().await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        env::ApiKey,
+        streaming::{
+            Broadcaster, StockProtocol, StockStreamMessage, StockSubscriptionList, StreamingClient,
+        },
+    };
+    use futures::{SinkExt, StreamExt};
+    use socketeer::{Message, WebSocketStreamType, get_mock_address, tungstenite};
+    use std::time::Duration;
+    use tokio::sync::mpsc;
+
+    const AAPL_TRADE: &str = r#"{"T":"t","S":"AAPL","i":1,"x":"V","p":150.0,"s":100,"t":"2024-01-02T15:30:00Z","z":"C"}"#;
+    const MSFT_TRADE: &str =
+        r#"{"T":"t","S":"MSFT","i":2,"x":"V","p":400.0,"s":10,"t":"2024-01-02T15:30:00Z","z":"C"}"#;
+
+    /// Handshake, then confirm every (un)subscribe and report it to the
+    /// test. After the second request, publish one AAPL and one MSFT trade.
+    async fn recording_server(
+        mut ws: WebSocketStreamType,
+        requests: mpsc::UnboundedSender<serde_json::Value>,
+    ) -> Result<bool, tungstenite::Error> {
+        ws.send(Message::text(r#"[{"T":"success","msg":"connected"}]"#))
+            .await?;
+        let _auth_request = ws.next().await;
+        ws.send(Message::text(r#"[{"T":"success","msg":"authenticated"}]"#))
+            .await?;
+        let mut seen = 0;
+        while let Some(Ok(message)) = ws.next().await {
+            if message.is_close() {
+                break;
+            }
+            let Ok(text) = message.to_text() else {
+                continue;
+            };
+            let Ok(request) = serde_json::from_str::<serde_json::Value>(text) else {
+                continue;
+            };
+            let _ = requests.send(request);
+            ws.send(Message::text(r#"[{"T":"subscription","trades":[]}]"#))
+                .await?;
+            seen += 1;
+            if seen == 2 {
+                ws.send(Message::text(format!("[{AAPL_TRADE},{MSFT_TRADE}]")))
+                    .await?;
+            }
+        }
+        Ok(true)
+    }
+
+    fn sorted_trades(request: &serde_json::Value) -> Vec<String> {
+        let mut symbols: Vec<String> = request["trades"]
+            .as_array()
+            .expect("request lists trades")
+            .iter()
+            .map(|s| s.as_str().unwrap().to_string())
+            .collect();
+        symbols.sort();
+        symbols
+    }
+
+    /// Subscribers only see their own symbols, and the server is only
+    /// asked to change subscriptions when the union of interest changes.
+    #[tokio::test]
+    async fn routes_by_symbol_and_reference_counts_interest() {
+        let (requests_tx, mut requests) = mpsc::unbounded_channel();
+        let address = get_mock_address(move |ws| recording_server(ws, requests_tx.clone())).await;
+        let url = format!("ws://{address}");
+        let client = StreamingClient::<StockProtocol>::connect(ApiKey::new("k", "s"), &url)
+            .await
+            .expect("handshake succeeds");
+        let broadcaster = Broadcaster::new(client);
+
+        let mut aapl_only = broadcaster
+            .subscribe(&StockSubscriptionList::new().add_trades("AAPL"))
+            .await
+            .expect("first subscriber is confirmed");
+        let mut both = broadcaster
+            .subscribe(
+                &StockSubscriptionList::new()
+                    .add_trades("AAPL")
+                    .add_trades("MSFT"),
+            )
+            .await
+            .expect("second subscriber is confirmed");
+
+        let first = requests.recv().await.unwrap();
+        assert_eq!(first["action"], "subscribe");
+        assert_eq!(sorted_trades(&first), vec!["AAPL"]);
+        let second = requests.recv().await.unwrap();
+        assert_eq!(second["action"], "subscribe");
+        assert_eq!(sorted_trades(&second), vec!["MSFT"], "AAPL is already held");
+
+        let timeout = Duration::from_secs(5);
+        let symbol = |message: Option<StockStreamMessage>| match message {
+            Some(StockStreamMessage::Trade(trade)) => trade.symbol,
+            other => panic!("expected a trade, got {other:?}"),
+        };
+        let received = tokio::time::timeout(timeout, aapl_only.recv())
+            .await
+            .unwrap();
+        assert_eq!(symbol(received), "AAPL");
+        let received = tokio::time::timeout(timeout, both.recv()).await.unwrap();
+        assert_eq!(symbol(received), "AAPL");
+        let received = tokio::time::timeout(timeout, both.recv()).await.unwrap();
+        assert_eq!(symbol(received), "MSFT");
+        assert!(
+            aapl_only.messages.try_recv().is_err(),
+            "MSFT is not routed to the AAPL-only subscriber"
+        );
+
+        // AAPL is still held by `both`, so only its release unsubscribes.
+        drop(aapl_only);
+        drop(both);
+        let third = tokio::time::timeout(timeout, requests.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(third["action"], "unsubscribe");
+        assert_eq!(sorted_trades(&third), vec!["AAPL", "MSFT"]);
+
+        let _ = tokio::time::timeout(timeout, broadcaster.shut_down())
+            .await
+            .expect("shutdown completes");
+    }
+}
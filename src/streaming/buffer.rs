@@ -0,0 +1,425 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    future::poll_fn,
+    sync::{Arc, Mutex, MutexGuard},
+    task::{Context, Poll, Waker},
+};
+
+use crate::{
+    Error,
+    streaming::client::{StreamProtocol, StreamProtocolCodec},
+};
+
+/// Default number of messages a split client buffers for its receiver.
+pub(crate) const DEFAULT_CAPACITY: usize = 8192;
+
+/// What a split client's background reader does when its receiver falls
+/// behind and the buffer is full.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum BackpressurePolicy {
+    /// Stop reading the socket until the receiver catches up. A receiver
+    /// that stays behind lets the server's send buffer fill, and Alpaca
+    /// eventually disconnects with `slow client`.
+    ///
+    /// Subscription requests are still answered while reading is paused.
+    /// Data that arrives ahead of their confirmation is held in a second
+    /// queue of the same capacity; if that overflows, its oldest messages
+    /// are dropped and counted in [`BufferStats::dropped`].
+    #[default]
+    Block,
+    /// Discard the oldest buffered item to make room for the newest.
+    DropOldest,
+    /// Replace a still-unread quote with a newer quote for the same symbol,
+    /// so the receiver only ever sees the latest one. Other messages fall
+    /// back to [`DropOldest`](Self::DropOldest) when the buffer is full.
+    ConflateQuotes,
+}
+
+/// Size and overflow policy of the buffer between a split client's
+/// background reader and its [`MessageReceiver`](crate::streaming::MessageReceiver).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct BufferConfig {
+    /// Maximum number of buffered items.
+    pub capacity: usize,
+    /// What to do when the buffer is full.
+    pub policy: BackpressurePolicy,
+}
+
+impl Default for BufferConfig {
+    fn default() -> Self {
+        Self {
+            capacity: DEFAULT_CAPACITY,
+            policy: BackpressurePolicy::default(),
+        }
+    }
+}
+
+impl BufferConfig {
+    /// Start from the defaults (8192 items, [`BackpressurePolicy::Block`]).
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the maximum number of buffered items. Values below one are
+    /// treated as one.
+    #[must_use]
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity.max(1);
+        self
+    }
+
+    /// Set the overflow policy.
+    #[must_use]
+    pub fn policy(mut self, policy: BackpressurePolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+}
+
+/// Counters for a split client's receive buffer, returned by
+/// [`MessageReceiver::buffer_stats`](crate::streaming::MessageReceiver::buffer_stats).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct BufferStats {
+    /// Items currently waiting to be received.
+    pub buffered: usize,
+    /// Largest number of items that have been waiting at once.
+    pub high_water_mark: usize,
+    /// Items discarded under [`BackpressurePolicy::DropOldest`] (or the
+    /// conflating policy's fallback), plus messages discarded while a
+    /// subscription request waited for its confirmation.
+    pub dropped: u64,
+    /// Quotes replaced by a newer quote for the same symbol under
+    /// [`BackpressurePolicy::ConflateQuotes`].
+    pub conflated: u64,
+}
+
+type Item<P> = Result<<P as StreamProtocol>::Message, Error>;
+
+struct State<P: StreamProtocol> {
+    queue: VecDeque<Item<P>>,
+    /// Sequence number of `queue[0]`; lets `quotes` address queued items
+    /// without rescanning the queue.
+    head: u64,
+    /// Sequence number of the most recently queued quote per symbol.
+    quotes: HashMap<String, u64>,
+    stats: BufferStats,
+    sender_done: bool,
+    receiver_gone: bool,
+    receiver_waker: Option<Waker>,
+    sender_waker: Option<Waker>,
+}
+
+struct Shared<P: StreamProtocol> {
+    config: BufferConfig,
+    state: Mutex<State<P>>,
+}
+
+impl<P: StreamProtocol> Shared<P> {
+    fn lock(&self) -> MutexGuard<'_, State<P>> {
+        // A panic while holding the lock leaves the queue itself intact.
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Create the bounded buffer behind [`StreamingClient::split`](crate::streaming::StreamingClient::split).
+pub(crate) fn buffer<P: StreamProtocol + StreamProtocolCodec>(
+    config: BufferConfig,
+) -> (BufferSender<P>, BufferReceiver<P>) {
+    let shared = Arc::new(Shared {
+        config,
+        state: Mutex::new(State {
+            queue: VecDeque::new(),
+            head: 0,
+            quotes: HashMap::new(),
+            stats: BufferStats::default(),
+            sender_done: false,
+            receiver_gone: false,
+            receiver_waker: None,
+            sender_waker: None,
+        }),
+    });
+    (
+        BufferSender {
+            shared: Arc::clone(&shared),
+        },
+        BufferReceiver { shared },
+    )
+}
+
+/// The receiver has been dropped.
+#[derive(Debug)]
+pub(crate) struct ReceiverGone;
+
+pub(crate) struct BufferSender<P: StreamProtocol> {
+    shared: Arc<Shared<P>>,
+}
+
+impl<P: StreamProtocol + StreamProtocolCodec> BufferSender<P> {
+    /// Queue `item`, applying the configured policy if the buffer is full.
+    /// Only waits under [`BackpressurePolicy::Block`].
+    pub(crate) async fn push(&self, item: Item<P>) -> Result<(), ReceiverGone> {
+        let mut item = Some(item);
+        poll_fn(|cx| self.poll_push(cx, &mut item)).await
+    }
+
+    /// Count messages discarded before reaching the buffer.
+    pub(crate) fn record_dropped(&self, count: u64) {
+        if count > 0 {
+            self.shared.lock().stats.dropped += count;
+        }
+    }
+
+    /// Resolves once the receiver has been dropped.
+    pub(crate) async fn closed(&self) {
+        poll_fn(|cx| {
+            let mut state = self.shared.lock();
+            if state.receiver_gone {
+                return Poll::Ready(());
+            }
+            state.sender_waker = Some(cx.waker().clone());
+            Poll::Pending
+        })
+        .await;
+    }
+
+    fn poll_push(
+        &self,
+        cx: &mut Context<'_>,
+        item: &mut Option<Item<P>>,
+    ) -> Poll<Result<(), ReceiverGone>> {
+        let config = self.shared.config;
+        let mut state = self.shared.lock();
+        if state.receiver_gone {
+            return Poll::Ready(Err(ReceiverGone));
+        }
+        let pending = item.take().expect("push polled after completion");
+
+        if config.policy == BackpressurePolicy::ConflateQuotes
+            && let Ok(message) = &pending
+            && let Some(symbol) = P::quote_symbol(message)
+        {
+            let queued = state.quotes.get(symbol).copied();
+            if let Some(sequence) = queued.filter(|sequence| *sequence >= state.head) {
+                let index = (sequence - state.head) as usize;
+                state.queue[index] = pending;
+                state.stats.conflated += 1;
+                return Poll::Ready(Ok(()));
+            }
+            let sequence = state.head + state.queue.len() as u64;
+            state.quotes.insert(symbol.to_string(), sequence);
+        }
+
+        if state.queue.len() >= config.capacity {
+            match config.policy {
+                BackpressurePolicy::Block => {
+                    *item = Some(pending);
+                    state.sender_waker = Some(cx.waker().clone());
+                    return Poll::Pending;
+                }
+                BackpressurePolicy::DropOldest | BackpressurePolicy::ConflateQuotes => {
+                    state.drop_oldest();
+                    state.stats.dropped += 1;
+                }
+            }
+        }
+        state.queue.push_back(pending);
+        state.stats.buffered = state.queue.len();
+        state.stats.high_water_mark = state.stats.high_water_mark.max(state.queue.len());
+        if let Some(waker) = state.receiver_waker.take() {
+            waker.wake();
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<P: StreamProtocol> Drop for BufferSender<P> {
+    fn drop(&mut self) {
+        let mut state = self.shared.lock();
+        state.sender_done = true;
+        if let Some(waker) = state.receiver_waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+pub(crate) struct BufferReceiver<P: StreamProtocol> {
+    shared: Arc<Shared<P>>,
+}
+
+impl<P: StreamProtocol> BufferReceiver<P> {
+    /// Take the next item, or `None` once the sender is gone and the buffer
+    /// has drained.
+    pub(crate) fn poll_recv(&self, cx: &mut Context<'_>) -> Poll<Option<Item<P>>> {
+        let mut state = self.shared.lock();
+        if let Some(item) = state.pop() {
+            if let Some(waker) = state.sender_waker.take() {
+                waker.wake();
+            }
+            return Poll::Ready(Some(item));
+        }
+        if state.sender_done {
+            return Poll::Ready(None);
+        }
+        state.receiver_waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+
+    pub(crate) fn stats(&self) -> BufferStats {
+        self.shared.lock().stats
+    }
+}
+
+impl<P: StreamProtocol> Drop for BufferReceiver<P> {
+    fn drop(&mut self) {
+        let mut state = self.shared.lock();
+        state.receiver_gone = true;
+        if let Some(waker) = state.sender_waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+impl<P: StreamProtocol> State<P> {
+    fn pop(&mut self) -> Option<Item<P>> {
+        let item = self.queue.pop_front()?;
+        self.head += 1;
+        self.stats.buffered = self.queue.len();
+        if self.queue.is_empty() {
+            // Every remembered quote has been received; start afresh so the
+            // map doesn't accumulate one entry per symbol ever seen.
+            self.quotes.clear();
+        }
+        Some(item)
+    }
+}
+
+impl<P: StreamProtocol + StreamProtocolCodec> State<P> {
+    /// Discard the oldest item to make room, forgetting it as a conflation
+    /// target. Unlike [`pop`](Self::pop) this leaves every other remembered
+    /// quote in place, including one queued by the push making room.
+    fn drop_oldest(&mut self) {
+        let sequence = self.head;
+        let Some(item) = self.queue.pop_front() else {
+            return;
+        };
+        self.head += 1;
+        if let Ok(message) = &item
+            && let Some(symbol) = P::quote_symbol(message)
+            && self.quotes.get(symbol) == Some(&sequence)
+        {
+            self.quotes.remove(symbol);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::streaming::{StockProtocol, StockStreamMessage};
+    use futures::FutureExt;
+
+    fn quote(symbol: &str, bid: f64) -> Item<StockProtocol> {
+        let json = format!(
+            r#"{{"T":"q","S":"{symbol}","bx":"V","bp":{bid},"bs":1,"ax":"V","ap":{bid},"as":1,"c":["R"],"z":"C","t":"2024-01-02T15:30:00Z"}}"#
+        );
+        Ok(serde_json::from_str(&json).unwrap())
+    }
+
+    fn trade(symbol: &str) -> Item<StockProtocol> {
+        let json = format!(
+            r#"{{"T":"t","S":"{symbol}","i":1,"x":"V","p":150.0,"s":100,"t":"2024-01-02T15:30:00Z","z":"C"}}"#
+        );
+        Ok(serde_json::from_str(&json).unwrap())
+    }
+
+    fn describe(item: Option<Item<StockProtocol>>) -> String {
+        match item {
+            Some(Ok(StockStreamMessage::Quote(quote))) => {
+                format!("q:{}@{}", quote.symbol, quote.bid_price)
+            }
+            Some(Ok(StockStreamMessage::Trade(trade))) => format!("t:{}", trade.symbol),
+            other => panic!("unexpected item {other:?}"),
+        }
+    }
+
+    async fn drain(receiver: &BufferReceiver<StockProtocol>, count: usize) -> Vec<String> {
+        let mut items = Vec::new();
+        for _ in 0..count {
+            items.push(describe(poll_fn(|cx| receiver.poll_recv(cx)).await));
+        }
+        items
+    }
+
+    #[tokio::test]
+    async fn drop_oldest_discards_and_counts() {
+        let (sender, receiver) = buffer::<StockProtocol>(
+            BufferConfig::new()
+                .capacity(2)
+                .policy(BackpressurePolicy::DropOldest),
+        );
+        for symbol in ["A", "B", "C"] {
+            sender.push(trade(symbol)).await.unwrap();
+        }
+        let stats = receiver.stats();
+        assert_eq!((stats.buffered, stats.dropped), (2, 1));
+        assert_eq!(drain(&receiver, 2).await, ["t:B", "t:C"]);
+    }
+
+    #[tokio::test]
+    async fn conflation_keeps_latest_quote_in_place() {
+        let (sender, receiver) =
+            buffer::<StockProtocol>(BufferConfig::new().policy(BackpressurePolicy::ConflateQuotes));
+        sender.push(quote("AAPL", 1.0)).await.unwrap();
+        sender.push(trade("AAPL")).await.unwrap();
+        sender.push(quote("MSFT", 2.0)).await.unwrap();
+        sender.push(quote("AAPL", 3.0)).await.unwrap();
+        assert_eq!(receiver.stats().conflated, 1);
+        assert_eq!(
+            drain(&receiver, 3).await,
+            ["q:AAPL@3", "t:AAPL", "q:MSFT@2"]
+        );
+
+        // Once received, a quote is no longer a conflation target.
+        sender.push(quote("AAPL", 4.0)).await.unwrap();
+        assert_eq!(drain(&receiver, 1).await, ["q:AAPL@4"]);
+        assert_eq!(receiver.stats().conflated, 1);
+    }
+
+    #[tokio::test]
+    async fn conflation_survives_overflow_at_capacity_one() {
+        let (sender, receiver) = buffer::<StockProtocol>(
+            BufferConfig::new()
+                .capacity(1)
+                .policy(BackpressurePolicy::ConflateQuotes),
+        );
+        sender.push(quote("AAPL", 1.0)).await.unwrap();
+        sender.push(quote("MSFT", 2.0)).await.unwrap();
+        sender.push(quote("MSFT", 3.0)).await.unwrap();
+        let stats = receiver.stats();
+        assert_eq!((stats.buffered, stats.dropped, stats.conflated), (1, 1, 1));
+        assert_eq!(drain(&receiver, 1).await, ["q:MSFT@3"]);
+    }
+
+    #[tokio::test]
+    async fn block_waits_for_the_receiver() {
+        let (sender, receiver) = buffer::<StockProtocol>(BufferConfig::new().capacity(1));
+        sender.push(trade("A")).await.unwrap();
+        let mut blocked = Box::pin(sender.push(trade("B")));
+        assert!(
+            (&mut blocked).now_or_never().is_none(),
+            "full buffer blocks"
+        );
+
+        assert_eq!(drain(&receiver, 1).await, ["t:A"]);
+        blocked.await.unwrap();
+        assert_eq!(drain(&receiver, 1).await, ["t:B"]);
+        assert_eq!(receiver.stats().dropped, 0);
+
+        drop(receiver);
+        assert!(sender.push(trade("C")).await.is_err());
+    }
+}
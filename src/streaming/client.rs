@@ -5,7 +5,12 @@ use std::collections::VecDeque;
 use crate::{
     Error,
     env::ApiKey,
-    streaming::wire::{ControlMessage, Request, StreamError},
+    streaming::{
+        buffer::DEFAULT_CAPACITY,
+        health::{ConnectionHealth, HealthMonitor, HeartbeatConfig},
+        message_stream::CommandKind,
+        wire::{ControlMessage, Request, StreamError},
+    },
 };
 
 macro_rules! info {
@@ -50,6 +55,7 @@ pub trait StreamProtocol: sealed::Sealed + 'static {
         + Default
         + Clone
         + Send
+        + Sync
         + std::fmt::Debug
         + 'static;
 
@@ -78,6 +84,13 @@ pub trait StreamProtocol: sealed::Sealed + 'static {
 pub(crate) trait StreamProtocolCodec: StreamProtocol {
     type Codec: socketeer::Codec<Tx = Request<Self::Subscriptions>, Rx = Vec<Self::Message>>
         + Default;
+
+    /// The symbol of `message` when it is a quote that a newer quote for
+    /// the same symbol may replace under
+    /// [`BackpressurePolicy::ConflateQuotes`](crate::streaming::BackpressurePolicy::ConflateQuotes).
+    fn quote_symbol(_message: &Self::Message) -> Option<&str> {
+        None
+    }
 }
 
 type StreamSocket<P> = Socketeer<<P as StreamProtocolCodec>::Codec>;
@@ -96,6 +109,16 @@ pub struct StreamingClient<P: StreamProtocol + StreamProtocolCodec> {
     websocket: StreamSocket<P>,
     messages: VecDeque<P::Message>,
     subscriptions: P::Subscriptions,
+    health: HealthMonitor,
+    /// Subscription confirmations still owed for liveness probes, which
+    /// must not be mistaken for the answer to a caller's subscribe.
+    probe_confirmations: usize,
+    /// Most messages held in `messages` while waiting for a subscription
+    /// confirmation; older ones are discarded beyond this.
+    pending_capacity: usize,
+    /// Messages discarded because `pending_capacity` was reached, not yet
+    /// collected by [`Self::take_pending_dropped`].
+    pending_dropped: u64,
 }
 
 #[allow(
@@ -106,11 +129,25 @@ impl<P: StreamProtocol + StreamProtocolCodec> StreamingClient<P> {
     /// Connect to `url` and complete the connect/auth handshake using
     /// `api_key`.
     pub(crate) async fn connect(api_key: ApiKey, url: &str) -> Result<Self, Error> {
-        let websocket = StreamSocket::<P>::connect(url).await?;
+        Self::connect_with_heartbeat(api_key, url, HeartbeatConfig::default()).await
+    }
+
+    /// Connect to `url` with explicit liveness settings and complete the
+    /// connect/auth handshake using `api_key`.
+    pub(crate) async fn connect_with_heartbeat(
+        api_key: ApiKey,
+        url: &str,
+        heartbeat: HeartbeatConfig,
+    ) -> Result<Self, Error> {
+        let websocket = StreamSocket::<P>::connect_with(url, heartbeat.connect_options()).await?;
         let mut client = Self {
             websocket,
             messages: VecDeque::new(),
             subscriptions: P::Subscriptions::default(),
+            health: HealthMonitor::new(heartbeat),
+            probe_confirmations: 0,
+            pending_capacity: DEFAULT_CAPACITY,
+            pending_dropped: 0,
         };
 
         let connection_confirmation = client.next_message_internal().await?;
@@ -146,6 +183,10 @@ impl<P: StreamProtocol + StreamProtocolCodec> StreamingClient<P> {
     /// [`Error::StreamingError`] rather than delivering it as a message —
     /// so a server-side error can't be silently dropped by a caller that
     /// only matches on data variants. The WebSocket is left open.
+    ///
+    /// With an idle timeout configured via [`HeartbeatConfig`], a connection
+    /// that stops answering returns [`Error::StreamingStale`] instead of
+    /// waiting forever.
     pub async fn next_message(&mut self) -> Result<P::Message, Error> {
         loop {
             let incoming = self.next_message_internal().await?;
@@ -153,8 +194,13 @@ impl<P: StreamProtocol + StreamProtocolCodec> StreamingClient<P> {
                 error!("Alpaca streaming error: {stream_error:?}");
                 return Err(Error::StreamingError(stream_error.clone()));
             }
-            if let Some(message) = self.handle_subscription_update(incoming) {
-                return Ok(message);
+            match self.handle_subscription_update(incoming) {
+                Some(message) => return Ok(message),
+                None => {
+                    // A confirmation read here answers a probe, never a
+                    // caller's subscribe (those are awaited inline).
+                    self.probe_confirmations = self.probe_confirmations.saturating_sub(1);
+                }
             }
         }
     }
@@ -185,6 +231,30 @@ impl<P: StreamProtocol + StreamProtocolCodec> StreamingClient<P> {
         Ok(self.subscriptions.clone())
     }
 
+    /// Carry out a subscription request queued through a
+    /// [`SubscriptionHandle`](crate::streaming::SubscriptionHandle).
+    pub(crate) async fn apply_subscription(
+        &mut self,
+        kind: CommandKind,
+        subscriptions: &P::Subscriptions,
+    ) -> Result<P::Subscriptions, Error> {
+        match kind {
+            CommandKind::Subscribe => self.add_subscriptions(subscriptions).await,
+            CommandKind::Unsubscribe => self.remove_subscriptions(subscriptions).await,
+        }
+    }
+
+    /// The subscription list most recently confirmed by the server.
+    pub fn subscriptions(&self) -> &P::Subscriptions {
+        &self.subscriptions
+    }
+
+    /// Connection health counters: when the last frame arrived, how many
+    /// liveness probes were sent and how long the last one took to answer.
+    pub fn health(&self) -> &ConnectionHealth {
+        self.health.stats()
+    }
+
     /// Close the WebSocket connection and shut down the client.
     pub async fn shut_down(self) -> Result<(), Error> {
         self.websocket.close_connection().await?;
@@ -194,41 +264,53 @@ impl<P: StreamProtocol + StreamProtocolCodec> StreamingClient<P> {
     async fn await_subscription_update_message(&mut self) -> Result<(), Error> {
         let mut received = false;
         while !received {
-            match self.websocket.next_message().await {
-                Ok(messages) => {
-                    for message in messages {
-                        if let Some(stream_error) = P::stream_error(&message) {
-                            error!("Alpaca rejected the subscription request: {stream_error:?}");
-                            return Err(Error::StreamingSubscribe(stream_error.clone()));
-                        }
-                        match self.handle_subscription_update(message) {
-                            None => {
-                                received = true;
-                            }
-                            Some(message) => {
-                                self.messages.push_back(message);
-                            }
-                        }
-                    }
+            for message in self.recv_frame().await? {
+                if let Some(stream_error) = P::stream_error(&message) {
+                    error!("Alpaca rejected the subscription request: {stream_error:?}");
+                    return Err(Error::StreamingSubscribe(stream_error.clone()));
                 }
-                Err(e) => {
-                    error!("Error retrieving next message: {e:?}");
-                    return Err(Error::from(e));
+                match self.handle_subscription_update(message) {
+                    None if self.probe_confirmations > 0 => {
+                        self.probe_confirmations -= 1;
+                    }
+                    None => {
+                        received = true;
+                    }
+                    Some(message) => {
+                        self.queue_pending(message);
+                    }
                 }
             }
         }
         Ok(())
     }
 
+    /// Hold a data message that arrived ahead of a subscription
+    /// confirmation, discarding the oldest held message once
+    /// `pending_capacity` is reached.
+    fn queue_pending(&mut self, message: P::Message) {
+        if self.messages.len() >= self.pending_capacity {
+            self.messages.pop_front();
+            self.pending_dropped += 1;
+        }
+        self.messages.push_back(message);
+    }
+
+    /// Bound the queue of messages held while waiting for a subscription
+    /// confirmation. Values below one are treated as one.
+    pub(crate) fn set_pending_capacity(&mut self, capacity: usize) {
+        self.pending_capacity = capacity.max(1);
+    }
+
+    /// Number of held messages discarded since the last call.
+    pub(crate) fn take_pending_dropped(&mut self) -> u64 {
+        std::mem::take(&mut self.pending_dropped)
+    }
+
     async fn next_message_internal(&mut self) -> Result<P::Message, Error> {
         while self.messages.is_empty() {
-            match self.websocket.next_message().await {
-                Ok(messages) => self.messages.extend(messages),
-                Err(e) => {
-                    error!("Error retrieving next message: {e:?}");
-                    return Err(Error::from(e));
-                }
-            }
+            let frame = self.recv_frame().await?;
+            self.messages.extend(frame);
         }
         Ok(self
             .messages
@@ -236,6 +318,62 @@ impl<P: StreamProtocol + StreamProtocolCodec> StreamingClient<P> {
             .expect("loop above guarantees the queue is non-empty"))
     }
 
+    /// Read the next frame off the socket, sending a liveness probe when the
+    /// connection has been idle for the configured timeout and failing with
+    /// [`Error::StreamingStale`] if the probe goes unanswered.
+    async fn recv_frame(&mut self) -> Result<Vec<P::Message>, Error> {
+        loop {
+            let received = match self.health.deadline() {
+                None => self.websocket.next_message().await,
+                Some(deadline) => {
+                    tokio::select! {
+                        received = self.websocket.next_message() => received,
+                        () = tokio::time::sleep_until(deadline) => {
+                            self.send_probe().await?;
+                            continue;
+                        }
+                    }
+                }
+            };
+            return match received {
+                Ok(frame) => {
+                    self.health.record_frame();
+                    Ok(frame)
+                }
+                Err(e) => {
+                    error!("Error retrieving next message: {e:?}");
+                    Err(Error::from(e))
+                }
+            };
+        }
+    }
+
+    /// Re-send the current subscription list. Subscribing is idempotent, and
+    /// the server answers with an unchanged subscription confirmation, which
+    /// makes it a cheap request/response liveness check.
+    ///
+    /// The probe is counted before it is sent, so a send cancelled part way
+    /// through still has its confirmation swallowed if it reached the
+    /// server.
+    async fn send_probe(&mut self) -> Result<(), Error> {
+        match self.health.deadline_elapsed() {
+            Ok(()) => {}
+            Err(stale) => {
+                error!("Alpaca streaming connection is stale: {stale}");
+                return Err(stale);
+            }
+        }
+        self.probe_confirmations += 1;
+        let sent = self
+            .websocket
+            .send(Request::Subscribe(self.subscriptions.clone()))
+            .await;
+        if sent.is_err() {
+            self.probe_confirmations -= 1;
+        }
+        Ok(sent?)
+    }
+
     fn handle_subscription_update(&mut self, message: P::Message) -> Option<P::Message> {
         match P::take_subscription_update(message) {
             Ok(updated) => {
@@ -410,4 +548,110 @@ mod tests {
             other => panic!("expected Err(StreamingError), got {other:?}"),
         }
     }
+    /// A connection that goes silent after the handshake must surface
+    /// `Err(StreamingStale)` once the idle probe goes unanswered, instead of
+    /// leaving `next_message` parked forever on a half-open socket.
+    #[tokio::test]
+    async fn next_message_reports_stale_connection() {
+        use crate::streaming::HeartbeatConfig;
+        use std::time::Duration;
+
+        // Complete the handshake, then read (and ignore) everything the
+        // client sends — including the liveness probe — without replying.
+        async fn silent_after_auth(
+            mut ws: WebSocketStreamType,
+        ) -> Result<bool, tungstenite::Error> {
+            ws.send(Message::text(CONNECTED)).await?;
+            let _auth_request = ws.next().await;
+            ws.send(Message::text(r#"[{"T":"success","msg":"authenticated"}]"#))
+                .await?;
+            while let Some(Ok(message)) = ws.next().await {
+                if message.is_close() {
+                    break;
+                }
+            }
+            Ok(true)
+        }
+
+        let address = get_mock_address(silent_after_auth).await;
+        let url = format!("ws://{address}");
+        let heartbeat = HeartbeatConfig::new()
+            .ping_interval(None)
+            .idle_timeout(Duration::from_millis(100))
+            .pong_timeout(Duration::from_millis(100));
+        let mut client = StreamingClient::<StockProtocol>::connect_with_heartbeat(
+            ApiKey::new("k", "s"),
+            &url,
+            heartbeat,
+        )
+        .await
+        .expect("handshake succeeds");
+
+        let outcome = tokio::time::timeout(Duration::from_secs(5), client.next_message())
+            .await
+            .expect("next_message must give up on a silent connection");
+
+        assert!(
+            matches!(outcome, Err(Error::StreamingStale(_))),
+            "expected Err(StreamingStale), got {outcome:?}"
+        );
+        assert_eq!(client.health().probes_sent, 1);
+    }
+
+    /// A probe the server answers keeps the connection alive, records the
+    /// round trip, and the probe's subscription confirmation is swallowed
+    /// rather than delivered as a message.
+    #[tokio::test]
+    async fn answered_probe_keeps_connection_alive() {
+        use crate::streaming::{HeartbeatConfig, StockStreamMessage};
+        use std::time::Duration;
+
+        async fn answer_probe(mut ws: WebSocketStreamType) -> Result<bool, tungstenite::Error> {
+            ws.send(Message::text(CONNECTED)).await?;
+            let _auth_request = ws.next().await;
+            ws.send(Message::text(r#"[{"T":"success","msg":"authenticated"}]"#))
+                .await?;
+            let _probe = ws.next().await;
+            ws.send(Message::text(r#"[{"T":"subscription","trades":[]}]"#))
+                .await?;
+            ws.send(Message::text(
+                r#"[{"T":"t","S":"AAPL","i":1,"x":"V","p":150.0,"s":100,"t":"2024-01-02T15:30:00Z","z":"C"}]"#,
+            ))
+            .await?;
+            while let Some(Ok(message)) = ws.next().await {
+                if message.is_close() {
+                    break;
+                }
+            }
+            Ok(true)
+        }
+
+        let address = get_mock_address(answer_probe).await;
+        let url = format!("ws://{address}");
+        let heartbeat = HeartbeatConfig::new()
+            .ping_interval(None)
+            .idle_timeout(Duration::from_millis(100))
+            .pong_timeout(Duration::from_secs(5));
+        let mut client = StreamingClient::<StockProtocol>::connect_with_heartbeat(
+            ApiKey::new("k", "s"),
+            &url,
+            heartbeat,
+        )
+        .await
+        .expect("handshake succeeds");
+
+        let message = tokio::time::timeout(Duration::from_secs(5), client.next_message())
+            .await
+            .expect("next_message returns once the server answers")
+            .expect("answered probe is not an error");
+
+        assert!(
+            matches!(message, StockStreamMessage::Trade(_)),
+            "expected the trade after the probe confirmation, got {message:?}"
+        );
+        let health = client.health();
+        assert_eq!(health.probes_sent, 1);
+        assert!(health.last_round_trip.is_some());
+        assert!(health.last_message_at.is_some());
+    }
 }
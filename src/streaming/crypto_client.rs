@@ -4,7 +4,9 @@ use crate::{
     AccountType, CryptoFeed, Error,
     env::ApiKey,
     streaming::{
+        broadcast::{Channel, FanOutProtocol, Topic, push_topics, symbols_for},
         client::{StreamProtocol, StreamProtocolCodec, StreamingClient, sealed},
+        health::HeartbeatConfig,
         messages::{CryptoStreamMessage, CryptoSubscriptionList},
         wire::{ControlMessage, Request, StreamError},
     },
@@ -41,6 +43,67 @@ impl StreamProtocol for CryptoProtocol {
 
 impl StreamProtocolCodec for CryptoProtocol {
     type Codec = JsonCodec<Vec<CryptoStreamMessage>, Request<CryptoSubscriptionList>>;
+
+    fn quote_symbol(message: &CryptoStreamMessage) -> Option<&str> {
+        match message {
+            CryptoStreamMessage::Quote(quote) => Some(&quote.symbol),
+            _ => None,
+        }
+    }
+}
+
+impl FanOutProtocol for CryptoProtocol {
+    fn topics(subscriptions: &CryptoSubscriptionList) -> Vec<Topic> {
+        let mut topics = Vec::new();
+        push_topics(&mut topics, Channel::Trades, subscriptions.trades.as_ref());
+        push_topics(&mut topics, Channel::Quotes, subscriptions.quotes.as_ref());
+        push_topics(&mut topics, Channel::Bars, subscriptions.bars.as_ref());
+        push_topics(
+            &mut topics,
+            Channel::DailyBars,
+            subscriptions.daily_bars.as_ref(),
+        );
+        push_topics(
+            &mut topics,
+            Channel::UpdatedBars,
+            subscriptions.updated_bars.as_ref(),
+        );
+        push_topics(
+            &mut topics,
+            Channel::Orderbooks,
+            subscriptions.orderbooks.as_ref(),
+        );
+        topics
+    }
+
+    fn subscriptions(topics: &[Topic]) -> CryptoSubscriptionList {
+        CryptoSubscriptionList {
+            trades: symbols_for(topics, Channel::Trades),
+            quotes: symbols_for(topics, Channel::Quotes),
+            bars: symbols_for(topics, Channel::Bars),
+            daily_bars: symbols_for(topics, Channel::DailyBars),
+            updated_bars: symbols_for(topics, Channel::UpdatedBars),
+            orderbooks: symbols_for(topics, Channel::Orderbooks),
+        }
+    }
+
+    fn topic(message: &CryptoStreamMessage) -> Option<(Channel, &str)> {
+        match message {
+            CryptoStreamMessage::Trade(trade) => Some((Channel::Trades, &trade.symbol)),
+            CryptoStreamMessage::Quote(quote) => Some((Channel::Quotes, &quote.symbol)),
+            CryptoStreamMessage::Bar(bar) => Some((Channel::Bars, &bar.symbol)),
+            CryptoStreamMessage::DailyBar(bar) => Some((Channel::DailyBars, &bar.symbol)),
+            CryptoStreamMessage::UpdatedBar(bar) => Some((Channel::UpdatedBars, &bar.symbol)),
+            CryptoStreamMessage::Orderbook(book) => Some((Channel::Orderbooks, &book.symbol)),
+            CryptoStreamMessage::Control { .. }
+            | CryptoStreamMessage::Error(_)
+            | CryptoStreamMessage::Subscription(_) => None,
+        }
+    }
+
+    fn error_message(error: StreamError) -> CryptoStreamMessage {
+        CryptoStreamMessage::Error(error)
+    }
 }
 
 /// Client for streaming real-time crypto market data over a WebSocket connection.
@@ -68,6 +131,17 @@ impl StreamingCryptoClient {
     ) -> Result<Self, Error> {
         Self::connect(api_key, feed.url(account_type)).await
     }
+
+    /// Connect like [`Self::new_with_credentials`], with explicit liveness
+    /// settings for detecting a stalled connection.
+    pub async fn new_with_heartbeat(
+        account_type: AccountType,
+        feed: CryptoFeed,
+        api_key: ApiKey,
+        heartbeat: HeartbeatConfig,
+    ) -> Result<Self, Error> {
+        Self::connect_with_heartbeat(api_key, feed.url(account_type), heartbeat).await
+    }
 }
 
 #[cfg(test)]
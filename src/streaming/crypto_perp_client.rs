@@ -0,0 +1,180 @@
+use socketeer::JsonCodec;
+
+use crate::{
+    AccountType, CryptoPerpFeed, Error,
+    env::ApiKey,
+    streaming::{
+        broadcast::{Channel, FanOutProtocol, Topic, push_topics, symbols_for},
+        client::{StreamProtocol, StreamProtocolCodec, StreamingClient, sealed},
+        health::HeartbeatConfig,
+        messages::{CryptoPerpStreamMessage, CryptoPerpSubscriptionList},
+        wire::{ControlMessage, Request, StreamError},
+    },
+};
+
+/// Marker type wiring [`CryptoPerpStreamMessage`] /
+/// [`CryptoPerpSubscriptionList`] into the shared [`StreamingClient`].
+#[derive(Debug)]
+pub struct CryptoPerpProtocol;
+
+impl sealed::Sealed for CryptoPerpProtocol {}
+
+impl StreamProtocol for CryptoPerpProtocol {
+    type Message = CryptoPerpStreamMessage;
+    type Subscriptions = CryptoPerpSubscriptionList;
+
+    fn control(message: &Self::Message) -> Option<&ControlMessage> {
+        message.control()
+    }
+
+    fn stream_error(message: &Self::Message) -> Option<&StreamError> {
+        message.stream_error()
+    }
+
+    fn take_subscription_update(
+        message: Self::Message,
+    ) -> Result<Self::Subscriptions, Self::Message> {
+        match message {
+            CryptoPerpStreamMessage::Subscription(updated) => Ok(updated),
+            other => Err(other),
+        }
+    }
+}
+
+impl StreamProtocolCodec for CryptoPerpProtocol {
+    type Codec = JsonCodec<Vec<CryptoPerpStreamMessage>, Request<CryptoPerpSubscriptionList>>;
+
+    fn quote_symbol(message: &CryptoPerpStreamMessage) -> Option<&str> {
+        match message {
+            CryptoPerpStreamMessage::Quote(quote) => Some(&quote.symbol),
+            _ => None,
+        }
+    }
+}
+
+impl FanOutProtocol for CryptoPerpProtocol {
+    fn topics(subscriptions: &CryptoPerpSubscriptionList) -> Vec<Topic> {
+        let mut topics = Vec::new();
+        push_topics(&mut topics, Channel::Trades, subscriptions.trades.as_ref());
+        push_topics(&mut topics, Channel::Quotes, subscriptions.quotes.as_ref());
+        push_topics(&mut topics, Channel::Bars, subscriptions.bars.as_ref());
+        push_topics(
+            &mut topics,
+            Channel::DailyBars,
+            subscriptions.daily_bars.as_ref(),
+        );
+        push_topics(
+            &mut topics,
+            Channel::UpdatedBars,
+            subscriptions.updated_bars.as_ref(),
+        );
+        push_topics(
+            &mut topics,
+            Channel::Orderbooks,
+            subscriptions.orderbooks.as_ref(),
+        );
+        push_topics(
+            &mut topics,
+            Channel::Pricing,
+            subscriptions.pricing.as_ref(),
+        );
+        topics
+    }
+
+    fn subscriptions(topics: &[Topic]) -> CryptoPerpSubscriptionList {
+        CryptoPerpSubscriptionList {
+            trades: symbols_for(topics, Channel::Trades),
+            quotes: symbols_for(topics, Channel::Quotes),
+            bars: symbols_for(topics, Channel::Bars),
+            daily_bars: symbols_for(topics, Channel::DailyBars),
+            updated_bars: symbols_for(topics, Channel::UpdatedBars),
+            orderbooks: symbols_for(topics, Channel::Orderbooks),
+            pricing: symbols_for(topics, Channel::Pricing),
+        }
+    }
+
+    fn topic(message: &CryptoPerpStreamMessage) -> Option<(Channel, &str)> {
+        match message {
+            CryptoPerpStreamMessage::Trade(trade) => Some((Channel::Trades, &trade.symbol)),
+            CryptoPerpStreamMessage::Quote(quote) => Some((Channel::Quotes, &quote.symbol)),
+            CryptoPerpStreamMessage::Bar(bar) => Some((Channel::Bars, &bar.symbol)),
+            CryptoPerpStreamMessage::DailyBar(bar) => Some((Channel::DailyBars, &bar.symbol)),
+            CryptoPerpStreamMessage::UpdatedBar(bar) => Some((Channel::UpdatedBars, &bar.symbol)),
+            CryptoPerpStreamMessage::Orderbook(book) => Some((Channel::Orderbooks, &book.symbol)),
+            CryptoPerpStreamMessage::Pricing(pricing) => Some((Channel::Pricing, &pricing.symbol)),
+            CryptoPerpStreamMessage::Control { .. }
+            | CryptoPerpStreamMessage::Error(_)
+            | CryptoPerpStreamMessage::Subscription(_) => None,
+        }
+    }
+
+    fn error_message(error: StreamError) -> CryptoPerpStreamMessage {
+        CryptoPerpStreamMessage::Error(error)
+    }
+}
+
+/// Client for streaming real-time crypto perpetual futures market data over
+/// a WebSocket connection.
+pub type StreamingCryptoPerpClient = StreamingClient<CryptoPerpProtocol>;
+
+impl StreamingCryptoPerpClient {
+    /// Connect to the chosen [`CryptoPerpFeed`] using credentials loaded from
+    /// the environment for `account_type`.
+    ///
+    /// As with spot crypto, every feed routes to the production wss host;
+    /// the account type only selects which credential pair is used to
+    /// authenticate.
+    pub async fn new(account_type: AccountType, feed: CryptoPerpFeed) -> Result<Self, Error> {
+        let api_key = ApiKey::from_env(&account_type)?;
+        Self::new_with_credentials(account_type, feed, api_key).await
+    }
+
+    /// Connect to the chosen [`CryptoPerpFeed`] using explicitly supplied
+    /// credentials.
+    pub async fn new_with_credentials(
+        account_type: AccountType,
+        feed: CryptoPerpFeed,
+        api_key: ApiKey,
+    ) -> Result<Self, Error> {
+        Self::connect(api_key, feed.url(account_type)).await
+    }
+
+    /// Connect like [`Self::new_with_credentials`], with explicit liveness
+    /// settings for detecting a stalled connection.
+    pub async fn new_with_heartbeat(
+        account_type: AccountType,
+        feed: CryptoPerpFeed,
+        api_key: ApiKey,
+        heartbeat: HeartbeatConfig,
+    ) -> Result<Self, Error> {
+        Self::connect_with_heartbeat(api_key, feed.url(account_type), heartbeat).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn perp_feed_targets_production_host_for_both_accounts() {
+        for account in [AccountType::Live, AccountType::Paper] {
+            assert_eq!(
+                CryptoPerpFeed::Global.url(account),
+                "wss://stream.data.alpaca.markets/v1beta1/crypto-perps/global",
+            );
+        }
+    }
+
+    #[test]
+    fn pricing_topics_round_trip_through_subscriptions() {
+        let subscriptions = CryptoPerpSubscriptionList::new()
+            .add_pricing("BTC-PERP")
+            .add_quotes("ETH-PERP");
+        let topics = CryptoPerpProtocol::topics(&subscriptions);
+        assert!(topics.contains(&(Channel::Pricing, "BTC-PERP".to_string())));
+        let rebuilt = CryptoPerpProtocol::subscriptions(&topics);
+        assert_eq!(rebuilt.pricing, Some(vec!["BTC-PERP".to_string()]));
+        assert_eq!(rebuilt.quotes, Some(vec!["ETH-PERP".to_string()]));
+        assert!(rebuilt.trades.is_none());
+    }
+}
@@ -0,0 +1,237 @@
+use chrono::{DateTime, Utc};
+use std::time::Duration;
+use tokio::time::Instant;
+
+use crate::Error;
+
+/// Default cadence for WebSocket-level pings while the connection is idle.
+/// Matches the transport's own default so opting into a
+/// [`HeartbeatConfig`] doesn't change ping behaviour unless asked to.
+const DEFAULT_PING_INTERVAL: Duration = Duration::from_secs(2);
+/// Default grace period for the server to answer a liveness probe.
+const DEFAULT_PONG_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Liveness settings for a streaming connection.
+///
+/// A half-open TCP connection never errors on its own: the socket simply
+/// stops delivering frames. The streaming clients guard against that with
+/// two layers:
+///
+/// - **Pings** — WebSocket ping frames sent every
+///   [`ping_interval`](Self::ping_interval) while the socket is otherwise
+///   quiet. They keep NAT / load-balancer state alive and make a dead peer
+///   surface as a write error sooner.
+/// - **Idle probes** — once no frame has arrived for
+///   [`idle_timeout`](Self::idle_timeout), the client sends an
+///   application-level probe the server is obliged to answer (the current
+///   subscription list on market-data feeds, a repeated `listen` on the
+///   trade-updates stream). If nothing arrives within
+///   [`pong_timeout`](Self::pong_timeout) of the probe, the read returns
+///   [`Error::StreamingStale`] so the caller can reconnect.
+///
+/// The default config keeps the transport's 2 s ping cadence and leaves the
+/// idle probe disabled, which is the behaviour of the plain `new`
+/// constructors.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct HeartbeatConfig {
+    /// Interval between WebSocket pings on a quiet connection. `None`
+    /// disables pings entirely.
+    pub ping_interval: Option<Duration>,
+    /// How long the connection may go without any inbound frame before a
+    /// liveness probe is sent. `None` disables the probe (and with it stale
+    /// detection).
+    pub idle_timeout: Option<Duration>,
+    /// How long to wait for any inbound frame after a probe before declaring
+    /// the connection stale.
+    pub pong_timeout: Duration,
+}
+
+impl Default for HeartbeatConfig {
+    fn default() -> Self {
+        Self {
+            ping_interval: Some(DEFAULT_PING_INTERVAL),
+            idle_timeout: None,
+            pong_timeout: DEFAULT_PONG_TIMEOUT,
+        }
+    }
+}
+
+impl HeartbeatConfig {
+    /// Start from the defaults (2 s pings, idle probe disabled).
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the WebSocket ping interval. `None` disables pings.
+    #[must_use]
+    pub fn ping_interval(mut self, interval: Option<Duration>) -> Self {
+        self.ping_interval = interval;
+        self
+    }
+
+    /// Probe the server after `timeout` without inbound frames.
+    #[must_use]
+    pub fn idle_timeout(mut self, timeout: Duration) -> Self {
+        self.idle_timeout = Some(timeout);
+        self
+    }
+
+    /// Declare the connection stale if a probe goes unanswered for
+    /// `timeout`.
+    #[must_use]
+    pub fn pong_timeout(mut self, timeout: Duration) -> Self {
+        self.pong_timeout = timeout;
+        self
+    }
+
+    pub(crate) fn connect_options(&self) -> socketeer::ConnectOptions {
+        socketeer::ConnectOptions::builder()
+            .keepalive_interval(self.ping_interval)
+            .build()
+    }
+}
+
+/// Snapshot of a streaming connection's health counters, returned by the
+/// clients' `health()` accessors.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ConnectionHealth {
+    /// When the WebSocket connection was established.
+    pub connected_at: DateTime<Utc>,
+    /// When the most recent inbound frame arrived, if any.
+    pub last_message_at: Option<DateTime<Utc>>,
+    /// Number of inbound frames received since connecting.
+    pub frames_received: u64,
+    /// Number of liveness probes sent since connecting.
+    pub probes_sent: u64,
+    /// Round-trip time of the most recently answered probe.
+    pub last_round_trip: Option<Duration>,
+}
+
+/// Crate-private bookkeeping shared by the streaming clients: tracks the
+/// last inbound activity, the outstanding probe (if any) and the public
+/// [`ConnectionHealth`] counters.
+#[derive(Debug)]
+pub(crate) struct HealthMonitor {
+    config: HeartbeatConfig,
+    last_activity: Instant,
+    probe_sent_at: Option<Instant>,
+    stats: ConnectionHealth,
+}
+
+impl HealthMonitor {
+    pub(crate) fn new(config: HeartbeatConfig) -> Self {
+        Self {
+            config,
+            last_activity: Instant::now(),
+            probe_sent_at: None,
+            stats: ConnectionHealth {
+                connected_at: Utc::now(),
+                last_message_at: None,
+                frames_received: 0,
+                probes_sent: 0,
+                last_round_trip: None,
+            },
+        }
+    }
+
+    pub(crate) fn stats(&self) -> &ConnectionHealth {
+        &self.stats
+    }
+
+    /// The instant at which the read loop must wake up to either send a
+    /// probe or give up on an unanswered one. `None` when idle probing is
+    /// disabled.
+    pub(crate) fn deadline(&self) -> Option<Instant> {
+        match self.probe_sent_at {
+            Some(sent_at) => Some(sent_at + self.config.pong_timeout),
+            None => self
+                .config
+                .idle_timeout
+                .map(|timeout| self.last_activity + timeout),
+        }
+    }
+
+    /// Record an inbound frame. Any frame proves the connection is alive, so
+    /// it also answers an outstanding probe.
+    pub(crate) fn record_frame(&mut self) {
+        let now = Instant::now();
+        if let Some(sent_at) = self.probe_sent_at.take() {
+            self.stats.last_round_trip = Some(now - sent_at);
+        }
+        self.last_activity = now;
+        self.stats.last_message_at = Some(Utc::now());
+        self.stats.frames_received += 1;
+    }
+
+    /// Called when [`Self::deadline`] elapses. Returns `Ok(())` when the
+    /// caller should send a probe, or [`Error::StreamingStale`] when the
+    /// previous probe went unanswered.
+    pub(crate) fn deadline_elapsed(&mut self) -> Result<(), Error> {
+        let now = Instant::now();
+        if self.probe_sent_at.is_some() {
+            return Err(Error::StreamingStale(now - self.last_activity));
+        }
+        self.probe_sent_at = Some(now);
+        self.stats.probes_sent += 1;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_config_disables_idle_probe() {
+        let config = HeartbeatConfig::default();
+        assert_eq!(config.ping_interval, Some(DEFAULT_PING_INTERVAL));
+        assert_eq!(config.idle_timeout, None);
+        assert!(HealthMonitor::new(config).deadline().is_none());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn unanswered_probe_reports_stale() {
+        let config = HeartbeatConfig::new()
+            .idle_timeout(Duration::from_secs(5))
+            .pong_timeout(Duration::from_secs(1));
+        let mut monitor = HealthMonitor::new(config);
+
+        tokio::time::advance(Duration::from_secs(5)).await;
+        monitor
+            .deadline_elapsed()
+            .expect("first deadline sends a probe");
+        assert_eq!(monitor.stats().probes_sent, 1);
+
+        tokio::time::advance(Duration::from_secs(1)).await;
+        match monitor.deadline_elapsed() {
+            Err(Error::StreamingStale(silent_for)) => {
+                assert_eq!(silent_for, Duration::from_secs(6));
+            }
+            other => panic!("expected Err(StreamingStale), got {other:?}"),
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn answered_probe_records_round_trip() {
+        let config = HeartbeatConfig::new().idle_timeout(Duration::from_secs(5));
+        let mut monitor = HealthMonitor::new(config);
+
+        tokio::time::advance(Duration::from_secs(5)).await;
+        monitor.deadline_elapsed().unwrap();
+        tokio::time::advance(Duration::from_millis(40)).await;
+        monitor.record_frame();
+
+        let stats = monitor.stats();
+        assert_eq!(stats.last_round_trip, Some(Duration::from_millis(40)));
+        assert_eq!(stats.frames_received, 1);
+        assert!(stats.last_message_at.is_some());
+        // Back to idle tracking: the next deadline is a fresh idle window.
+        assert_eq!(
+            monitor.deadline(),
+            Some(Instant::now() + Duration::from_secs(5))
+        );
+    }
+}
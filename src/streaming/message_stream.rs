@@ -0,0 +1,473 @@
+use futures::{Stream, stream::BoxStream};
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tokio::sync::{mpsc, oneshot, watch};
+
+use crate::{
+    Error,
+    streaming::{
+        TradeUpdate, TradingUpdatesClient,
+        client::{StreamProtocol, StreamProtocolCodec, StreamingClient},
+    },
+};
+
+/// A request forwarded from a [`SubscriptionHandle`] (or a split receiver)
+/// to whoever reads the socket, with the channel its outcome is reported
+/// back on.
+pub(crate) enum Command<P: StreamProtocol> {
+    Subscription {
+        kind: CommandKind,
+        subscriptions: P::Subscriptions,
+        reply: oneshot::Sender<Result<P::Subscriptions, Error>>,
+    },
+    Shutdown(oneshot::Sender<Result<(), Error>>),
+}
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum CommandKind {
+    Subscribe,
+    Unsubscribe,
+}
+
+/// The error reported to a requester when the socket reader has gone away
+/// before answering.
+pub(crate) fn reader_gone() -> Error {
+    Error::from(socketeer::Error::WebsocketClosed)
+}
+
+/// Cloneable handle for changing the subscriptions of a streaming client
+/// after it has been turned into a [`MessageStream`] or split with
+/// [`StreamingClient::split`].
+///
+/// Requests are queued to whoever reads the socket and answered once the
+/// server confirms (or rejects) them, so the handle can live in a different
+/// task from the consumer. Concurrent requests from several clones are
+/// carried out one at a time, in the order they were made.
+#[allow(
+    private_bounds,
+    reason = "See StreamingClient — bound is sealed on purpose."
+)]
+pub struct SubscriptionHandle<P: StreamProtocol + StreamProtocolCodec> {
+    commands: mpsc::UnboundedSender<Command<P>>,
+    subscriptions: watch::Receiver<P::Subscriptions>,
+}
+
+impl<P: StreamProtocol + StreamProtocolCodec> Clone for SubscriptionHandle<P> {
+    fn clone(&self) -> Self {
+        Self {
+            commands: self.commands.clone(),
+            subscriptions: self.subscriptions.clone(),
+        }
+    }
+}
+
+impl<P: StreamProtocol + StreamProtocolCodec> std::fmt::Debug for SubscriptionHandle<P> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SubscriptionHandle").finish_non_exhaustive()
+    }
+}
+
+#[allow(
+    private_bounds,
+    reason = "See StreamingClient — bound is sealed on purpose."
+)]
+impl<P: StreamProtocol + StreamProtocolCodec> SubscriptionHandle<P> {
+    /// Subscribe to additional channels, returning the server-confirmed
+    /// subscription list.
+    ///
+    /// Returns [`Error::StreamingSubscribe`] if the server rejects the
+    /// request, or a websocket error if the connection has gone away.
+    pub async fn add_subscriptions(
+        &self,
+        subscriptions: &P::Subscriptions,
+    ) -> Result<P::Subscriptions, Error> {
+        self.request(CommandKind::Subscribe, subscriptions).await
+    }
+
+    /// Unsubscribe from channels, returning the server-confirmed
+    /// subscription list.
+    pub async fn remove_subscriptions(
+        &self,
+        subscriptions: &P::Subscriptions,
+    ) -> Result<P::Subscriptions, Error> {
+        self.request(CommandKind::Unsubscribe, subscriptions).await
+    }
+
+    /// The subscription list most recently confirmed by the server.
+    pub fn current_subscriptions(&self) -> P::Subscriptions {
+        self.subscriptions.borrow().clone()
+    }
+
+    /// Whether the connection behind this handle is still being read.
+    /// Requests made once this returns `true` fail immediately.
+    pub fn is_closed(&self) -> bool {
+        self.commands.is_closed()
+    }
+
+    /// Ask the reader to close the connection and wait for the outcome.
+    pub(crate) async fn request_shutdown(&self) -> Result<(), Error> {
+        let (reply, response) = oneshot::channel();
+        self.commands
+            .send(Command::Shutdown(reply))
+            .map_err(|_| reader_gone())?;
+        response.await.map_err(|_| reader_gone())?
+    }
+
+    async fn request(
+        &self,
+        kind: CommandKind,
+        subscriptions: &P::Subscriptions,
+    ) -> Result<P::Subscriptions, Error> {
+        let (reply, response) = oneshot::channel();
+        self.commands
+            .send(Command::Subscription {
+                kind,
+                subscriptions: subscriptions.clone(),
+                reply,
+            })
+            .map_err(|_| reader_gone())?;
+        response.await.map_err(|_| reader_gone())?
+    }
+}
+
+/// Whether an error ends the stream. Server error envelopes leave the
+/// socket open; transport failures and stale connections do not.
+pub(crate) fn is_terminal(error: &Error) -> bool {
+    matches!(error, Error::Websocket(_) | Error::StreamingStale(_))
+}
+
+/// [`Stream`] of messages from a market-data feed, produced by
+/// [`StreamingClient::into_stream`].
+///
+/// Yields the same items as [`StreamingClient::next_message`]. A transport
+/// failure or [`Error::StreamingStale`] is yielded once and then the stream
+/// ends; server error envelopes ([`Error::StreamingError`]) are yielded
+/// without ending it. Subscription requests made through the paired
+/// [`SubscriptionHandle`] are serviced while the stream is being polled.
+#[must_use = "streams do nothing unless polled"]
+#[allow(
+    private_bounds,
+    reason = "See StreamingClient — bound is sealed on purpose."
+)]
+pub struct MessageStream<P: StreamProtocol + StreamProtocolCodec> {
+    inner: BoxStream<'static, Result<P::Message, Error>>,
+}
+
+impl<P: StreamProtocol + StreamProtocolCodec> std::fmt::Debug for MessageStream<P> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MessageStream").finish_non_exhaustive()
+    }
+}
+
+impl<P: StreamProtocol + StreamProtocolCodec> Stream for MessageStream<P> {
+    type Item = Result<P::Message, Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.inner.as_mut().poll_next(cx)
+    }
+}
+
+/// A client together with the receiving end of its command channel. Drives
+/// both [`MessageStream`] and the background task behind
+/// [`StreamingClient::split`].
+pub(crate) struct ClientState<P: StreamProtocol + StreamProtocolCodec> {
+    client: StreamingClient<P>,
+    commands: mpsc::UnboundedReceiver<Command<P>>,
+    subscriptions: watch::Sender<P::Subscriptions>,
+    commands_open: bool,
+}
+
+/// What the reader should do next.
+pub(crate) enum Step<P: StreamProtocol> {
+    /// Deliver a message (or a non-fatal / terminal error).
+    Message(Result<P::Message, Error>),
+    /// Close the socket and report the outcome on the channel.
+    Shutdown(oneshot::Sender<Result<(), Error>>),
+}
+
+enum Event<P: StreamProtocol + StreamProtocolCodec> {
+    Command(Option<Command<P>>),
+    Message(Result<P::Message, Error>),
+}
+
+#[allow(
+    private_bounds,
+    reason = "See StreamingClient — bound is sealed on purpose."
+)]
+impl<P: StreamProtocol + StreamProtocolCodec> StreamingClient<P> {
+    /// Convert the client into a [`Stream`] of messages plus a
+    /// [`SubscriptionHandle`] for changing subscriptions while the stream is
+    /// being consumed.
+    ///
+    /// The returned stream can be used with `StreamExt` combinators,
+    /// `tokio::select!`, or forwarded into a channel. Subscription requests
+    /// are only serviced while the stream is polled; use
+    /// [`split`](Self::split) when the socket should be read independently
+    /// of the consumer.
+    pub fn into_stream(self) -> (SubscriptionHandle<P>, MessageStream<P>) {
+        let (handle, state) = ClientState::new(self);
+        let inner = futures::stream::unfold(Some(state), |state| async move {
+            let mut state = state?;
+            match state.next_step().await {
+                Step::Message(message) if message.as_ref().is_err_and(is_terminal) => {
+                    Some((message, None))
+                }
+                Step::Message(message) => Some((message, Some(state))),
+                Step::Shutdown(reply) => {
+                    let _ = reply.send(state.shut_down().await);
+                    None
+                }
+            }
+        });
+        (
+            handle,
+            MessageStream {
+                inner: Box::pin(inner),
+            },
+        )
+    }
+}
+
+#[allow(
+    private_bounds,
+    reason = "See StreamingClient — bound is sealed on purpose."
+)]
+impl<P: StreamProtocol + StreamProtocolCodec> ClientState<P> {
+    pub(crate) fn new(client: StreamingClient<P>) -> (SubscriptionHandle<P>, Self) {
+        let (commands_tx, commands) = mpsc::unbounded_channel();
+        let (subscriptions, subscriptions_rx) = watch::channel(client.subscriptions().clone());
+        let handle = SubscriptionHandle {
+            commands: commands_tx,
+            subscriptions: subscriptions_rx,
+        };
+        let state = Self {
+            client,
+            commands,
+            subscriptions,
+            commands_open: true,
+        };
+        (handle, state)
+    }
+
+    /// Wait for the next message, servicing any subscription requests that
+    /// arrive in the meantime. `StreamingClient::next_message` only awaits
+    /// cancel-safe channel reads, so racing it against the command channel
+    /// never drops a frame.
+    pub(crate) async fn next_step(&mut self) -> Step<P> {
+        loop {
+            let event = if self.commands_open {
+                tokio::select! {
+                    biased;
+                    command = self.commands.recv() => Event::Command(command),
+                    message = self.client.next_message() => Event::Message(message),
+                }
+            } else {
+                Event::Message(self.client.next_message().await)
+            };
+            match event {
+                Event::Command(Some(command)) => {
+                    if let Some(reply) = self.handle_command(command).await {
+                        return Step::Shutdown(reply);
+                    }
+                }
+                Event::Command(None) => self.commands_open = false,
+                Event::Message(message) => return Step::Message(message),
+            }
+        }
+    }
+
+    /// Wait for the next request from a handle without reading messages.
+    /// Cancel-safe; never resolves once every handle has been dropped.
+    pub(crate) async fn next_command(&mut self) -> Command<P> {
+        while self.commands_open {
+            match self.commands.recv().await {
+                Some(command) => return command,
+                None => self.commands_open = false,
+            }
+        }
+        std::future::pending().await
+    }
+
+    /// Carry out a subscription request, or hand back the reply channel of
+    /// a shutdown request for the caller to act on.
+    pub(crate) async fn handle_command(
+        &mut self,
+        command: Command<P>,
+    ) -> Option<oneshot::Sender<Result<(), Error>>> {
+        match command {
+            Command::Subscription {
+                kind,
+                subscriptions,
+                reply,
+            } => {
+                let outcome = self.client.apply_subscription(kind, &subscriptions).await;
+                self.subscriptions
+                    .send_replace(self.client.subscriptions().clone());
+                // The requester may have given up waiting; nothing to do then.
+                let _ = reply.send(outcome);
+                None
+            }
+            Command::Shutdown(reply) => Some(reply),
+        }
+    }
+
+    /// Bound the messages held back while a subscription request awaits
+    /// its confirmation.
+    pub(crate) fn set_pending_capacity(&mut self, capacity: usize) {
+        self.client.set_pending_capacity(capacity);
+    }
+
+    /// Number of held-back messages discarded since the last call.
+    pub(crate) fn take_pending_dropped(&mut self) -> u64 {
+        self.client.take_pending_dropped()
+    }
+
+    /// Close the underlying connection.
+    pub(crate) async fn shut_down(self) -> Result<(), Error> {
+        self.client.shut_down().await
+    }
+}
+
+/// [`Stream`] of trade updates, produced by
+/// [`TradingUpdatesClient::into_stream`].
+///
+/// Yields the same items as [`TradingUpdatesClient::next_trade_update`]; the
+/// first error ends the stream, since every error on this feed means the
+/// connection is unusable.
+#[must_use = "streams do nothing unless polled"]
+pub struct TradeUpdateStream {
+    inner: BoxStream<'static, Result<TradeUpdate, Error>>,
+}
+
+impl std::fmt::Debug for TradeUpdateStream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TradeUpdateStream").finish_non_exhaustive()
+    }
+}
+
+impl Stream for TradeUpdateStream {
+    type Item = Result<TradeUpdate, Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.inner.as_mut().poll_next(cx)
+    }
+}
+
+impl TradingUpdatesClient {
+    /// Convert the client into a [`Stream`] of trade updates.
+    pub fn into_stream(self) -> TradeUpdateStream {
+        let inner = futures::stream::unfold(Some(self), |client| async move {
+            let mut client = client?;
+            match client.next_trade_update().await {
+                Ok(update) => Some((Ok(update), Some(client))),
+                Err(e) => Some((Err(e), None)),
+            }
+        });
+        TradeUpdateStream {
+            inner: Box::pin(inner),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Error,
+        env::ApiKey,
+        streaming::{StockProtocol, StockStreamMessage, StockSubscriptionList, StreamingClient},
+    };
+    use futures::{SinkExt, StreamExt};
+    use socketeer::{Message, WebSocketStreamType, get_mock_address, tungstenite};
+    use std::time::Duration;
+
+    async fn handshake(ws: &mut WebSocketStreamType) -> Result<(), tungstenite::Error> {
+        ws.send(Message::text(r#"[{"T":"success","msg":"connected"}]"#))
+            .await?;
+        let _auth_request = ws.next().await;
+        ws.send(Message::text(r#"[{"T":"success","msg":"authenticated"}]"#))
+            .await
+    }
+
+    async fn hold_open(ws: &mut WebSocketStreamType) {
+        while let Some(Ok(message)) = ws.next().await {
+            if message.is_close() {
+                break;
+            }
+        }
+    }
+
+    /// A subscribe issued through the handle is answered while another task
+    /// consumes the stream, and data after the confirmation is delivered to
+    /// that consumer.
+    #[tokio::test]
+    async fn handle_subscribes_while_stream_is_consumed() {
+        async fn confirm_then_trade(
+            mut ws: WebSocketStreamType,
+        ) -> Result<bool, tungstenite::Error> {
+            handshake(&mut ws).await?;
+            let _subscribe_request = ws.next().await;
+            ws.send(Message::text(
+                r#"[{"T":"subscription","trades":["AAPL"]},{"T":"t","S":"AAPL","i":1,"x":"V","p":150.0,"s":100,"t":"2024-01-02T15:30:00Z","z":"C"}]"#,
+            ))
+            .await?;
+            hold_open(&mut ws).await;
+            Ok(true)
+        }
+
+        let address = get_mock_address(confirm_then_trade).await;
+        let url = format!("ws://{address}");
+        let client = StreamingClient::<StockProtocol>::connect(ApiKey::new("k", "s"), &url)
+            .await
+            .expect("handshake succeeds");
+        let (handle, mut stream) = client.into_stream();
+
+        let consumer = tokio::spawn(async move { stream.next().await });
+        let confirmed = tokio::time::timeout(
+            Duration::from_secs(5),
+            handle.add_subscriptions(&StockSubscriptionList::new().add_trades("AAPL")),
+        )
+        .await
+        .expect("subscribe is serviced by the polling consumer")
+        .expect("server confirms the subscription");
+        assert_eq!(confirmed.trades, Some(vec!["AAPL".to_string()]));
+
+        let first = tokio::time::timeout(Duration::from_secs(5), consumer)
+            .await
+            .expect("consumer receives the trade")
+            .unwrap();
+        assert!(
+            matches!(first, Some(Ok(StockStreamMessage::Trade(_)))),
+            "expected a trade, got {first:?}"
+        );
+    }
+
+    /// A closed socket is yielded as one error, after which the stream ends
+    /// rather than repeating the failure forever.
+    #[tokio::test]
+    async fn stream_ends_after_transport_error() {
+        async fn close_after_auth(mut ws: WebSocketStreamType) -> Result<bool, tungstenite::Error> {
+            handshake(&mut ws).await?;
+            ws.close(None).await?;
+            Ok(true)
+        }
+
+        let address = get_mock_address(close_after_auth).await;
+        let url = format!("ws://{address}");
+        let client = StreamingClient::<StockProtocol>::connect(ApiKey::new("k", "s"), &url)
+            .await
+            .expect("handshake succeeds");
+        let (_handle, stream) = client.into_stream();
+
+        let items: Vec<_> = tokio::time::timeout(Duration::from_secs(5), stream.collect())
+            .await
+            .expect("stream terminates");
+        assert_eq!(items.len(), 1, "expected a single terminal item");
+        assert!(
+            matches!(items[0], Err(Error::Websocket(_))),
+            "expected a websocket error, got {:?}",
+            items[0]
+        );
+    }
+}
@@ -4,6 +4,9 @@ use serde::{Deserialize, Serialize};
 use crate::streaming::wire::StreamError;
 
 /// Subscriptions for the crypto streaming feed.
+///
+/// The `add_*` methods take the slashed pair symbol (`BTC/USD`), either as
+/// a string or a [`CryptoPair`](crate::CryptoPair).
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 #[non_exhaustive]
@@ -37,54 +40,54 @@ impl CryptoSubscriptionList {
 
     /// Subscribe to minute bars for `symbol`.
     #[must_use]
-    pub fn add_minute_bars(self, symbol: &str) -> Self {
+    pub fn add_minute_bars(self, symbol: impl AsRef<str>) -> Self {
         Self {
-            bars: Some(append_unique(self.bars, symbol)),
+            bars: Some(append_unique(self.bars, symbol.as_ref())),
             ..self
         }
     }
 
     /// Subscribe to daily bars for `symbol`.
     #[must_use]
-    pub fn add_daily_bars(self, symbol: &str) -> Self {
+    pub fn add_daily_bars(self, symbol: impl AsRef<str>) -> Self {
         Self {
-            daily_bars: Some(append_unique(self.daily_bars, symbol)),
+            daily_bars: Some(append_unique(self.daily_bars, symbol.as_ref())),
             ..self
         }
     }
 
     /// Subscribe to updated bars for `symbol`.
     #[must_use]
-    pub fn add_updated_bars(self, symbol: &str) -> Self {
+    pub fn add_updated_bars(self, symbol: impl AsRef<str>) -> Self {
         Self {
-            updated_bars: Some(append_unique(self.updated_bars, symbol)),
+            updated_bars: Some(append_unique(self.updated_bars, symbol.as_ref())),
             ..self
         }
     }
 
     /// Subscribe to quotes for `symbol`.
     #[must_use]
-    pub fn add_quotes(self, symbol: &str) -> Self {
+    pub fn add_quotes(self, symbol: impl AsRef<str>) -> Self {
         Self {
-            quotes: Some(append_unique(self.quotes, symbol)),
+            quotes: Some(append_unique(self.quotes, symbol.as_ref())),
             ..self
         }
     }
 
     /// Subscribe to trades for `symbol`.
     #[must_use]
-    pub fn add_trades(self, symbol: &str) -> Self {
+    pub fn add_trades(self, symbol: impl AsRef<str>) -> Self {
         Self {
-            trades: Some(append_unique(self.trades, symbol)),
+            trades: Some(append_unique(self.trades, symbol.as_ref())),
             ..self
         }
     }
 
     /// Subscribe to orderbook updates for `symbol`.
     #[must_use]
-    pub fn add_orderbooks(self, symbol: &str) -> Self {
+    pub fn add_orderbooks(self, symbol: impl AsRef<str>) -> Self {
         Self {
-            orderbooks: Some(append_unique(self.orderbooks, symbol)),
+            orderbooks: Some(append_unique(self.orderbooks, symbol.as_ref())),
             ..self
         }
     }
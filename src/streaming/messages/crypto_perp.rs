@@ -0,0 +1,240 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::streaming::wire::StreamError;
+
+pub use super::crypto::{
+    CryptoBarEvent, CryptoBookLevel, CryptoOrderbookEvent, CryptoQuoteEvent, CryptoTradeEvent,
+};
+
+/// Subscriptions for the crypto perpetual futures streaming feed.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct CryptoPerpSubscriptionList {
+    /// Symbols subscribed to minute bars.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bars: Option<Vec<String>>,
+    /// Symbols subscribed to daily bars.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub daily_bars: Option<Vec<String>>,
+    /// Symbols subscribed to updated bars.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_bars: Option<Vec<String>>,
+    /// Symbols subscribed to quotes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quotes: Option<Vec<String>>,
+    /// Symbols subscribed to trades.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trades: Option<Vec<String>>,
+    /// Symbols subscribed to orderbook updates.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub orderbooks: Option<Vec<String>>,
+    /// Symbols subscribed to index/mark price and funding updates.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pricing: Option<Vec<String>>,
+}
+
+impl CryptoPerpSubscriptionList {
+    /// Create an empty subscription list.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Subscribe to minute bars for `symbol`.
+    #[must_use]
+    pub fn add_minute_bars(self, symbol: &str) -> Self {
+        Self {
+            bars: Some(append_unique(self.bars, symbol)),
+            ..self
+        }
+    }
+
+    /// Subscribe to daily bars for `symbol`.
+    #[must_use]
+    pub fn add_daily_bars(self, symbol: &str) -> Self {
+        Self {
+            daily_bars: Some(append_unique(self.daily_bars, symbol)),
+            ..self
+        }
+    }
+
+    /// Subscribe to updated bars for `symbol`.
+    #[must_use]
+    pub fn add_updated_bars(self, symbol: &str) -> Self {
+        Self {
+            updated_bars: Some(append_unique(self.updated_bars, symbol)),
+            ..self
+        }
+    }
+
+    /// Subscribe to quotes for `symbol`.
+    #[must_use]
+    pub fn add_quotes(self, symbol: &str) -> Self {
+        Self {
+            quotes: Some(append_unique(self.quotes, symbol)),
+            ..self
+        }
+    }
+
+    /// Subscribe to trades for `symbol`.
+    #[must_use]
+    pub fn add_trades(self, symbol: &str) -> Self {
+        Self {
+            trades: Some(append_unique(self.trades, symbol)),
+            ..self
+        }
+    }
+
+    /// Subscribe to orderbook updates for `symbol`.
+    #[must_use]
+    pub fn add_orderbooks(self, symbol: &str) -> Self {
+        Self {
+            orderbooks: Some(append_unique(self.orderbooks, symbol)),
+            ..self
+        }
+    }
+
+    /// Subscribe to pricing (index price, mark price, funding rate and
+    /// open interest) updates for `symbol`.
+    #[must_use]
+    pub fn add_pricing(self, symbol: &str) -> Self {
+        Self {
+            pricing: Some(append_unique(self.pricing, symbol)),
+            ..self
+        }
+    }
+}
+
+fn append_unique(list: Option<Vec<String>>, symbol: &str) -> Vec<String> {
+    let mut list = list.unwrap_or_default();
+    if !list.iter().any(|s| s == symbol) {
+        list.push(symbol.to_string());
+    }
+    list
+}
+
+/// Pricing update for a perpetual futures contract.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct CryptoPerpPricingEvent {
+    /// Contract symbol.
+    #[serde(rename = "S")]
+    pub symbol: String,
+    /// Index price of the underlying spot pair.
+    #[serde(rename = "ip")]
+    pub index_price: f64,
+    /// Mark price used for margining and liquidation.
+    #[serde(rename = "mp")]
+    pub mark_price: f64,
+    /// Current funding rate, as a fraction per funding interval.
+    #[serde(rename = "fr")]
+    pub funding_rate: f64,
+    /// Open interest in contracts.
+    #[serde(rename = "oi")]
+    pub open_interest: f64,
+    /// When the next funding payment is exchanged.
+    #[serde(rename = "ft")]
+    pub next_funding_time: DateTime<Utc>,
+    /// Update timestamp.
+    #[serde(rename = "t")]
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Messages received from the crypto perpetual futures streaming feed.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(tag = "T")]
+#[non_exhaustive]
+pub enum CryptoPerpStreamMessage {
+    /// Internally consumed stream acknowledging successful completion of requests.
+    #[serde(rename = "success")]
+    Control {
+        /// The control message payload.
+        msg: crate::streaming::wire::ControlMessage,
+    },
+    /// Error message from the server.
+    #[serde(rename = "error")]
+    Error(StreamError),
+    /// Subscription confirmation with the current subscription list.
+    #[serde(rename = "subscription")]
+    Subscription(CryptoPerpSubscriptionList),
+    /// Minute bar update.
+    #[serde(rename = "b")]
+    Bar(CryptoBarEvent),
+    /// Daily bar update.
+    #[serde(rename = "d")]
+    DailyBar(CryptoBarEvent),
+    /// Updated bar.
+    #[serde(rename = "u")]
+    UpdatedBar(CryptoBarEvent),
+    /// Trade event.
+    #[serde(rename = "t")]
+    Trade(CryptoTradeEvent),
+    /// Quote update.
+    #[serde(rename = "q")]
+    Quote(CryptoQuoteEvent),
+    /// Orderbook update.
+    #[serde(rename = "o")]
+    Orderbook(CryptoOrderbookEvent),
+    /// Index/mark price and funding update.
+    #[serde(rename = "p")]
+    Pricing(CryptoPerpPricingEvent),
+}
+
+impl CryptoPerpStreamMessage {
+    pub(crate) const fn control(&self) -> Option<&crate::streaming::wire::ControlMessage> {
+        match self {
+            CryptoPerpStreamMessage::Control { msg } => Some(msg),
+            _ => None,
+        }
+    }
+
+    pub(crate) const fn stream_error(&self) -> Option<&StreamError> {
+        match self {
+            CryptoPerpStreamMessage::Error(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserializes_pricing_update() {
+        let json = r#"{"T":"p","S":"BTC-PERP","ip":103250.5,"mp":103262.25,"fr":0.0001,"oi":1520.75,"ft":"2026-05-07T16:00:00Z","t":"2026-05-07T13:30:00Z"}"#;
+        match serde_json::from_str(json).unwrap() {
+            CryptoPerpStreamMessage::Pricing(pricing) => {
+                assert_eq!(pricing.symbol, "BTC-PERP");
+                assert_eq!(pricing.mark_price, 103_262.25);
+                assert_eq!(pricing.funding_rate, 0.0001);
+            }
+            other => panic!("expected Pricing, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn deserializes_spot_shaped_events() {
+        let json = r#"[{"T":"t","S":"BTC-PERP","i":1,"p":103250.0,"s":0.5,"tks":"S","t":"2026-05-07T13:30:00Z"},{"T":"o","S":"BTC-PERP","t":"2026-05-07T13:30:00Z","b":[{"p":103250.0,"s":1.0}],"a":[],"r":true}]"#;
+        let messages: Vec<CryptoPerpStreamMessage> = serde_json::from_str(json).unwrap();
+        assert!(
+            matches!(&messages[0], CryptoPerpStreamMessage::Trade(trade) if trade.price == 103_250.0)
+        );
+        assert!(matches!(&messages[1], CryptoPerpStreamMessage::Orderbook(book) if book.reset));
+    }
+
+    #[test]
+    fn pricing_subscription_serializes_alongside_other_channels() {
+        let subscriptions = CryptoPerpSubscriptionList::new()
+            .add_pricing("BTC-PERP")
+            .add_pricing("BTC-PERP")
+            .add_trades("ETH-PERP");
+        let json = serde_json::to_value(&subscriptions).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({"pricing": ["BTC-PERP"], "trades": ["ETH-PERP"]})
+        );
+    }
+}
@@ -6,6 +6,12 @@ pub use stock::{StockStreamMessage, StockSubscriptionList};
 pub mod crypto;
 pub use crypto::{CryptoStreamMessage, CryptoSubscriptionList};
 
+/// Streaming crypto perpetual futures market data message types.
+#[cfg(feature = "crypto-perps")]
+pub mod crypto_perp;
+#[cfg(feature = "crypto-perps")]
+pub use crypto_perp::{CryptoPerpStreamMessage, CryptoPerpSubscriptionList};
+
 /// Streaming news message types.
 pub mod news;
 pub use news::{NewsStreamMessage, NewsSubscriptionList};
@@ -30,18 +30,18 @@ impl OptionSubscriptionList {
 
     /// Subscribe to trades for `symbol`.
     #[must_use]
-    pub fn add_trades(self, symbol: &str) -> Self {
+    pub fn add_trades(self, symbol: impl AsRef<str>) -> Self {
         Self {
-            trades: Some(append_unique(self.trades, symbol)),
+            trades: Some(append_unique(self.trades, symbol.as_ref())),
             ..self
         }
     }
 
     /// Subscribe to quotes for `symbol`. The server rejects `"*"` here.
     #[must_use]
-    pub fn add_quotes(self, symbol: &str) -> Self {
+    pub fn add_quotes(self, symbol: impl AsRef<str>) -> Self {
         Self {
-            quotes: Some(append_unique(self.quotes, symbol)),
+            quotes: Some(append_unique(self.quotes, symbol.as_ref())),
             ..self
         }
     }
@@ -63,7 +63,7 @@ pub struct Listening {
 }
 
 /// Single execution leg reported alongside a multi-leg trade-update event.
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 #[non_exhaustive]
 pub struct TradeUpdateLeg {
     /// Execution ID for this leg.
@@ -95,7 +95,7 @@ pub struct TradeUpdateLeg {
 }
 
 /// Event types delivered on the `trade_updates` stream.
-#[derive(Clone, Copy, Debug, Deserialize, Serialize, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, Eq, PartialEq, Hash)]
 #[serde(rename_all = "snake_case")]
 #[non_exhaustive]
 pub enum TradeUpdateEvent {
@@ -136,7 +136,7 @@ pub enum TradeUpdateEvent {
 }
 
 /// Payload of a `trade_updates` server envelope.
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 #[non_exhaustive]
 pub struct TradeUpdate {
     /// What happened to the order.
@@ -178,6 +178,11 @@ pub struct TradeUpdate {
     pub legs: Option<Vec<TradeUpdateLeg>>,
     /// The order this update applies to.
     pub order: Order,
+    /// `true` when the update was synthesized from the REST API to cover a
+    /// gap in the stream rather than received from the server. Only the
+    /// resilient trade-updates client sets this.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub reconciled: bool,
 }
 
 /// Top-level envelope delivered by the trade-updates stream.
@@ -4,6 +4,8 @@
 /// at [`crate::streaming`]; reach for the deeper path here when you need the
 /// individual event payload structs.
 pub mod messages;
+#[cfg(feature = "crypto-perps")]
+pub use messages::{CryptoPerpStreamMessage, CryptoPerpSubscriptionList};
 pub use messages::{
     CryptoStreamMessage, CryptoSubscriptionList, NewsStreamMessage, NewsSubscriptionList,
     OptionStreamMessage, OptionSubscriptionList, StockStreamMessage, StockSubscriptionList,
@@ -13,15 +15,35 @@ pub use messages::{
 mod wire;
 pub use wire::{ControlMessage, StreamError, StreamErrorCode};
 
+mod health;
+pub use health::{ConnectionHealth, HeartbeatConfig};
+
 mod client;
 pub use client::{StreamProtocol, StreamingClient};
 
+mod message_stream;
+pub use message_stream::{MessageStream, SubscriptionHandle, TradeUpdateStream};
+
+mod buffer;
+pub use buffer::{BackpressurePolicy, BufferConfig, BufferStats};
+
+mod split;
+pub use split::MessageReceiver;
+
+mod broadcast;
+pub use broadcast::{Broadcaster, Subscriber};
+
 mod stock_client;
 pub use stock_client::{StockProtocol, StreamingStockClient};
 
 mod crypto_client;
 pub use crypto_client::{CryptoProtocol, StreamingCryptoClient};
 
+#[cfg(feature = "crypto-perps")]
+mod crypto_perp_client;
+#[cfg(feature = "crypto-perps")]
+pub use crypto_perp_client::{CryptoPerpProtocol, StreamingCryptoPerpClient};
+
 mod news_client;
 pub use news_client::{NewsProtocol, StreamingNewsClient};
 
@@ -30,3 +52,17 @@ pub use option_client::{OptionProtocol, StreamingOptionClient};
 
 mod trading_updates_client;
 pub use trading_updates_client::TradingUpdatesClient;
+
+#[cfg(feature = "restful")]
+mod resilient_trade_updates;
+#[cfg(feature = "restful")]
+pub use resilient_trade_updates::{ReconnectPolicy, ResilientTradingUpdatesClient};
+
+mod source;
+pub use source::MessageSource;
+
+mod recording;
+pub use recording::{Recordable, RecordedFeed, Recording, StreamRecorder};
+
+mod replay;
+pub use replay::{ReplaySpeed, StreamReplay};
@@ -5,6 +5,7 @@ use crate::{
     env::ApiKey,
     streaming::{
         client::{StreamProtocol, StreamProtocolCodec, StreamingClient, sealed},
+        health::HeartbeatConfig,
         messages::{NewsStreamMessage, NewsSubscriptionList},
         wire::{ControlMessage, Request, StreamError},
     },
@@ -63,10 +64,23 @@ impl StreamingNewsClient {
         account_type: AccountType,
         api_key: ApiKey,
     ) -> Result<Self, Error> {
-        let url = match account_type {
-            AccountType::Live => NEWS_LIVE_URL,
-            AccountType::Paper => NEWS_SANDBOX_URL,
-        };
-        Self::connect(api_key, url).await
+        Self::connect(api_key, news_url(account_type)).await
+    }
+
+    /// Connect like [`Self::new_with_credentials`], with explicit liveness
+    /// settings for detecting a stalled connection.
+    pub async fn new_with_heartbeat(
+        account_type: AccountType,
+        api_key: ApiKey,
+        heartbeat: HeartbeatConfig,
+    ) -> Result<Self, Error> {
+        Self::connect_with_heartbeat(api_key, news_url(account_type), heartbeat).await
+    }
+}
+
+const fn news_url(account_type: AccountType) -> &'static str {
+    match account_type {
+        AccountType::Live => NEWS_LIVE_URL,
+        AccountType::Paper => NEWS_SANDBOX_URL,
     }
 }
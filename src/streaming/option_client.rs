@@ -4,7 +4,9 @@ use crate::{
     AccountType, Error, OptionFeed,
     env::ApiKey,
     streaming::{
+        broadcast::{Channel, FanOutProtocol, Topic, push_topics, symbols_for},
         client::{StreamProtocol, StreamProtocolCodec, StreamingClient, sealed},
+        health::HeartbeatConfig,
         messages::{OptionStreamMessage, OptionSubscriptionList},
         wire::{ControlMessage, Request, StreamError},
     },
@@ -44,6 +46,43 @@ impl StreamProtocol for OptionProtocol {
 
 impl StreamProtocolCodec for OptionProtocol {
     type Codec = MsgPackCodec<Vec<OptionStreamMessage>, Request<OptionSubscriptionList>>;
+
+    fn quote_symbol(message: &OptionStreamMessage) -> Option<&str> {
+        match message {
+            OptionStreamMessage::Quote(quote) => Some(&quote.symbol),
+            _ => None,
+        }
+    }
+}
+
+impl FanOutProtocol for OptionProtocol {
+    fn topics(subscriptions: &OptionSubscriptionList) -> Vec<Topic> {
+        let mut topics = Vec::new();
+        push_topics(&mut topics, Channel::Trades, subscriptions.trades.as_ref());
+        push_topics(&mut topics, Channel::Quotes, subscriptions.quotes.as_ref());
+        topics
+    }
+
+    fn subscriptions(topics: &[Topic]) -> OptionSubscriptionList {
+        OptionSubscriptionList {
+            trades: symbols_for(topics, Channel::Trades),
+            quotes: symbols_for(topics, Channel::Quotes),
+        }
+    }
+
+    fn topic(message: &OptionStreamMessage) -> Option<(Channel, &str)> {
+        match message {
+            OptionStreamMessage::Trade(trade) => Some((Channel::Trades, &trade.symbol)),
+            OptionStreamMessage::Quote(quote) => Some((Channel::Quotes, &quote.symbol)),
+            OptionStreamMessage::Control { .. }
+            | OptionStreamMessage::Error(_)
+            | OptionStreamMessage::Subscription(_) => None,
+        }
+    }
+
+    fn error_message(error: StreamError) -> OptionStreamMessage {
+        OptionStreamMessage::Error(error)
+    }
 }
 
 /// Client for streaming real-time options market data over a WebSocket
@@ -67,4 +106,15 @@ impl StreamingOptionClient {
     ) -> Result<Self, Error> {
         Self::connect(api_key, feed.url(account_type)).await
     }
+
+    /// Connect like [`Self::new_with_credentials`], with explicit liveness
+    /// settings for detecting a stalled connection.
+    pub async fn new_with_heartbeat(
+        account_type: AccountType,
+        feed: OptionFeed,
+        api_key: ApiKey,
+        heartbeat: HeartbeatConfig,
+    ) -> Result<Self, Error> {
+        Self::connect_with_heartbeat(api_key, feed.url(account_type), heartbeat).await
+    }
 }
@@ -0,0 +1,270 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use std::path::Path;
+use tokio::{
+    fs::{File, OpenOptions},
+    io::{AsyncWriteExt, BufWriter},
+};
+
+use crate::{
+    Error,
+    streaming::{
+        CryptoStreamMessage, NewsStreamMessage, OptionStreamMessage, StockStreamMessage,
+        TradeUpdate, source::MessageSource,
+    },
+};
+
+mod sealed {
+    /// Sealing supertrait for [`super::Recordable`].
+    pub trait Sealed {}
+}
+
+/// Which feed a recorded message came from. Written alongside every message
+/// so several feeds can share one recording file.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum RecordedFeed {
+    /// [`StockStreamMessage`]s.
+    Stock,
+    /// [`CryptoStreamMessage`]s.
+    Crypto,
+    /// [`CryptoPerpStreamMessage`](crate::streaming::CryptoPerpStreamMessage)s.
+    #[cfg(feature = "crypto-perps")]
+    CryptoPerp,
+    /// [`OptionStreamMessage`]s.
+    Option,
+    /// [`NewsStreamMessage`]s.
+    News,
+    /// [`TradeUpdate`]s.
+    TradeUpdates,
+}
+
+/// A streaming message type that can be written to and read back from a
+/// stream recording.
+///
+/// This trait is sealed and cannot be implemented outside the crate.
+pub trait Recordable: sealed::Sealed + Serialize + DeserializeOwned + Send + 'static {
+    /// The feed tag written with each message of this type.
+    const FEED: RecordedFeed;
+}
+
+macro_rules! recordable {
+    ($($message:ty => $feed:ident),* $(,)?) => {
+        $(
+            impl sealed::Sealed for $message {}
+            impl Recordable for $message {
+                const FEED: RecordedFeed = RecordedFeed::$feed;
+            }
+        )*
+    };
+}
+
+recordable! {
+    StockStreamMessage => Stock,
+    CryptoStreamMessage => Crypto,
+    OptionStreamMessage => Option,
+    NewsStreamMessage => News,
+    TradeUpdate => TradeUpdates,
+}
+
+#[cfg(feature = "crypto-perps")]
+recordable! {
+    crate::streaming::CryptoPerpStreamMessage => CryptoPerp,
+}
+
+/// One line of a recording as written.
+#[derive(Serialize)]
+struct RecordLine<'a, M> {
+    received_at: DateTime<Utc>,
+    feed: RecordedFeed,
+    message: &'a M,
+}
+
+/// Append-only writer for stream recordings.
+///
+/// A recording is a JSON Lines file: one
+/// `{"received_at":…,"feed":…,"message":…}` object per message, in the
+/// order they were received. Messages are stored in their wire shape, so a
+/// recording reads back into the same types that produced it. Appending to
+/// an existing file continues the recording.
+///
+/// File I/O goes through Tokio, so recording never blocks the task that
+/// reads the socket. Writes are buffered; call [`flush`](Self::flush)
+/// before reading a recording back or dropping the recorder, as messages
+/// still buffered at drop are lost.
+#[derive(Debug)]
+pub struct StreamRecorder {
+    writer: BufWriter<File>,
+    recorded: u64,
+}
+
+impl StreamRecorder {
+    /// Open `path` for appending, creating it if it does not exist.
+    pub async fn create(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .await
+            .map_err(Error::RecordingIo)?;
+        Ok(Self {
+            writer: BufWriter::new(file),
+            recorded: 0,
+        })
+    }
+
+    /// Record `message`, stamped with the current time as its receive time.
+    pub async fn record<M: Recordable>(&mut self, message: &M) -> Result<(), Error> {
+        self.record_at(Utc::now(), message).await
+    }
+
+    /// Record `message` with an explicit receive time.
+    pub async fn record_at<M: Recordable>(
+        &mut self,
+        received_at: DateTime<Utc>,
+        message: &M,
+    ) -> Result<(), Error> {
+        let encoded = encode(received_at, message)?;
+        self.write_encoded(&encoded).await
+    }
+
+    async fn write_encoded(&mut self, encoded: &[u8]) -> Result<(), Error> {
+        self.writer
+            .write_all(encoded)
+            .await
+            .map_err(Error::RecordingIo)?;
+        self.recorded += 1;
+        Ok(())
+    }
+
+    /// Number of messages recorded by this recorder.
+    pub fn recorded(&self) -> u64 {
+        self.recorded
+    }
+
+    /// Flush buffered messages to disk.
+    pub async fn flush(&mut self) -> Result<(), Error> {
+        self.writer.flush().await.map_err(Error::RecordingIo)
+    }
+}
+
+/// Serialize one recording line, newline included.
+fn encode<M: Recordable>(received_at: DateTime<Utc>, message: &M) -> Result<Vec<u8>, Error> {
+    let line = RecordLine {
+        received_at,
+        feed: M::FEED,
+        message,
+    };
+    let mut encoded =
+        serde_json::to_vec(&line).map_err(|source| Error::RecordingFormat { line: 0, source })?;
+    encoded.push(b'\n');
+    Ok(encoded)
+}
+
+/// A [`MessageSource`] that records every message it delivers.
+///
+/// Wrap a live client in a `Recording` to capture a session without
+/// changing the consumer: messages are written before they are returned,
+/// errors are passed through unrecorded.
+#[derive(Debug)]
+pub struct Recording<S> {
+    source: S,
+    recorder: StreamRecorder,
+}
+
+impl<S> Recording<S>
+where
+    S: MessageSource + Send,
+    S::Message: Recordable,
+{
+    /// Record everything `source` delivers with `recorder`.
+    pub fn new(source: S, recorder: StreamRecorder) -> Self {
+        Self { source, recorder }
+    }
+
+    /// The recorder, e.g. to [`flush`](StreamRecorder::flush) it.
+    pub fn recorder_mut(&mut self) -> &mut StreamRecorder {
+        &mut self.recorder
+    }
+
+    /// Stop recording, returning the source and recorder.
+    pub fn into_parts(self) -> (S, StreamRecorder) {
+        (self.source, self.recorder)
+    }
+}
+
+impl<S> MessageSource for Recording<S>
+where
+    S: MessageSource + Send,
+    S::Message: Recordable,
+{
+    type Message = S::Message;
+
+    async fn next_message(&mut self) -> Result<Self::Message, Error> {
+        let message = self.source.next_message().await?;
+        // Encode before writing so the message isn't borrowed across the
+        // write, which would require it to be `Sync`.
+        let encoded = encode(Utc::now(), &message)?;
+        self.recorder.write_encoded(&encoded).await?;
+        Ok(message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn scratch_file(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "oxidized_alpaca_{name}_{}.jsonl",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    fn trade(symbol: &str) -> StockStreamMessage {
+        serde_json::from_str(&format!(
+            r#"{{"T":"t","S":"{symbol}","i":1,"x":"V","p":150.0,"s":100,"t":"2024-01-02T15:30:00Z","z":"C"}}"#
+        ))
+        .unwrap()
+    }
+
+    /// Each record is one JSON line carrying its receive time, feed tag and
+    /// wire-shaped message, and reopening the file appends after it.
+    #[tokio::test]
+    async fn writes_one_tagged_line_per_message_and_appends() {
+        let path = scratch_file("recorder_round_trip");
+        let received_at = DateTime::from_timestamp(1_704_209_400, 0).unwrap();
+
+        let mut recorder = StreamRecorder::create(&path).await.unwrap();
+        recorder
+            .record_at(received_at, &trade("AAPL"))
+            .await
+            .unwrap();
+        recorder.flush().await.unwrap();
+        let mut recorder = StreamRecorder::create(&path).await.unwrap();
+        recorder
+            .record_at(received_at, &trade("MSFT"))
+            .await
+            .unwrap();
+        recorder.flush().await.unwrap();
+        assert_eq!(recorder.recorded(), 1);
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<serde_json::Value> = contents
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["received_at"], "2024-01-02T15:30:00Z");
+        assert_eq!(lines[0]["feed"], "stock");
+        assert_eq!(lines[0]["message"]["T"], "t");
+        assert_eq!(lines[0]["message"]["S"], "AAPL");
+        assert_eq!(lines[1]["message"]["S"], "MSFT");
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
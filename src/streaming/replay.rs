@@ -0,0 +1,288 @@
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::{marker::PhantomData, path::Path, time::Duration};
+use tokio::{
+    fs::File,
+    io::{AsyncBufReadExt, BufReader},
+    time::Instant,
+};
+
+use crate::{
+    Error,
+    streaming::{
+        recording::{Recordable, RecordedFeed},
+        source::MessageSource,
+    },
+};
+
+/// How fast a [`StreamReplay`] delivers recorded messages.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[non_exhaustive]
+pub enum ReplaySpeed {
+    /// Keep the recorded gaps between messages.
+    #[default]
+    RealTime,
+    /// Divide the recorded gaps by this factor (`2.0` replays twice as
+    /// fast). Factors that are not positive replay as fast as possible.
+    Accelerated(f64),
+    /// Deliver messages back to back, without waiting.
+    AsFastAsPossible,
+}
+
+impl ReplaySpeed {
+    fn scale(self, gap: Duration) -> Option<Duration> {
+        match self {
+            Self::RealTime => Some(gap),
+            Self::Accelerated(factor) if factor > 0.0 => Some(gap.div_f64(factor)),
+            Self::Accelerated(_) | Self::AsFastAsPossible => None,
+        }
+    }
+}
+
+/// One line of a recording as read back; the message is decoded once its
+/// feed is known to match.
+#[derive(Deserialize)]
+struct RecordLine {
+    received_at: DateTime<Utc>,
+    feed: RecordedFeed,
+    message: serde_json::Value,
+}
+
+/// Plays back the messages of one feed from a recording written by
+/// [`StreamRecorder`](crate::streaming::StreamRecorder).
+///
+/// Messages from other feeds in the same file are skipped. Timing is
+/// relative to the first replayed message, which is delivered immediately;
+/// each later one is held back until its recorded offset (scaled by the
+/// [`ReplaySpeed`]) has elapsed. The replay implements [`MessageSource`],
+/// so consumer code written against a live client runs unchanged; once
+/// the recording is exhausted it reports a closed connection, just as a
+/// live client would on disconnect.
+#[derive(Debug)]
+pub struct StreamReplay<M: Recordable> {
+    reader: BufReader<File>,
+    line: u64,
+    speed: ReplaySpeed,
+    /// Receive time of the first replayed message and when it was replayed.
+    origin: Option<(DateTime<Utc>, Instant)>,
+    _message: PhantomData<fn() -> M>,
+}
+
+impl<M: Recordable> StreamReplay<M> {
+    /// Open the recording at `path` for replay at `speed`.
+    pub async fn open(path: impl AsRef<Path>, speed: ReplaySpeed) -> Result<Self, Error> {
+        let file = File::open(path).await.map_err(Error::RecordingIo)?;
+        Ok(Self {
+            reader: BufReader::new(file),
+            line: 0,
+            speed,
+            origin: None,
+            _message: PhantomData,
+        })
+    }
+
+    /// Wait for and return the next message along with its recorded receive
+    /// time, or `None` at the end of the recording.
+    pub async fn next_record(&mut self) -> Result<Option<(DateTime<Utc>, M)>, Error> {
+        let Some((received_at, message)) = self.read_next().await? else {
+            return Ok(None);
+        };
+        match self.origin {
+            None => self.origin = Some((received_at, Instant::now())),
+            Some((first_received, replay_started)) => {
+                let gap = (received_at - first_received).to_std().unwrap_or_default();
+                if let Some(delay) = self.speed.scale(gap) {
+                    tokio::time::sleep_until(replay_started + delay).await;
+                }
+            }
+        }
+        Ok(Some((received_at, message)))
+    }
+
+    /// Wait for and return the next message. Reports a closed connection at
+    /// the end of the recording.
+    pub async fn next_message(&mut self) -> Result<M, Error> {
+        match self.next_record().await? {
+            Some((_, message)) => Ok(message),
+            None => Err(Error::from(socketeer::Error::WebsocketClosed)),
+        }
+    }
+
+    async fn read_next(&mut self) -> Result<Option<(DateTime<Utc>, M)>, Error> {
+        let mut buffer = String::new();
+        loop {
+            buffer.clear();
+            if self
+                .reader
+                .read_line(&mut buffer)
+                .await
+                .map_err(Error::RecordingIo)?
+                == 0
+            {
+                return Ok(None);
+            }
+            self.line += 1;
+            if buffer.trim().is_empty() {
+                continue;
+            }
+            let format_error = |source| Error::RecordingFormat {
+                line: self.line,
+                source,
+            };
+            let record: RecordLine = serde_json::from_str(&buffer).map_err(format_error)?;
+            if record.feed != M::FEED {
+                continue;
+            }
+            let message = serde_json::from_value(record.message).map_err(format_error)?;
+            return Ok(Some((record.received_at, message)));
+        }
+    }
+}
+
+impl<M: Recordable> MessageSource for StreamReplay<M> {
+    type Message = M;
+
+    async fn next_message(&mut self) -> Result<M, Error> {
+        StreamReplay::next_message(self).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::streaming::{StockStreamMessage, StreamRecorder, TradeUpdate};
+    use std::path::PathBuf;
+
+    fn scratch_file(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "oxidized_alpaca_{name}_{}.jsonl",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    fn at(seconds: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(1_704_209_400 + seconds, 0).unwrap()
+    }
+
+    fn trade(symbol: &str) -> StockStreamMessage {
+        serde_json::from_str(&format!(
+            r#"{{"T":"t","S":"{symbol}","i":1,"x":"V","p":150.0,"s":100,"t":"2024-01-02T15:30:00Z","z":"C"}}"#
+        ))
+        .unwrap()
+    }
+
+    fn fill() -> TradeUpdate {
+        serde_json::from_str(
+            r#"{
+                "event":"fill",
+                "execution_id":"exec-1",
+                "timestamp":"2024-01-02T15:30:01Z",
+                "price":"150.25",
+                "qty":"10",
+                "position_qty":"10",
+                "order":{
+                    "id":"abc",
+                    "client_order_id":"client-abc",
+                    "created_at":"2024-01-02T15:30:00Z",
+                    "updated_at":"2024-01-02T15:30:01Z",
+                    "submitted_at":"2024-01-02T15:30:00Z",
+                    "filled_at":"2024-01-02T15:30:01Z",
+                    "expired_at":null,
+                    "canceled_at":null,
+                    "asset_id":"asset-1",
+                    "symbol":"AAPL",
+                    "qty":"10",
+                    "filled_qty":"10",
+                    "filled_avg_price":"150.25",
+                    "type":"market",
+                    "side":"buy",
+                    "time_in_force":"day",
+                    "status":"filled",
+                    "extended_hours":false,
+                    "order_class":"",
+                    "legs":null
+                }
+            }"#,
+        )
+        .unwrap()
+    }
+
+    fn symbol(message: StockStreamMessage) -> String {
+        match message {
+            StockStreamMessage::Trade(trade) => trade.symbol,
+            other => panic!("expected a trade, got {other:?}"),
+        }
+    }
+
+    /// A mixed-feed recording reads back per feed, in order, with receive
+    /// times intact, and ends like a closed connection.
+    #[tokio::test]
+    async fn replays_each_feed_from_a_shared_recording() {
+        let path = scratch_file("shared_recording");
+        let mut recorder = StreamRecorder::create(&path).await.unwrap();
+        recorder.record_at(at(0), &trade("AAPL")).await.unwrap();
+        recorder.record_at(at(1), &fill()).await.unwrap();
+        recorder.record_at(at(2), &trade("MSFT")).await.unwrap();
+        recorder.flush().await.unwrap();
+        assert_eq!(recorder.recorded(), 3);
+
+        let mut stocks =
+            StreamReplay::<StockStreamMessage>::open(&path, ReplaySpeed::AsFastAsPossible)
+                .await
+                .unwrap();
+        let (received_at, first) = stocks.next_record().await.unwrap().unwrap();
+        assert_eq!((received_at, symbol(first)), (at(0), "AAPL".to_string()));
+        assert_eq!(symbol(stocks.next_message().await.unwrap()), "MSFT");
+        assert!(matches!(
+            stocks.next_message().await,
+            Err(Error::Websocket(_))
+        ));
+
+        let mut updates = StreamReplay::<TradeUpdate>::open(&path, ReplaySpeed::AsFastAsPossible)
+            .await
+            .unwrap();
+        let update = updates.next_message().await.unwrap();
+        assert_eq!(update.price, Some("150.25".parse().unwrap()));
+        assert_eq!(update.order.symbol, "AAPL");
+        assert!(updates.next_record().await.unwrap().is_none());
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn accelerated_replay_scales_recorded_gaps() {
+        let path = scratch_file("accelerated_replay");
+        let mut recorder = StreamRecorder::create(&path).await.unwrap();
+        recorder.record_at(at(0), &trade("AAPL")).await.unwrap();
+        recorder.record_at(at(10), &trade("MSFT")).await.unwrap();
+        recorder.flush().await.unwrap();
+
+        let mut replay =
+            StreamReplay::<StockStreamMessage>::open(&path, ReplaySpeed::Accelerated(4.0))
+                .await
+                .unwrap();
+        let started = Instant::now();
+        replay.next_message().await.unwrap();
+        assert_eq!(started.elapsed(), Duration::ZERO);
+        replay.next_message().await.unwrap();
+        assert_eq!(started.elapsed(), Duration::from_millis(2500));
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn malformed_line_reports_its_position() {
+        let path = scratch_file("malformed_recording");
+        std::fs::write(&path, "\nnot json\n").unwrap();
+        let mut replay = StreamReplay::<StockStreamMessage>::open(&path, ReplaySpeed::RealTime)
+            .await
+            .unwrap();
+        match replay.next_message().await {
+            Err(Error::RecordingFormat { line, .. }) => assert_eq!(line, 2),
+            other => panic!("expected Err(RecordingFormat), got {other:?}"),
+        }
+        let _ = std::fs::remove_file(&path);
+    }
+}
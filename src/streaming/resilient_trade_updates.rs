@@ -0,0 +1,761 @@
+use chrono::{DateTime, TimeDelta, Utc};
+use rust_decimal::Decimal;
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    time::Duration,
+};
+
+use crate::{
+    AccountType, Error, OrderId,
+    env::ApiKey,
+    orders::{Order, OrderStatus},
+    restful::{
+        SortDirection, TradingClient,
+        trading::{
+            account_activities::{Activity, ActivityType, FillType},
+            orders::OrderStatusFilter,
+        },
+    },
+    streaming::{
+        TradingUpdatesClient,
+        health::{ConnectionHealth, HeartbeatConfig},
+        messages::trade_update::{TradeUpdate, TradeUpdateEvent},
+        source::MessageSource,
+    },
+};
+
+macro_rules! info {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        tracing::info!($($arg)*);
+    };
+}
+macro_rules! error {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        tracing::error!($($arg)*);
+    };
+}
+
+/// How far before the last seen event reconciliation starts looking, to
+/// absorb clock skew between this host and Alpaca. Anything already
+/// delivered in the overlap is filtered out again.
+const RECONCILE_OVERLAP: TimeDelta = TimeDelta::seconds(5);
+/// Largest page `list_orders` accepts.
+const RECONCILE_ORDER_LIMIT: usize = 500;
+
+/// Backoff settings for [`ResilientTradingUpdatesClient`] reconnects.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ReconnectPolicy {
+    /// Delay before the first reconnect attempt; doubled after each failure.
+    pub initial_backoff: Duration,
+    /// Upper bound on the delay between attempts.
+    pub max_backoff: Duration,
+    /// Give up after this many consecutive failed attempts. `None` retries
+    /// forever.
+    pub max_attempts: Option<u32>,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(30),
+            max_attempts: None,
+        }
+    }
+}
+
+impl ReconnectPolicy {
+    /// Start from the defaults (1 s doubling up to 30 s, unlimited attempts).
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the delay before the first reconnect attempt.
+    #[must_use]
+    pub fn initial_backoff(mut self, backoff: Duration) -> Self {
+        self.initial_backoff = backoff;
+        self
+    }
+
+    /// Set the upper bound on the delay between attempts.
+    #[must_use]
+    pub fn max_backoff(mut self, backoff: Duration) -> Self {
+        self.max_backoff = backoff;
+        self
+    }
+
+    /// Give up after `attempts` consecutive failures.
+    #[must_use]
+    pub fn max_attempts(mut self, attempts: u32) -> Self {
+        self.max_attempts = Some(attempts);
+        self
+    }
+}
+
+/// Trade-updates client that survives disconnects without losing fills.
+///
+/// When the socket drops (or goes stale), the client reconnects with
+/// backoff, repeats the auth + `listen` handshake, and then asks the REST
+/// API what happened in the meantime: fill activities and orders updated
+/// since the last event it delivered. Anything the stream never reported
+/// is delivered as a synthesized [`TradeUpdate`] with
+/// [`reconciled`](TradeUpdate::reconciled) set, before any new live
+/// events.
+///
+/// Fills are tracked by each order's cumulative filled quantity, so a fill
+/// is delivered exactly once whether it arrives live, through
+/// reconciliation, or both. Synthesized fills carry the order as it stood
+/// at reconciliation time and no execution id.
+#[derive(Debug)]
+pub struct ResilientTradingUpdatesClient {
+    account_type: AccountType,
+    api_key: ApiKey,
+    heartbeat: HeartbeatConfig,
+    policy: ReconnectPolicy,
+    trading: TradingClient,
+    connection: Option<TradingUpdatesClient>,
+    ledger: Ledger,
+    pending: VecDeque<TradeUpdate>,
+    /// Start of a gap that still has to be reconciled.
+    unreconciled_since: Option<DateTime<Utc>>,
+    reconnects: u64,
+}
+
+impl ResilientTradingUpdatesClient {
+    /// Connect for `account_type`, loading credentials from the
+    /// environment.
+    pub async fn new(account_type: AccountType) -> Result<Self, Error> {
+        let api_key = ApiKey::from_env(&account_type)?;
+        Self::new_with_credentials(account_type, api_key).await
+    }
+
+    /// Connect for `account_type` using explicitly supplied credentials,
+    /// with default heartbeat and reconnect settings.
+    pub async fn new_with_credentials(
+        account_type: AccountType,
+        api_key: ApiKey,
+    ) -> Result<Self, Error> {
+        Self::new_with_options(
+            account_type,
+            api_key,
+            HeartbeatConfig::default(),
+            ReconnectPolicy::default(),
+        )
+        .await
+    }
+
+    /// Connect with explicit liveness and reconnect settings. An idle
+    /// timeout in `heartbeat` is what lets a silently dead connection be
+    /// noticed and replaced.
+    pub async fn new_with_options(
+        account_type: AccountType,
+        api_key: ApiKey,
+        heartbeat: HeartbeatConfig,
+        policy: ReconnectPolicy,
+    ) -> Result<Self, Error> {
+        let trading = TradingClient::new_with_credentials(account_type, api_key.clone())?;
+        let connection =
+            TradingUpdatesClient::new_with_heartbeat(account_type, api_key.clone(), heartbeat)
+                .await?;
+        Ok(Self {
+            account_type,
+            api_key,
+            heartbeat,
+            policy,
+            trading,
+            connection: Some(connection),
+            ledger: Ledger::new(Utc::now()),
+            pending: VecDeque::new(),
+            unreconciled_since: None,
+            reconnects: 0,
+        })
+    }
+
+    /// Receive the next trade update, reconnecting and reconciling as
+    /// needed.
+    ///
+    /// Errors are returned when reconnecting runs out of attempts or the
+    /// REST reconciliation fails; calling again resumes where it left off.
+    pub async fn next_trade_update(&mut self) -> Result<TradeUpdate, Error> {
+        loop {
+            if let Some(update) = self.pending.pop_front() {
+                return Ok(update);
+            }
+            if let Some(since) = self.unreconciled_since {
+                let updates = self.reconcile(since).await?;
+                self.unreconciled_since = None;
+                self.pending.extend(updates);
+                continue;
+            }
+            let Some(connection) = self.connection.as_mut() else {
+                self.reconnect().await?;
+                continue;
+            };
+            match connection.next_trade_update().await {
+                Ok(update) => {
+                    if self.ledger.observe(&update) {
+                        return Ok(update);
+                    }
+                }
+                Err(_e) => {
+                    error!("Trade-updates connection lost, reconnecting: {_e}");
+                    self.connection = None;
+                }
+            }
+        }
+    }
+
+    /// Number of times the connection has been re-established.
+    pub fn reconnects(&self) -> u64 {
+        self.reconnects
+    }
+
+    /// Health counters of the current connection, if connected.
+    pub fn health(&self) -> Option<&ConnectionHealth> {
+        self.connection.as_ref().map(TradingUpdatesClient::health)
+    }
+
+    /// Close the WebSocket connection and shut down the client.
+    pub async fn shut_down(self) -> Result<(), Error> {
+        match self.connection {
+            Some(connection) => connection.shut_down().await,
+            None => Ok(()),
+        }
+    }
+
+    async fn reconnect(&mut self) -> Result<(), Error> {
+        let mut backoff = self.policy.initial_backoff;
+        let mut attempts = 0;
+        loop {
+            tokio::time::sleep(backoff).await;
+            attempts += 1;
+            match TradingUpdatesClient::new_with_heartbeat(
+                self.account_type,
+                self.api_key.clone(),
+                self.heartbeat,
+            )
+            .await
+            {
+                Ok(connection) => {
+                    info!("Reconnected to trade-updates stream after {attempts} attempt(s)");
+                    self.connection = Some(connection);
+                    self.reconnects += 1;
+                    self.unreconciled_since = Some(self.ledger.watermark - RECONCILE_OVERLAP);
+                    return Ok(());
+                }
+                Err(e) => {
+                    error!("Trade-updates reconnect attempt {attempts} failed: {e}");
+                    if self.policy.max_attempts.is_some_and(|max| attempts >= max) {
+                        return Err(e);
+                    }
+                    backoff = (backoff * 2).min(self.policy.max_backoff);
+                }
+            }
+        }
+    }
+
+    /// Fetch what happened since `since` and turn whatever the stream
+    /// missed into reconciled updates.
+    async fn reconcile(&mut self, since: DateTime<Utc>) -> Result<Vec<TradeUpdate>, Error> {
+        let mut orders = list_orders_since(&self.trading, since, RECONCILE_ORDER_LIMIT).await?;
+        let fills = self
+            .trading
+            .list_activities()
+            .activity_type(ActivityType::Fill)
+            .after(since)
+            .direction(SortDirection::Asc)
+            .execute()
+            .await?;
+
+        // Fills can belong to orders submitted before the gap, which the
+        // `after` filter on orders leaves out.
+        let mut known: HashSet<OrderId> = HashSet::new();
+        collect_ids(&orders, &mut known);
+        let mut missing: Vec<OrderId> = fills
+            .iter()
+            .filter_map(|fill| fill.order_id.clone())
+            .filter(|id| !known.contains(id))
+            .collect();
+        missing.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+        missing.dedup();
+        for id in missing {
+            orders.push(self.trading.get_order(&id).await?);
+        }
+
+        let updates = self.ledger.reconcile(&orders, &fills, since);
+        info!("Reconciled {} missed trade update(s)", updates.len());
+        Ok(updates)
+    }
+}
+
+impl MessageSource for ResilientTradingUpdatesClient {
+    type Message = TradeUpdate;
+
+    async fn next_message(&mut self) -> Result<TradeUpdate, Error> {
+        self.next_trade_update().await
+    }
+}
+
+/// Every order submitted after `since`, oldest first, following the
+/// `after_order_id` cursor until a short page ends the listing.
+async fn list_orders_since(
+    trading: &TradingClient,
+    since: DateTime<Utc>,
+    page_size: usize,
+) -> Result<Vec<Order>, Error> {
+    let mut orders: Vec<Order> = Vec::new();
+    loop {
+        let request = trading
+            .list_orders()
+            .status(OrderStatusFilter::All)
+            .direction(SortDirection::Asc)
+            .nested(true)
+            .limit(page_size);
+        let request = match orders.last() {
+            Some(last) => request.after_order_id(last.id.clone()),
+            None => request.after(since),
+        };
+        let page = request.execute().await?;
+        let exhausted = page.len() < page_size;
+        orders.extend(page);
+        if exhausted {
+            return Ok(orders);
+        }
+    }
+}
+
+fn collect_ids(orders: &[Order], ids: &mut HashSet<OrderId>) {
+    for order in orders {
+        ids.insert(order.id.clone());
+        collect_ids(&order.legs, ids);
+    }
+}
+
+/// What has already been delivered, so live and reconciled events can be
+/// merged without duplicates.
+#[derive(Debug)]
+struct Ledger {
+    /// Cumulative filled quantity already reported per order.
+    filled: HashMap<OrderId, Decimal>,
+    /// Non-fill events already reported per order.
+    events: HashMap<OrderId, HashSet<TradeUpdateEvent>>,
+    /// Orders that reached a terminal status, with the time they did, in
+    /// the order they were seen.
+    finished: VecDeque<(DateTime<Utc>, OrderId)>,
+    /// Latest event time seen, or the connect time before any event.
+    watermark: DateTime<Utc>,
+}
+
+impl Ledger {
+    fn new(connected_at: DateTime<Utc>) -> Self {
+        Self {
+            filled: HashMap::new(),
+            events: HashMap::new(),
+            finished: VecDeque::new(),
+            watermark: connected_at,
+        }
+    }
+
+    /// Record `update`, returning `false` if it repeats something already
+    /// delivered.
+    fn observe(&mut self, update: &TradeUpdate) -> bool {
+        self.watermark = self.watermark.max(update.timestamp);
+        self.evict_finished();
+        let id = &update.order.id;
+        let fresh = match update.event {
+            TradeUpdateEvent::Fill | TradeUpdateEvent::PartialFill => {
+                let reported = self.filled.get(id).copied().unwrap_or_default();
+                if update.order.filled_qty <= reported {
+                    return false;
+                }
+                self.filled.insert(id.clone(), update.order.filled_qty);
+                true
+            }
+            event => self.events.entry(id.clone()).or_default().insert(event),
+        };
+        if fresh && update.order.status.is_terminal() {
+            self.finished.push_back((update.timestamp, id.clone()));
+        }
+        fresh
+    }
+
+    /// Forget orders that finished before the reconciliation overlap
+    /// window. No later reconciliation looks back far enough to report
+    /// them again, so keeping them would only grow the ledger.
+    fn evict_finished(&mut self) {
+        let cutoff = self.watermark - RECONCILE_OVERLAP;
+        while let Some((finished_at, _)) = self.finished.front()
+            && *finished_at < cutoff
+        {
+            let (_, id) = self.finished.pop_front().expect("front was just checked");
+            self.filled.remove(&id);
+            self.events.remove(&id);
+        }
+    }
+
+    /// Synthesize the updates the stream missed, oldest first.
+    fn reconcile(
+        &mut self,
+        orders: &[Order],
+        fills: &[Activity],
+        since: DateTime<Utc>,
+    ) -> Vec<TradeUpdate> {
+        let mut by_id: HashMap<&OrderId, &Order> = HashMap::new();
+        let mut stack: Vec<&Order> = orders.iter().collect();
+        while let Some(order) = stack.pop() {
+            by_id.insert(&order.id, order);
+            stack.extend(&order.legs);
+        }
+
+        let mut updates = Vec::new();
+        for fill in fills {
+            let (Some(id), Some(cum_qty)) = (&fill.order_id, fill.cum_qty) else {
+                continue;
+            };
+            let Some(order) = by_id.get(id) else {
+                continue;
+            };
+            let complete = fill.transaction_type == Some(FillType::Fill)
+                || fill.leaves_qty == Some(Decimal::ZERO);
+            let mut snapshot = (*order).clone();
+            snapshot.filled_qty = cum_qty;
+            updates.push(reconciled(
+                if complete {
+                    TradeUpdateEvent::Fill
+                } else {
+                    TradeUpdateEvent::PartialFill
+                },
+                fill.transaction_time.unwrap_or(since),
+                fill.price,
+                fill.qty,
+                snapshot,
+            ));
+        }
+
+        for order in by_id.values() {
+            match order.status {
+                OrderStatus::Filled | OrderStatus::PartiallyFilled => {
+                    // Normally covered by fill activities; if the activity
+                    // feed lagged, report the outstanding quantity from the
+                    // order itself.
+                    let reported = updates
+                        .iter()
+                        .filter(|u| u.order.id == order.id)
+                        .map(|u| u.order.filled_qty)
+                        .chain(self.filled.get(&order.id).copied())
+                        .max()
+                        .unwrap_or_default();
+                    if order.filled_qty > reported {
+                        let event = if order.status == OrderStatus::Filled {
+                            TradeUpdateEvent::Fill
+                        } else {
+                            TradeUpdateEvent::PartialFill
+                        };
+                        updates.push(reconciled(
+                            event,
+                            order.filled_at.or(order.updated_at).unwrap_or(since),
+                            order.filled_avg_price,
+                            Some(order.filled_qty - reported),
+                            (*order).clone(),
+                        ));
+                    }
+                }
+                status => {
+                    if let Some(event) = status_event(status) {
+                        updates.push(reconciled(
+                            event,
+                            order.updated_at.unwrap_or(order.created_at),
+                            None,
+                            None,
+                            (*order).clone(),
+                        ));
+                    }
+                }
+            }
+        }
+
+        updates.sort_by_key(|update| update.timestamp);
+        updates.retain(|update| self.observe(update));
+        updates
+    }
+}
+
+/// The trade-update event that leaves an order in `status`, for statuses
+/// not reached through a fill.
+fn status_event(status: OrderStatus) -> Option<TradeUpdateEvent> {
+    Some(match status {
+        OrderStatus::New => TradeUpdateEvent::New,
+        OrderStatus::Accepted => TradeUpdateEvent::Accepted,
+        OrderStatus::PendingNew => TradeUpdateEvent::PendingNew,
+        OrderStatus::Canceled => TradeUpdateEvent::Canceled,
+        OrderStatus::PendingCancel => TradeUpdateEvent::PendingCancel,
+        OrderStatus::Replaced => TradeUpdateEvent::Replaced,
+        OrderStatus::PendingReplace => TradeUpdateEvent::PendingReplace,
+        OrderStatus::Expired => TradeUpdateEvent::Expired,
+        OrderStatus::Rejected => TradeUpdateEvent::Rejected,
+        OrderStatus::Stopped => TradeUpdateEvent::Stopped,
+        OrderStatus::Suspended => TradeUpdateEvent::Suspended,
+        OrderStatus::DoneForDay => TradeUpdateEvent::DoneForDay,
+        OrderStatus::Calculated => TradeUpdateEvent::Calculated,
+        _ => return None,
+    })
+}
+
+fn reconciled(
+    event: TradeUpdateEvent,
+    timestamp: DateTime<Utc>,
+    price: Option<Decimal>,
+    qty: Option<Decimal>,
+    order: Order,
+) -> TradeUpdate {
+    TradeUpdate {
+        event,
+        execution_id: None,
+        timestamp,
+        price,
+        qty,
+        position_qty: None,
+        at: None,
+        event_id: None,
+        legs: None,
+        order,
+        reconciled: true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serial_test::serial;
+    use std::env;
+    use wiremock::matchers::{method, path, query_param, query_param_is_missing};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn paper_client() -> TradingClient {
+        unsafe {
+            if env::var("ALPACA_PAPER_API_KEY_ID").is_err() {
+                env::set_var("ALPACA_PAPER_API_KEY_ID", "test_key_id");
+            }
+            if env::var("ALPACA_PAPER_API_SECRET_KEY").is_err() {
+                env::set_var("ALPACA_PAPER_API_SECRET_KEY", "test_secret_key");
+            }
+        }
+        TradingClient::new(AccountType::Paper).unwrap()
+    }
+
+    fn at(seconds: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(1_704_209_400 + seconds, 0).unwrap()
+    }
+
+    fn order(id: &str, status: &str, qty: &str, filled_qty: &str, updated: i64) -> Order {
+        serde_json::from_value(order_json(id, status, qty, filled_qty, updated)).unwrap()
+    }
+
+    fn order_json(
+        id: &str,
+        status: &str,
+        qty: &str,
+        filled_qty: &str,
+        updated: i64,
+    ) -> serde_json::Value {
+        serde_json::json!({
+            "id": id,
+            "client_order_id": format!("client-{id}"),
+            "created_at": at(0),
+            "updated_at": at(updated),
+            "submitted_at": at(0),
+            "filled_at": null,
+            "expired_at": null,
+            "canceled_at": null,
+            "asset_id": "asset-1",
+            "symbol": "AAPL",
+            "qty": qty,
+            "filled_qty": filled_qty,
+            "filled_avg_price": "150",
+            "type": "market",
+            "side": "buy",
+            "time_in_force": "day",
+            "status": status,
+            "extended_hours": false
+        })
+    }
+
+    fn fill_activity(
+        order_id: &str,
+        qty: &str,
+        cum_qty: &str,
+        leaves: &str,
+        time: i64,
+    ) -> Activity {
+        serde_json::from_value(serde_json::json!({
+            "id": format!("{time}::{order_id}"),
+            "activity_type": "FILL",
+            "transaction_time": at(time),
+            "type": if leaves == "0" { "fill" } else { "partial_fill" },
+            "price": "150",
+            "qty": qty,
+            "side": "buy",
+            "symbol": "AAPL",
+            "leaves_qty": leaves,
+            "order_id": order_id,
+            "cum_qty": cum_qty,
+            "order_status": if leaves == "0" { "filled" } else { "partially_filled" }
+        }))
+        .unwrap()
+    }
+
+    fn live(event: TradeUpdateEvent, order: Order, time: i64) -> TradeUpdate {
+        TradeUpdate {
+            event,
+            execution_id: None,
+            timestamp: at(time),
+            price: None,
+            qty: None,
+            position_qty: None,
+            at: None,
+            event_id: None,
+            legs: None,
+            order,
+            reconciled: false,
+        }
+    }
+
+    #[test]
+    fn observe_drops_repeated_fills_and_events() {
+        let mut ledger = Ledger::new(at(0));
+        let partial = live(
+            TradeUpdateEvent::PartialFill,
+            order("a", "partially_filled", "10", "4", 1),
+            1,
+        );
+        assert!(ledger.observe(&partial));
+        assert!(!ledger.observe(&partial), "same cumulative qty is a repeat");
+        let new = live(TradeUpdateEvent::New, order("a", "new", "10", "0", 0), 0);
+        assert!(ledger.observe(&new));
+        assert!(!ledger.observe(&new));
+        assert_eq!(ledger.watermark, at(1));
+    }
+
+    /// Fills the stream already delivered are skipped, missed ones are
+    /// synthesized in time order and flagged, and a late live copy of a
+    /// reconciled fill is suppressed.
+    #[test]
+    fn reconcile_reports_only_what_the_stream_missed() {
+        let mut ledger = Ledger::new(at(0));
+        assert!(ledger.observe(&live(
+            TradeUpdateEvent::PartialFill,
+            order("a", "partially_filled", "10", "4", 1),
+            1,
+        )));
+
+        let orders = vec![
+            order("a", "filled", "10", "10", 5),
+            order("b", "canceled", "5", "0", 4),
+        ];
+        let fills = vec![
+            fill_activity("a", "4", "4", "6", 1),
+            fill_activity("a", "6", "10", "0", 5),
+        ];
+        let updates = ledger.reconcile(&orders, &fills, at(0));
+
+        let summary: Vec<_> = updates
+            .iter()
+            .map(|u| (u.order.id.as_str(), u.event, u.qty))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("b", TradeUpdateEvent::Canceled, None),
+                ("a", TradeUpdateEvent::Fill, Some(Decimal::from(6))),
+            ]
+        );
+        assert!(updates.iter().all(|u| u.reconciled));
+
+        let late_copy = live(
+            TradeUpdateEvent::Fill,
+            order("a", "filled", "10", "10", 5),
+            5,
+        );
+        assert!(!ledger.observe(&late_copy));
+        assert!(
+            ledger.reconcile(&orders, &fills, at(0)).is_empty(),
+            "reconciling the same window twice reports nothing new"
+        );
+    }
+
+    #[test]
+    fn reconcile_falls_back_to_order_when_fill_activity_lags() {
+        let mut ledger = Ledger::new(at(0));
+        let updates = ledger.reconcile(&[order("a", "filled", "10", "10", 3)], &[], at(0));
+        assert_eq!(updates.len(), 1);
+        assert_eq!(updates[0].event, TradeUpdateEvent::Fill);
+        assert_eq!(updates[0].qty, Some(Decimal::from(10)));
+        assert_eq!(updates[0].price, Some(Decimal::from(150)));
+    }
+
+    /// A terminal order is forgotten once the watermark has moved past the
+    /// overlap window; open orders are kept.
+    #[test]
+    fn observe_evicts_orders_finished_before_the_overlap() {
+        let mut ledger = Ledger::new(at(0));
+        assert!(ledger.observe(&live(
+            TradeUpdateEvent::Fill,
+            order("a", "filled", "10", "10", 1),
+            1,
+        )));
+        assert!(ledger.observe(&live(
+            TradeUpdateEvent::New,
+            order("b", "new", "5", "0", 2),
+            2,
+        )));
+        assert!(ledger.filled.contains_key(&OrderId::from("a")));
+
+        assert!(ledger.observe(&live(
+            TradeUpdateEvent::New,
+            order("c", "new", "5", "0", 60),
+            60,
+        )));
+        assert!(!ledger.filled.contains_key(&OrderId::from("a")));
+        assert!(ledger.finished.is_empty());
+        assert!(ledger.events.contains_key(&OrderId::from("b")));
+    }
+
+    /// Reconciliation keeps paging through orders with the
+    /// `after_order_id` cursor until a short page comes back.
+    #[tokio::test]
+    #[serial]
+    async fn order_listing_follows_the_cursor_past_a_full_page() {
+        let server = MockServer::start().await;
+        let page = |ids: &[&str]| {
+            let orders: Vec<_> = ids
+                .iter()
+                .map(|id| order_json(id, "new", "1", "0", 1))
+                .collect();
+            ResponseTemplate::new(200).set_body_json(orders)
+        };
+        Mock::given(method("GET"))
+            .and(path("/v2/orders"))
+            .and(query_param_is_missing("after_order_id"))
+            .and(query_param("limit", "2"))
+            .respond_with(page(&["a", "b"]))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v2/orders"))
+            .and(query_param("after_order_id", "b"))
+            .respond_with(page(&["c"]))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let trading = paper_client().with_base_url(&server.uri());
+        let orders = list_orders_since(&trading, at(0), 2).await.unwrap();
+        let ids: Vec<_> = orders.iter().map(|o| o.id.as_str()).collect();
+        assert_eq!(ids, ["a", "b", "c"]);
+    }
+}
@@ -0,0 +1,47 @@
+use std::future::Future;
+
+use crate::{
+    Error,
+    streaming::{
+        TradeUpdate, TradingUpdatesClient,
+        client::{StreamProtocol, StreamProtocolCodec, StreamingClient},
+    },
+};
+
+/// Anything that yields streaming messages one at a time: a live
+/// [`StreamingClient`], a [`TradingUpdatesClient`], a
+/// [`StreamReplay`](crate::streaming::StreamReplay) of a recorded session,
+/// or a [`Recording`](crate::streaming::Recording) wrapped around any of
+/// them.
+///
+/// Writing consumer code against `MessageSource` lets the same strategy run
+/// live and against a replayed day.
+pub trait MessageSource {
+    /// The message type delivered by this source.
+    type Message;
+
+    /// Receive the next message. Errors have the same meaning as on
+    /// [`StreamingClient::next_message`]; a source that has run dry reports
+    /// a closed connection.
+    fn next_message(&mut self) -> impl Future<Output = Result<Self::Message, Error>> + Send;
+}
+
+#[allow(
+    private_bounds,
+    reason = "See StreamingClient — bound is sealed on purpose."
+)]
+impl<P: StreamProtocol + StreamProtocolCodec> MessageSource for StreamingClient<P> {
+    type Message = P::Message;
+
+    fn next_message(&mut self) -> impl Future<Output = Result<Self::Message, Error>> + Send {
+        StreamingClient::next_message(self)
+    }
+}
+
+impl MessageSource for TradingUpdatesClient {
+    type Message = TradeUpdate;
+
+    fn next_message(&mut self) -> impl Future<Output = Result<Self::Message, Error>> + Send {
+        self.next_trade_update()
+    }
+}
@@ -0,0 +1,343 @@
+use crate::{
+    Error,
+    streaming::{
+        buffer::{BufferConfig, BufferReceiver, BufferSender, BufferStats, buffer},
+        client::{StreamProtocol, StreamProtocolCodec, StreamingClient},
+        message_stream::{ClientState, Step, SubscriptionHandle, is_terminal},
+    },
+};
+use futures::Stream;
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// Receiving half of a split streaming client, produced by
+/// [`StreamingClient::split`].
+///
+/// A background task owns the socket: it reads frames, services requests
+/// from the paired [`SubscriptionHandle`]s and forwards every message here.
+/// Items match [`StreamingClient::next_message`]; after a transport failure
+/// or [`Error::StreamingStale`] the task exits and the receiver yields
+/// `None`. Dropping the receiver stops the task and closes the connection.
+///
+/// Messages wait in a bounded buffer between the task and the receiver; see
+/// [`BufferConfig`] for what happens when the receiver falls behind.
+#[allow(
+    private_bounds,
+    reason = "See StreamingClient — bound is sealed on purpose."
+)]
+pub struct MessageReceiver<P: StreamProtocol + StreamProtocolCodec> {
+    messages: BufferReceiver<P>,
+    handle: SubscriptionHandle<P>,
+}
+
+impl<P: StreamProtocol + StreamProtocolCodec> std::fmt::Debug for MessageReceiver<P> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MessageReceiver").finish_non_exhaustive()
+    }
+}
+
+#[allow(
+    private_bounds,
+    reason = "See StreamingClient — bound is sealed on purpose."
+)]
+impl<P: StreamProtocol + StreamProtocolCodec> MessageReceiver<P> {
+    /// Receive the next message, or `None` once the connection has ended.
+    pub async fn recv(&mut self) -> Option<Result<P::Message, Error>> {
+        std::future::poll_fn(|cx| self.messages.poll_recv(cx)).await
+    }
+
+    /// Counters for the receive buffer: how full it is and how many
+    /// messages the overflow policy has dropped or conflated.
+    pub fn buffer_stats(&self) -> BufferStats {
+        self.messages.stats()
+    }
+
+    /// Close the WebSocket connection and stop the background task.
+    /// Outstanding [`SubscriptionHandle`]s fail from then on.
+    pub async fn shut_down(self) -> Result<(), Error> {
+        self.handle.request_shutdown().await
+    }
+}
+
+impl<P: StreamProtocol + StreamProtocolCodec> Stream for MessageReceiver<P> {
+    type Item = Result<P::Message, Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.messages.poll_recv(cx)
+    }
+}
+
+#[allow(
+    private_bounds,
+    reason = "See StreamingClient — bound is sealed on purpose."
+)]
+impl<P: StreamProtocol + StreamProtocolCodec> StreamingClient<P> {
+    /// Split the client into a cloneable [`SubscriptionHandle`] and a
+    /// [`MessageReceiver`], handing the socket to a background task.
+    ///
+    /// Unlike [`into_stream`](Self::into_stream), subscription requests are
+    /// answered even while nobody is reading messages, so one task can
+    /// consume the feed while others add and remove symbols. Subscription
+    /// confirmations and rejections are routed back to the handle that made
+    /// the request rather than delivered to the receiver.
+    ///
+    /// Must be called from within a Tokio runtime.
+    ///
+    /// Buffers up to 8192 messages and stops reading the socket while the
+    /// buffer is full, still answering subscription requests meanwhile;
+    /// use [`split_with_buffer`](Self::split_with_buffer) to choose a
+    /// different size or overflow policy.
+    pub fn split(self) -> (SubscriptionHandle<P>, MessageReceiver<P>) {
+        self.split_with_buffer(BufferConfig::default())
+    }
+
+    /// Split like [`split`](Self::split), with an explicit receive buffer
+    /// size and overflow policy.
+    pub fn split_with_buffer(
+        self,
+        buffer_config: BufferConfig,
+    ) -> (SubscriptionHandle<P>, MessageReceiver<P>) {
+        let (handle, mut state) = ClientState::new(self);
+        state.set_pending_capacity(buffer_config.capacity);
+        let (tx, messages) = buffer(buffer_config);
+        tokio::spawn(drive(state, tx));
+        let receiver = MessageReceiver {
+            messages,
+            handle: handle.clone(),
+        };
+        (handle, receiver)
+    }
+}
+
+/// Background loop behind [`StreamingClient::split`].
+async fn drive<P: StreamProtocol + StreamProtocolCodec>(
+    mut state: ClientState<P>,
+    messages: BufferSender<P>,
+) {
+    loop {
+        let step = tokio::select! {
+            step = state.next_step() => step,
+            () = messages.closed() => {
+                // Receiver dropped: nobody is listening any more.
+                let _ = state.shut_down().await;
+                return;
+            }
+        };
+        match step {
+            Step::Message(message) => {
+                messages.record_dropped(state.take_pending_dropped());
+                let terminal = message.as_ref().is_err_and(is_terminal);
+                let mut push = std::pin::pin!(messages.push(message));
+                let pushed = loop {
+                    // While a full buffer holds the push back, keep answering
+                    // handles: a caller awaiting a subscription confirmation
+                    // may be the one that would otherwise drain the receiver.
+                    let command = tokio::select! {
+                        biased;
+                        pushed = &mut push => break pushed,
+                        command = state.next_command() => command,
+                    };
+                    let shutdown = state.handle_command(command).await;
+                    messages.record_dropped(state.take_pending_dropped());
+                    if let Some(reply) = shutdown {
+                        let _ = reply.send(state.shut_down().await);
+                        return;
+                    }
+                };
+                if pushed.is_err() {
+                    // Receiver dropped: nobody is listening any more.
+                    let _ = state.shut_down().await;
+                    return;
+                }
+                if terminal {
+                    return;
+                }
+            }
+            Step::Shutdown(reply) => {
+                let _ = reply.send(state.shut_down().await);
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Error,
+        env::ApiKey,
+        streaming::{
+            BufferConfig, StockProtocol, StockStreamMessage, StockSubscriptionList,
+            StreamErrorCode, StreamingClient,
+        },
+    };
+    use futures::{SinkExt, StreamExt};
+    use socketeer::{Message, WebSocketStreamType, get_mock_address, tungstenite};
+    use std::time::Duration;
+
+    /// Handshake, confirm the first subscribe, reject the second, then push a
+    /// trade and hold the socket open.
+    async fn confirm_then_reject(mut ws: WebSocketStreamType) -> Result<bool, tungstenite::Error> {
+        ws.send(Message::text(r#"[{"T":"success","msg":"connected"}]"#))
+            .await?;
+        let _auth_request = ws.next().await;
+        ws.send(Message::text(r#"[{"T":"success","msg":"authenticated"}]"#))
+            .await?;
+        let _first_subscribe = ws.next().await;
+        ws.send(Message::text(r#"[{"T":"subscription","quotes":["AAPL"]}]"#))
+            .await?;
+        let _second_subscribe = ws.next().await;
+        ws.send(Message::text(
+            r#"[{"T":"error","code":405,"msg":"symbol limit exceeded"}]"#,
+        ))
+        .await?;
+        ws.send(Message::text(
+            r#"[{"T":"t","S":"AAPL","i":1,"x":"V","p":150.0,"s":100,"t":"2024-01-02T15:30:00Z","z":"C"}]"#,
+        ))
+        .await?;
+        while let Some(Ok(message)) = ws.next().await {
+            if message.is_close() {
+                break;
+            }
+        }
+        Ok(true)
+    }
+
+    /// Subscription requests are answered by the background task without
+    /// anyone reading the receiver, rejections go back to the requesting
+    /// handle, and data still reaches the receiver afterwards.
+    #[tokio::test]
+    async fn split_routes_replies_to_the_requesting_handle() {
+        let address = get_mock_address(confirm_then_reject).await;
+        let url = format!("ws://{address}");
+        let client = StreamingClient::<StockProtocol>::connect(ApiKey::new("k", "s"), &url)
+            .await
+            .expect("handshake succeeds");
+        let (handle, mut receiver) = client.split();
+
+        let confirmed = tokio::time::timeout(
+            Duration::from_secs(5),
+            handle.add_subscriptions(&StockSubscriptionList::new().add_quotes("AAPL")),
+        )
+        .await
+        .expect("subscribe answered without polling the receiver")
+        .expect("first subscribe is confirmed");
+        assert_eq!(confirmed.quotes, Some(vec!["AAPL".to_string()]));
+        assert_eq!(
+            handle.current_subscriptions().quotes,
+            Some(vec!["AAPL".to_string()])
+        );
+
+        let other = handle.clone();
+        let rejected = tokio::spawn(async move {
+            other
+                .add_subscriptions(&StockSubscriptionList::new().add_quotes("MSFT"))
+                .await
+        });
+        match tokio::time::timeout(Duration::from_secs(5), rejected)
+            .await
+            .expect("rejection is routed back")
+            .unwrap()
+        {
+            Err(Error::StreamingSubscribe(err)) => {
+                assert_eq!(err.code, StreamErrorCode::SymbolLimitExceeded);
+            }
+            other => panic!("expected Err(StreamingSubscribe), got {other:?}"),
+        }
+
+        let message = tokio::time::timeout(Duration::from_secs(5), receiver.recv())
+            .await
+            .expect("receiver gets the trade");
+        assert!(
+            matches!(message, Some(Ok(StockStreamMessage::Trade(_)))),
+            "expected a trade, got {message:?}"
+        );
+
+        // The mock server hangs up without completing the close handshake,
+        // so only check that shutdown returns, not that it was clean.
+        let _ = tokio::time::timeout(Duration::from_secs(5), receiver.shut_down())
+            .await
+            .expect("shutdown completes");
+        assert!(
+            handle
+                .add_subscriptions(&StockSubscriptionList::new())
+                .await
+                .is_err(),
+            "handles fail once the connection is shut down"
+        );
+    }
+
+    fn trade(id: u64) -> String {
+        format!(
+            r#"{{"T":"t","S":"AAPL","i":{id},"x":"V","p":150.0,"s":100,"t":"2024-01-02T15:30:00Z","z":"C"}}"#
+        )
+    }
+
+    /// Send two trades in one frame, then answer the subscribe with two more
+    /// trades ahead of the confirmation.
+    async fn burst_then_confirm(mut ws: WebSocketStreamType) -> Result<bool, tungstenite::Error> {
+        ws.send(Message::text(r#"[{"T":"success","msg":"connected"}]"#))
+            .await?;
+        let _auth_request = ws.next().await;
+        ws.send(Message::text(r#"[{"T":"success","msg":"authenticated"}]"#))
+            .await?;
+        ws.send(Message::text(format!("[{},{}]", trade(1), trade(2))))
+            .await?;
+        let _subscribe = ws.next().await;
+        ws.send(Message::text(format!(
+            r#"[{},{},{{"T":"subscription","trades":["AAPL"]}}]"#,
+            trade(3),
+            trade(4)
+        )))
+        .await?;
+        while let Some(Ok(message)) = ws.next().await {
+            if message.is_close() {
+                break;
+            }
+        }
+        Ok(true)
+    }
+
+    /// A subscribe made while the reader is blocked on a full buffer is
+    /// still answered, and data arriving ahead of its confirmation is held
+    /// within the configured capacity.
+    #[tokio::test]
+    async fn full_buffer_still_answers_subscriptions() {
+        let address = get_mock_address(burst_then_confirm).await;
+        let url = format!("ws://{address}");
+        let client = StreamingClient::<StockProtocol>::connect(ApiKey::new("k", "s"), &url)
+            .await
+            .expect("handshake succeeds");
+        let (handle, mut receiver) = client.split_with_buffer(BufferConfig::new().capacity(1));
+
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while receiver.buffer_stats().buffered < 1 {
+                tokio::task::yield_now().await;
+            }
+        })
+        .await
+        .expect("first trade is buffered");
+
+        let confirmed = tokio::time::timeout(
+            Duration::from_secs(5),
+            handle.add_subscriptions(&StockSubscriptionList::new().add_trades("AAPL")),
+        )
+        .await
+        .expect("subscribe answered while the buffer is full")
+        .expect("subscribe is confirmed");
+        assert_eq!(confirmed.trades, Some(vec!["AAPL".to_string()]));
+
+        let mut ids = Vec::new();
+        for _ in 0..3 {
+            match tokio::time::timeout(Duration::from_secs(5), receiver.recv()).await {
+                Ok(Some(Ok(StockStreamMessage::Trade(trade)))) => ids.push(trade.trade_id),
+                other => panic!("expected a trade, got {other:?}"),
+            }
+        }
+        // Trade 3 was held ahead of the confirmation and displaced by trade 4.
+        assert_eq!(ids, [1, 2, 4]);
+        assert_eq!(receiver.buffer_stats().dropped, 1);
+    }
+}
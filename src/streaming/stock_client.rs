@@ -4,7 +4,9 @@ use crate::{
     AccountType, Error, StreamingFeed,
     env::ApiKey,
     streaming::{
+        broadcast::{Channel, FanOutProtocol, Topic, push_topics, symbols_for},
         client::{StreamProtocol, StreamProtocolCodec, StreamingClient, sealed},
+        health::HeartbeatConfig,
         messages::{StockStreamMessage, StockSubscriptionList},
         wire::{ControlMessage, Request, StreamError},
     },
@@ -41,6 +43,83 @@ impl StreamProtocol for StockProtocol {
 
 impl StreamProtocolCodec for StockProtocol {
     type Codec = JsonCodec<Vec<StockStreamMessage>, Request<StockSubscriptionList>>;
+
+    fn quote_symbol(message: &StockStreamMessage) -> Option<&str> {
+        match message {
+            StockStreamMessage::Quote(quote) => Some(&quote.symbol),
+            _ => None,
+        }
+    }
+}
+
+impl FanOutProtocol for StockProtocol {
+    fn topics(subscriptions: &StockSubscriptionList) -> Vec<Topic> {
+        let mut topics = Vec::new();
+        push_topics(&mut topics, Channel::Trades, subscriptions.trades.as_ref());
+        push_topics(&mut topics, Channel::Quotes, subscriptions.quotes.as_ref());
+        push_topics(&mut topics, Channel::Bars, subscriptions.bars.as_ref());
+        push_topics(
+            &mut topics,
+            Channel::DailyBars,
+            subscriptions.daily_bars.as_ref(),
+        );
+        push_topics(
+            &mut topics,
+            Channel::UpdatedBars,
+            subscriptions.updated_bars.as_ref(),
+        );
+        push_topics(
+            &mut topics,
+            Channel::Statuses,
+            subscriptions.statuses.as_ref(),
+        );
+        push_topics(&mut topics, Channel::Lulds, subscriptions.lulds.as_ref());
+        push_topics(
+            &mut topics,
+            Channel::Imbalances,
+            subscriptions.imbalances.as_ref(),
+        );
+        topics
+    }
+
+    fn subscriptions(topics: &[Topic]) -> StockSubscriptionList {
+        StockSubscriptionList {
+            trades: symbols_for(topics, Channel::Trades),
+            quotes: symbols_for(topics, Channel::Quotes),
+            bars: symbols_for(topics, Channel::Bars),
+            daily_bars: symbols_for(topics, Channel::DailyBars),
+            updated_bars: symbols_for(topics, Channel::UpdatedBars),
+            statuses: symbols_for(topics, Channel::Statuses),
+            lulds: symbols_for(topics, Channel::Lulds),
+            imbalances: symbols_for(topics, Channel::Imbalances),
+        }
+    }
+
+    fn topic(message: &StockStreamMessage) -> Option<(Channel, &str)> {
+        match message {
+            StockStreamMessage::Trade(trade) => Some((Channel::Trades, &trade.symbol)),
+            StockStreamMessage::Correction(correction) => {
+                Some((Channel::Trades, &correction.symbol))
+            }
+            StockStreamMessage::CancelError(cancel) => Some((Channel::Trades, &cancel.symbol)),
+            StockStreamMessage::Quote(quote) => Some((Channel::Quotes, &quote.symbol)),
+            StockStreamMessage::Bar(bar) => Some((Channel::Bars, &bar.symbol)),
+            StockStreamMessage::DailyBar(bar) => Some((Channel::DailyBars, &bar.symbol)),
+            StockStreamMessage::UpdatedBar(bar) => Some((Channel::UpdatedBars, &bar.symbol)),
+            StockStreamMessage::TradingStatus(status) => Some((Channel::Statuses, &status.symbol)),
+            StockStreamMessage::Luld(luld) => Some((Channel::Lulds, &luld.symbol)),
+            StockStreamMessage::Imbalance(imbalance) => {
+                Some((Channel::Imbalances, &imbalance.symbol))
+            }
+            StockStreamMessage::Control { .. }
+            | StockStreamMessage::Error(_)
+            | StockStreamMessage::Subscription(_) => None,
+        }
+    }
+
+    fn error_message(error: StreamError) -> StockStreamMessage {
+        StockStreamMessage::Error(error)
+    }
 }
 
 /// Client for streaming real-time stock market data over a WebSocket connection.
@@ -63,4 +142,15 @@ impl StreamingStockClient {
     ) -> Result<Self, Error> {
         Self::connect(api_key, feed.url(account_type)).await
     }
+
+    /// Connect like [`Self::new_with_credentials`], with explicit liveness
+    /// settings for detecting a stalled connection.
+    pub async fn new_with_heartbeat(
+        account_type: AccountType,
+        feed: StreamingFeed,
+        api_key: ApiKey,
+        heartbeat: HeartbeatConfig,
+    ) -> Result<Self, Error> {
+        Self::connect_with_heartbeat(api_key, feed.url(account_type), heartbeat).await
+    }
 }
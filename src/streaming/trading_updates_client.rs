@@ -4,9 +4,12 @@ use std::collections::VecDeque;
 use crate::{
     AccountType, Error,
     env::ApiKey,
-    streaming::messages::trade_update::{
-        AuthorizationStatus, ListenStreams, TradeUpdate, TradingUpdatesMessage,
-        TradingUpdatesRequest,
+    streaming::{
+        health::{ConnectionHealth, HealthMonitor, HeartbeatConfig},
+        messages::trade_update::{
+            AuthorizationStatus, ListenStreams, TradeUpdate, TradingUpdatesMessage,
+            TradingUpdatesRequest,
+        },
     },
 };
 
@@ -38,6 +41,7 @@ type TradingUpdatesSocket = Socketeer<JsonCodec<TradingUpdatesMessage, TradingUp
 pub struct TradingUpdatesClient {
     websocket: TradingUpdatesSocket,
     queue: VecDeque<TradeUpdate>,
+    health: HealthMonitor,
 }
 
 impl TradingUpdatesClient {
@@ -54,12 +58,32 @@ impl TradingUpdatesClient {
     pub async fn new_with_credentials(
         account_type: AccountType,
         api_key: ApiKey,
+    ) -> Result<Self, Error> {
+        Self::new_with_heartbeat(account_type, api_key, HeartbeatConfig::default()).await
+    }
+
+    /// Connect like [`Self::new_with_credentials`], with explicit liveness
+    /// settings for detecting a stalled connection.
+    pub async fn new_with_heartbeat(
+        account_type: AccountType,
+        api_key: ApiKey,
+        heartbeat: HeartbeatConfig,
     ) -> Result<Self, Error> {
         let url = match account_type {
             AccountType::Live => TRADING_UPDATES_LIVE_URL,
             AccountType::Paper => TRADING_UPDATES_PAPER_URL,
         };
-        let mut websocket = TradingUpdatesSocket::connect(url).await?;
+        Self::connect(api_key, url, heartbeat).await
+    }
+
+    /// Connect to `url` and complete the auth + `listen` handshake.
+    pub(crate) async fn connect(
+        api_key: ApiKey,
+        url: &str,
+        heartbeat: HeartbeatConfig,
+    ) -> Result<Self, Error> {
+        let mut websocket =
+            TradingUpdatesSocket::connect_with(url, heartbeat.connect_options()).await?;
 
         websocket
             .send(TradingUpdatesRequest::Auth {
@@ -82,13 +106,7 @@ impl TradingUpdatesClient {
             }
         }
 
-        websocket
-            .send(TradingUpdatesRequest::Listen {
-                data: ListenStreams {
-                    streams: vec!["trade_updates".to_string()],
-                },
-            })
-            .await?;
+        websocket.send(Self::listen_request()).await?;
         match Self::recv(&mut websocket).await? {
             TradingUpdatesMessage::Listening(_) => {
                 info!("Subscribed to trade_updates");
@@ -101,17 +119,22 @@ impl TradingUpdatesClient {
         Ok(Self {
             websocket,
             queue: VecDeque::new(),
+            health: HealthMonitor::new(heartbeat),
         })
     }
 
     /// Receive the next trade-update event. Drops handshake-level envelopes
     /// the server may resend.
+    ///
+    /// With an idle timeout configured via [`HeartbeatConfig`], a connection
+    /// that stops answering returns [`Error::StreamingStale`] instead of
+    /// waiting forever.
     pub async fn next_trade_update(&mut self) -> Result<TradeUpdate, Error> {
         loop {
             if let Some(update) = self.queue.pop_front() {
                 return Ok(update);
             }
-            match Self::recv(&mut self.websocket).await? {
+            match self.recv_monitored().await? {
                 TradingUpdatesMessage::TradeUpdate(update) => self.queue.push_back(update),
                 TradingUpdatesMessage::Authorization(_) | TradingUpdatesMessage::Listening(_) => {
                     // re-emitted handshake envelopes; ignore.
@@ -120,12 +143,57 @@ impl TradingUpdatesClient {
         }
     }
 
+    /// Connection health counters: when the last frame arrived, how many
+    /// liveness probes were sent and how long the last one took to answer.
+    pub fn health(&self) -> &ConnectionHealth {
+        self.health.stats()
+    }
+
     /// Close the WebSocket connection and shut down the client.
     pub async fn shut_down(self) -> Result<(), Error> {
         self.websocket.close_connection().await?;
         Ok(())
     }
 
+    fn listen_request() -> TradingUpdatesRequest {
+        TradingUpdatesRequest::Listen {
+            data: ListenStreams {
+                streams: vec!["trade_updates".to_string()],
+            },
+        }
+    }
+
+    /// Read the next envelope, probing the server with a repeated `listen`
+    /// once the connection has been idle for the configured timeout. The
+    /// server answers with a `listening` envelope, which
+    /// [`Self::next_trade_update`] already discards.
+    async fn recv_monitored(&mut self) -> Result<TradingUpdatesMessage, Error> {
+        loop {
+            let received = match self.health.deadline() {
+                None => Self::recv(&mut self.websocket).await,
+                Some(deadline) => {
+                    tokio::select! {
+                        received = Self::recv(&mut self.websocket) => received,
+                        () = tokio::time::sleep_until(deadline) => {
+                            match self.health.deadline_elapsed() {
+                                Ok(()) => {}
+                                Err(stale) => {
+                                    error!("Trade-updates connection is stale: {stale}");
+                                    return Err(stale);
+                                }
+                            }
+                            self.websocket.send(Self::listen_request()).await?;
+                            continue;
+                        }
+                    }
+                }
+            };
+            let message = received?;
+            self.health.record_frame();
+            return Ok(message);
+        }
+    }
+
     async fn recv(socket: &mut TradingUpdatesSocket) -> Result<TradingUpdatesMessage, Error> {
         socket.next_message().await.map_err(|e| {
             error!("Error retrieving next message: {e:?}");
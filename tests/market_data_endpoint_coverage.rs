@@ -209,7 +209,7 @@ async fn market_data_endpoints_live_smoke() {
 
     // Options market data
     let chain = expect_ok_or_status(
-        client.option_chain("AAPL").await,
+        client.option_chain("AAPL").limit(100).execute().await,
         &[403, 404, 422],
         "option_chain",
     )
@@ -234,19 +234,43 @@ async fn market_data_endpoints_live_smoke() {
     );
 
     let _ = expect_ok_or_status(
-        client.option_latest_trades(&[&option_symbol]).await,
+        client
+            .option_trades(&[&option_symbol])
+            .start(window_start)
+            .end(window_end)
+            .limit(1)
+            .execute()
+            .await,
+        &[403, 404, 422],
+        "option_trades",
+    );
+
+    let _ = expect_ok_or_status(
+        client.option_latest_bars(&[&option_symbol]).execute().await,
+        &[403, 404, 422],
+        "option_latest_bars",
+    );
+
+    let _ = expect_ok_or_status(
+        client
+            .option_latest_trades(&[&option_symbol])
+            .execute()
+            .await,
         &[403, 404, 422],
         "option_latest_trades",
     );
 
     let _ = expect_ok_or_status(
-        client.option_latest_quotes(&[&option_symbol]).await,
+        client
+            .option_latest_quotes(&[&option_symbol])
+            .execute()
+            .await,
         &[403, 404, 422],
         "option_latest_quotes",
     );
 
     let _ = expect_ok_or_status(
-        client.option_snapshots(&[&option_symbol]).await,
+        client.option_snapshots(&[&option_symbol]).execute().await,
         &[403, 404, 422],
         "option_snapshots",
     );
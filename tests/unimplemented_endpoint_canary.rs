@@ -8,14 +8,16 @@
 //! returns an empty list, so the endpoints appear to be an unfinished spec
 //! entry rather than a live surface.
 //!
-//! Rather than periodically re-checking by hand, this test asserts the
-//! endpoints are still absent. It **fails when they start working**, which
-//! surfaces through the scheduled compatibility workflow as an issue. When
-//! that happens the fix is to implement the endpoints (a branch already
-//! exists) and delete this file.
+//! The crate implements them behind the opt-in `crypto-perps` feature,
+//! written against the spec and mock fixtures only. Rather than periodically
+//! re-checking by hand, this test asserts the endpoints are still absent. It
+//! **fails when they start working**, which surfaces through the scheduled
+//! compatibility workflow as an issue. When that happens, check the
+//! `crypto-perps` response types against real payloads, add live coverage
+//! for them, and delete this file.
 //!
-//! Deliberately uses raw HTTP rather than the crate: the whole point is to
-//! probe a surface the crate does not implement.
+//! Deliberately uses raw HTTP rather than the crate, so the probe does not
+//! depend on the feature being enabled or on the response types matching.
 #![cfg(feature = "restful")]
 
 const PERP_ENDPOINTS: &[&str] = &[
@@ -59,9 +61,10 @@ async fn crypto_perps_endpoints_are_still_unavailable() {
 
     assert!(
         now_available.is_empty(),
-        "Alpaca now serves crypto perpetual futures endpoints that this crate \
-         does not implement:\n  {}\n\nImplement them (see the crypto_perps \
-         work) and delete tests/unimplemented_endpoint_canary.rs.",
+        "Alpaca now serves crypto perpetual futures endpoints:\n  {}\n\n\
+         The crypto-perps feature was written against mock fixtures; verify \
+         its response types against live payloads and delete \
+         tests/unimplemented_endpoint_canary.rs.",
         now_available.join("\n  "),
     );
 }